@@ -0,0 +1,408 @@
+use std::{
+    env, fmt,
+    path::{Path, PathBuf},
+    process,
+    str::FromStr,
+};
+
+use family_banking_core::{
+    backup,
+    config::{self, Args},
+    db_operations, export, format_decimal,
+    model::{Currency, Member},
+    Error,
+};
+use rusqlite::Connection;
+use serde_json::{json, Value};
+
+/// Needed when the group has an admin password, as
+/// the command line has no login screen
+const PASSWORD_ENV_VAR: &str = "FAMILY_BANKING_PASSWORD";
+
+const EXIT_FAILURE: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+
+const USAGE: &str = "\
+Usage: family_banking-cli [--db <file> | --new <file>] [--json] <command>
+
+Commands:
+  member list
+  member add <name> <shares>
+  member edit <id> [--name <name>] [--shares <shares>]
+  member delete <id>
+  loan lend <id> <amount> [--interest <amount>]
+  loan repay <id> <amount>
+  summary
+  export <file>
+  backup
+
+  --db <file>   Use this group file instead of the default one
+  --new <file>  Create a new group file and use it
+  --json        Print the result as JSON
+
+If the group has an admin password it has to be given in the
+FAMILY_BANKING_PASSWORD environment variable.
+
+Exits with 1 if the command fails and 2 if it is not understood.";
+
+enum CliError {
+    Usage(String),
+    Failed(String),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(reason) | CliError::Failed(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl From<Error> for CliError {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Usage(rule) => CliError::Usage(rule.to_string()),
+            _ => CliError::Failed(err.to_string()),
+        }
+    }
+}
+
+/// The result of a command, as text for people and JSON for scripts
+struct Output {
+    text: String,
+    json: Value,
+}
+
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let json = take_flag(&mut args, "--json");
+
+    match run(args) {
+        Ok(output) => {
+            if json {
+                println!("{}", output.json)
+            } else {
+                println!("{}", output.text)
+            }
+        }
+        Err(err) => {
+            if json {
+                eprintln!("{}", json!({ "error": err.to_string() }))
+            } else if let CliError::Usage(_) = err {
+                eprintln!("{}\n\n{}", err, USAGE)
+            } else {
+                eprintln!("{}", err)
+            }
+
+            process::exit(match err {
+                CliError::Usage(_) => EXIT_USAGE,
+                CliError::Failed(_) => EXIT_FAILURE,
+            })
+        }
+    }
+}
+
+fn run(mut args: Vec<String>) -> Result<Output, CliError> {
+    let database = take_option(&mut args, "--db")?.map(PathBuf::from);
+    let new_database = take_option(&mut args, "--new")?.map(PathBuf::from);
+
+    if database.is_some() && new_database.is_some() {
+        return Err(CliError::Usage(
+            "Only one of --db and --new can be given".to_string(),
+        ));
+    }
+
+    if args.is_empty() || take_flag(&mut args, "--help") || take_flag(&mut args, "-h") {
+        return Ok(Output {
+            text: USAGE.to_string(),
+            json: json!({ "usage": USAGE }),
+        });
+    }
+
+    let (_, conn) = config::open_database(&Args {
+        create: new_database.is_some(),
+        database: database.or(new_database),
+        ..Args::default()
+    })?;
+
+    check_password(&conn)?;
+    backup::update_db_schema(&conn)?;
+
+    let currency = db_operations::currency(&conn)?;
+    let command: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    match command.as_slice() {
+        ["member", "list"] => member_list(&conn, &currency),
+        ["member", "add", name, shares] => member_add(
+            &conn,
+            &currency,
+            name,
+            parse_amount(&currency, shares, "shares")?,
+        ),
+        ["member", "edit", id, ..] => {
+            let id = parse(id, "member id")?;
+            let mut options: Vec<String> = args[3..].to_vec();
+            let name = take_option(&mut options, "--name")?;
+            let shares = match take_option(&mut options, "--shares")? {
+                Some(shares) => Some(parse_amount(&currency, &shares, "shares")?),
+                None => None,
+            };
+
+            no_more_args(&options)?;
+            member_edit(&conn, &currency, id, name, shares)
+        }
+        ["member", "delete", id] => member_delete(&conn, &currency, parse(id, "member id")?),
+        ["loan", "lend", id, amount, ..] => {
+            let (id, amount) = (
+                parse(id, "member id")?,
+                parse_amount(&currency, amount, "amount")?,
+            );
+            let mut options: Vec<String> = args[4..].to_vec();
+            let interest = match take_option(&mut options, "--interest")? {
+                Some(interest) => parse_amount(&currency, &interest, "interest")?,
+                None => 0.0,
+            };
+
+            no_more_args(&options)?;
+            loan_lend(&conn, &currency, id, amount, interest)
+        }
+        ["loan", "repay", id, amount] => {
+            let (id, amount) = (
+                parse(id, "member id")?,
+                parse_amount(&currency, amount, "amount")?,
+            );
+
+            loan_repay(&conn, &currency, id, amount)
+        }
+        ["summary"] => summary(&conn, &currency),
+        ["export", path] => {
+            export::export_to_file(&conn, Path::new(path))?;
+
+            Ok(Output {
+                text: format!("Exported to {}", path),
+                json: json!({ "exported": path }),
+            })
+        }
+        ["backup"] => {
+            let path = backup::create_backup(&conn)?;
+
+            Ok(Output {
+                text: format!("Backed up to {}", path.display()),
+                json: json!({ "backup": path }),
+            })
+        }
+        _ => Err(CliError::Usage(format!(
+            "Unknown command \"{}\"",
+            args.join(" ")
+        ))),
+    }
+}
+
+/// Removes `flag` from `args`, returning whether it was there
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let found = args.iter().any(|arg| arg == flag);
+
+    args.retain(|arg| arg != flag);
+    found
+}
+
+/// Removes `option` and the value after it from `args`
+fn take_option(args: &mut Vec<String>, option: &str) -> Result<Option<String>, CliError> {
+    match args.iter().position(|arg| arg == option) {
+        Some(i) if i + 1 < args.len() => {
+            let value = args.remove(i + 1);
+
+            args.remove(i);
+            Ok(Some(value))
+        }
+        Some(_) => Err(CliError::Usage(format!("{} needs a value", option))),
+        None => Ok(None),
+    }
+}
+
+fn no_more_args(args: &[String]) -> Result<(), CliError> {
+    match args.first() {
+        Some(arg) => Err(CliError::Usage(format!("Unknown argument \"{}\"", arg))),
+        None => Ok(()),
+    }
+}
+
+fn parse<T: FromStr>(value: &str, what: &str) -> Result<T, CliError> {
+    value
+        .parse()
+        .map_err(|_| CliError::Usage(format!("\"{}\" is not a valid {}", value, what)))
+}
+
+/// Reads an amount written the way the group writes money
+fn parse_amount(currency: &Currency, value: &str, what: &str) -> Result<f64, CliError> {
+    currency
+        .parse(value)
+        .ok_or_else(|| CliError::Usage(format!("\"{}\" is not a valid {}", value, what)))
+}
+
+fn check_password(conn: &Connection) -> Result<(), CliError> {
+    match db_operations::get_password(conn)? {
+        Some(hash) => match env::var(PASSWORD_ENV_VAR) {
+            Ok(password) if db_operations::verify_password(&hash, &password) => Ok(()),
+            Ok(_) => Err(CliError::Failed("Incorrect password".to_string())),
+            Err(_) => Err(CliError::Failed(format!(
+                "This group has a password, set it in {}",
+                PASSWORD_ENV_VAR
+            ))),
+        },
+        None => Ok(()),
+    }
+}
+
+fn find_member(conn: &Connection, id: i32) -> Result<Member, CliError> {
+    db_operations::members(conn)?
+        .into_iter()
+        .find(|user| user.id == id)
+        .ok_or_else(|| Error::NotFound(format!("Member {}", id)).into())
+}
+
+fn member_json(user: &Member) -> Value {
+    json!({
+        "id": user.id,
+        "name": user.name,
+        "shares": user.shares,
+        "percent": user.percent,
+        "loan": user.loan,
+        "interest": user.interest,
+    })
+}
+
+fn member_line(currency: &Currency, user: &Member) -> String {
+    format!(
+        "{:>4}  {:<20}  {:>12}  {:>6}%  {:>12}  {:>12}",
+        user.id,
+        user.name,
+        currency.format_number(user.shares),
+        format_decimal(user.percent),
+        currency.format_number(user.loan),
+        currency.format_number(user.interest)
+    )
+}
+
+fn member_output(currency: &Currency, verb: &str, user: &Member) -> Output {
+    Output {
+        text: format!("{} {}\n{}", verb, user.name, member_line(currency, user)),
+        json: member_json(user),
+    }
+}
+
+fn member_list(conn: &Connection, currency: &Currency) -> Result<Output, CliError> {
+    let users = db_operations::members(conn)?;
+    let mut lines = vec![format!(
+        "{:>4}  {:<20}  {:>12}  {:>7}  {:>12}  {:>12}",
+        "ID",
+        "Name",
+        format!("Shares ({})", currency.symbol),
+        "Percent",
+        format!("Loan ({})", currency.symbol),
+        format!("Interest ({})", currency.symbol)
+    )];
+
+    lines.extend(users.iter().map(|user| member_line(currency, user)));
+
+    Ok(Output {
+        text: lines.join("\n"),
+        json: Value::Array(users.iter().map(member_json).collect()),
+    })
+}
+
+fn member_add(
+    conn: &Connection,
+    currency: &Currency,
+    name: &str,
+    shares: f64,
+) -> Result<Output, CliError> {
+    let id = db_operations::store_new_user(conn, name.to_string(), shares)?;
+
+    Ok(member_output(currency, "Added", &find_member(conn, id)?))
+}
+
+fn member_edit(
+    conn: &Connection,
+    currency: &Currency,
+    id: i32,
+    name: Option<String>,
+    shares: Option<f64>,
+) -> Result<Output, CliError> {
+    let user = find_member(conn, id)?;
+
+    db_operations::edit_user(
+        conn,
+        id,
+        name.unwrap_or(user.name),
+        shares.unwrap_or(user.shares),
+    )?;
+
+    Ok(member_output(currency, "Edited", &find_member(conn, id)?))
+}
+
+fn member_delete(conn: &Connection, currency: &Currency, id: i32) -> Result<Output, CliError> {
+    let user = find_member(conn, id)?;
+
+    backup::create_snapshot(conn, "delete")?;
+    db_operations::delete_user(conn, id)?;
+
+    Ok(member_output(currency, "Deleted", &user))
+}
+
+fn loan_lend(
+    conn: &Connection,
+    currency: &Currency,
+    id: i32,
+    amount: f64,
+    interest: f64,
+) -> Result<Output, CliError> {
+    db_operations::borrow_debt(conn, id, amount, interest)?;
+
+    Ok(member_output(currency, "Lent to", &find_member(conn, id)?))
+}
+
+fn loan_repay(
+    conn: &Connection,
+    currency: &Currency,
+    id: i32,
+    amount: f64,
+) -> Result<Output, CliError> {
+    db_operations::repay(conn, id, amount)?;
+
+    Ok(member_output(
+        currency,
+        "Repayment from",
+        &find_member(conn, id)?,
+    ))
+}
+
+fn summary(conn: &Connection, currency: &Currency) -> Result<Output, CliError> {
+    let data = db_operations::summary(conn)?;
+    let totals = [
+        ("Available cash", data.total_cash),
+        ("Outstanding debt", data.total_debt),
+        ("Total shares", data.total_shares),
+        ("Profit", data.profit),
+        ("Expenses", data.total_expenses),
+        ("Other income", data.total_income),
+    ];
+
+    Ok(Output {
+        text: totals
+            .iter()
+            .map(|(label, value)| format!("{:<18}{:>16}", label, currency.format(*value)))
+            .collect::<Vec<String>>()
+            .join("\n"),
+        json: json!({
+            "total_cash": data.total_cash,
+            "total_debt": data.total_debt,
+            "total_loans": data.total_loans,
+            "total_shares": data.total_shares,
+            "profit": data.profit,
+            "total_expenses": data.total_expenses,
+            "total_income": data.total_income,
+            "members": db_operations::members(conn)?.len(),
+        }),
+    })
+}
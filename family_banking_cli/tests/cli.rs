@@ -0,0 +1,137 @@
+//! Runs the binary on group files in the temp folder and checks
+//! what it prints and the code it exits with
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+use serde_json::Value;
+
+/// A folder of its own for each test, emptied first
+fn test_folder(test: &str) -> PathBuf {
+    let folder = env::temp_dir().join("family_banking_cli-tests").join(test);
+
+    let _ = fs::remove_dir_all(&folder);
+    fs::create_dir_all(&folder).unwrap();
+    folder
+}
+
+fn cli(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_family_banking-cli"))
+        .args(args)
+        .env_remove("FAMILY_BANKING_PASSWORD")
+        .env_remove("FAMILY_BANKING_DB")
+        .output()
+        .unwrap()
+}
+
+/// Runs a command with `--json` on the group file at `db`
+fn cli_json(db: &Path, args: &[&str]) -> (Option<i32>, Value) {
+    let db = db.to_str().unwrap();
+    let output = cli(&[&["--db", db, "--json"], args].concat());
+    let printed = if output.status.success() {
+        output.stdout
+    } else {
+        output.stderr
+    };
+
+    (
+        output.status.code(),
+        serde_json::from_slice(&printed).unwrap(),
+    )
+}
+
+#[test]
+fn a_new_group_file_can_be_created_and_used() {
+    let db = test_folder("new_group").join("group.store");
+    let db_arg = db.to_str().unwrap();
+
+    let output = cli(&["--new", db_arg, "--json", "member", "add", "Ann", "500"]);
+    let added: Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert!(db.is_file());
+    assert_eq!(added["id"], 1);
+    assert_eq!(added["name"], "Ann");
+    assert_eq!(added["shares"], 500.0);
+
+    let (code, added) = cli_json(&db, &["member", "add", "Ben", "300"]);
+
+    assert_eq!(code, Some(0));
+    assert_eq!(added["id"], 2);
+    assert_eq!(added["name"], "Ben");
+
+    let (code, lent) = cli_json(&db, &["loan", "lend", "1", "200", "--interest", "20"]);
+
+    assert_eq!(code, Some(0));
+    assert_eq!(lent["loan"], 200.0);
+    assert_eq!(lent["interest"], 20.0);
+
+    let (code, members) = cli_json(&db, &["member", "list"]);
+
+    assert_eq!(code, Some(0));
+    assert_eq!(members.as_array().unwrap().len(), 2);
+    assert_eq!(members[1]["name"], "Ben");
+
+    let (code, summary) = cli_json(&db, &["summary"]);
+
+    assert_eq!(code, Some(0));
+    assert_eq!(summary["members"], 2);
+    assert_eq!(summary["total_shares"], 800.0);
+    assert_eq!(summary["total_debt"], 220.0);
+}
+
+#[test]
+fn a_command_that_fails_exits_with_1() {
+    let db = test_folder("failure").join("group.store");
+
+    cli(&["--new", db.to_str().unwrap(), "member", "add", "Ann", "500"]);
+
+    let (code, error) = cli_json(&db, &["loan", "repay", "1", "50"]);
+
+    assert_eq!(code, Some(1));
+    assert_eq!(error["error"], "Repayment is higher than debt");
+
+    let (code, error) = cli_json(&db, &["member", "edit", "7", "--shares", "10"]);
+
+    assert_eq!(code, Some(1));
+    assert_eq!(error["error"], "Member 7 could not be found");
+}
+
+#[test]
+fn a_command_that_is_not_understood_exits_with_2() {
+    let folder = test_folder("usage");
+    let db = folder.join("group.store");
+    let db_arg = db.to_str().unwrap();
+
+    cli(&["--new", db_arg, "summary"]);
+
+    let (code, error) = cli_json(&db, &["member", "fly"]);
+
+    assert_eq!(code, Some(2));
+    assert_eq!(error["error"], "Unknown command \"member fly\"");
+
+    let (code, _) = cli_json(&db, &["member", "add", "Ann", "lots"]);
+
+    assert_eq!(code, Some(2));
+
+    // A mistyped path is not turned into a new group
+    let (code, _) = cli_json(&folder.join("missing.store"), &["summary"]);
+
+    assert_eq!(code, Some(2));
+    assert!(!folder.join("missing.store").exists());
+
+    // Nor is an existing group replaced by a new one
+    let output = cli(&["--new", db_arg, "summary"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(
+        String::from_utf8_lossy(&output.stderr).starts_with(&format!("{} already exists", db_arg))
+    );
+
+    let output = cli(&["--db", db_arg, "--new", db_arg, "summary"]);
+
+    assert_eq!(output.status.code(), Some(2));
+}
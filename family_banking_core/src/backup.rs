@@ -0,0 +1,326 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use rusqlite::{backup::Backup, Connection, DatabaseName, OpenFlags};
+
+use crate::{db_operations, Error, Result};
+
+pub const DEFAULT_BACKUP_FOLDER: &str = "./backups";
+const DEFAULT_BACKUP_FOLDER_NAME: &str = "backups";
+pub const DEFAULT_BACKUP_RETENTION: usize = 10;
+pub const DEFAULT_SNAPSHOT_RETENTION: usize = 5;
+
+const BACKUP_FOLDER_KEY: &str = "backup.folder";
+const BACKUP_RETENTION_KEY: &str = "backup.retention";
+const SNAPSHOT_RETENTION_KEY: &str = "snapshot.retention";
+/// Automatic snapshots are kept in this folder inside the backup
+/// folder, so they rotate separately from the manual backups
+const SNAPSHOT_FOLDER: &str = "snapshots";
+const BACKUP_PREFIX: &str = "family_banking-";
+const BACKUP_EXTENSION: &str = "store";
+const DAMAGED_SUFFIX: &str = ".damaged";
+const RESTORE_PAGES_PER_STEP: std::os::raw::c_int = 100;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackupSettings {
+    pub folder: PathBuf,
+    /// How many backups to keep before the oldest are deleted
+    pub retention: usize,
+    /// How many automatic snapshots to keep
+    pub snapshot_retention: usize,
+}
+
+impl BackupSettings {
+    pub fn snapshot_folder(&self) -> PathBuf {
+        self.folder.join(SNAPSHOT_FOLDER)
+    }
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        BackupSettings {
+            folder: PathBuf::from(DEFAULT_BACKUP_FOLDER),
+            retention: DEFAULT_BACKUP_RETENTION,
+            snapshot_retention: DEFAULT_SNAPSHOT_RETENTION,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackupFile {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Backups go next to the database by default, so that each
+/// group file keeps its own backups
+fn default_backup_folder(conn: &Connection) -> PathBuf {
+    match conn
+        .path()
+        .and_then(Path::parent)
+        .filter(|folder| !folder.as_os_str().is_empty())
+    {
+        Some(folder) => folder.join(DEFAULT_BACKUP_FOLDER_NAME),
+        None => PathBuf::from(DEFAULT_BACKUP_FOLDER),
+    }
+}
+
+pub fn backup_settings(conn: &Connection) -> Result<BackupSettings> {
+    let defaults = BackupSettings {
+        folder: default_backup_folder(conn),
+        ..BackupSettings::default()
+    };
+
+    // Snapshots are taken before migrations, which may
+    // be what creates the settings table
+    if !db_operations::table_exists(conn, "setting")? {
+        return Ok(defaults);
+    }
+
+    Ok(BackupSettings {
+        folder: db_operations::get_setting(conn, BACKUP_FOLDER_KEY)?
+            .map(PathBuf::from)
+            .unwrap_or(defaults.folder),
+        retention: db_operations::get_setting(conn, BACKUP_RETENTION_KEY)?
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(defaults.retention),
+        snapshot_retention: db_operations::get_setting(conn, SNAPSHOT_RETENTION_KEY)?
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(defaults.snapshot_retention),
+    })
+}
+
+/// The saved settings, or the defaults if the database is too
+/// damaged to read them, so that its backups can still be found
+pub fn backup_settings_or_default(conn: &Connection) -> BackupSettings {
+    backup_settings(conn).unwrap_or_else(|_| BackupSettings {
+        folder: default_backup_folder(conn),
+        ..BackupSettings::default()
+    })
+}
+
+pub fn store_backup_settings(conn: &Connection, settings: &BackupSettings) -> Result<usize> {
+    db_operations::in_transaction(conn, |conn| {
+        Ok(db_operations::store_setting(
+            conn,
+            BACKUP_FOLDER_KEY,
+            &settings.folder.to_string_lossy(),
+        )? + db_operations::store_setting(
+            conn,
+            BACKUP_RETENTION_KEY,
+            &settings.retention.to_string(),
+        )? + db_operations::store_setting(
+            conn,
+            SNAPSHOT_RETENTION_KEY,
+            &settings.snapshot_retention.to_string(),
+        )?)
+    })
+}
+
+/// Lists the backups in `folder`, newest first. A folder
+/// that does not exist yet simply has no backups
+pub fn list_backups(folder: &Path) -> Vec<BackupFile> {
+    let mut backups: Vec<BackupFile> = match fs::read_dir(folder) {
+        Ok(dir) => dir
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == BACKUP_EXTENSION))
+            .filter_map(|path| {
+                let name = path.file_name()?.to_str()?.to_string();
+
+                if name.starts_with(BACKUP_PREFIX) {
+                    Some(BackupFile { name, path })
+                } else {
+                    None
+                }
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+
+    // The timestamp in the names makes them sort by age
+    backups.sort_by(|a, b| b.name.cmp(&a.name));
+    backups
+}
+
+/// Copies the live database into a new timestamped file in the backup
+/// folder, then deletes the oldest backups beyond the retention limit.
+/// SQLite's backup API gives a consistent copy even while the
+/// connection is in use
+pub fn create_backup(conn: &Connection) -> Result<PathBuf> {
+    let settings = backup_settings(conn)?;
+
+    write_backup(conn, &settings.folder, settings.retention, "")
+}
+
+/// Takes an automatic snapshot before something that is hard to undo.
+/// `reason` is added to the file name so the snapshot can be told
+/// apart when choosing one to restore
+pub fn create_snapshot(conn: &Connection, reason: &str) -> Result<PathBuf> {
+    let settings = backup_settings(conn)?;
+
+    write_backup(
+        conn,
+        &settings.snapshot_folder(),
+        settings.snapshot_retention,
+        &format!("-{}", reason),
+    )
+}
+
+fn write_backup(
+    conn: &Connection,
+    folder: &Path,
+    retention: usize,
+    label: &str,
+) -> Result<PathBuf> {
+    fs::create_dir_all(folder)?;
+
+    let timestamp: String = conn.query_row(
+        "SELECT replace(strftime('%Y%m%d-%H%M%f', 'now', 'localtime'), '.', '')",
+        [],
+        |row| row.get(0),
+    )?;
+
+    let mut path = folder.join(format!(
+        "{}{}{}.{}",
+        BACKUP_PREFIX, timestamp, label, BACKUP_EXTENSION
+    ));
+    let mut n = 1;
+
+    // '_' sorts after '.', so a clash keeps the names in order of age
+    while path.exists() {
+        path = folder.join(format!(
+            "{}{}{}_{}.{}",
+            BACKUP_PREFIX, timestamp, label, n, BACKUP_EXTENSION
+        ));
+        n += 1;
+    }
+
+    conn.backup(DatabaseName::Main, &path, None)?;
+
+    for old_backup in list_backups(folder).iter().skip(retention.max(1)) {
+        fs::remove_file(&old_backup.path)?;
+    }
+
+    Ok(path)
+}
+
+/// Checks that the file at `path` is an intact Family Banking
+/// database that this version of the app can open
+pub fn validate_backup(path: &Path) -> Result<()> {
+    if !path.is_file() {
+        return Err(Error::InvalidBackup(format!(
+            "{} does not exist",
+            path.display()
+        )));
+    }
+
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+    let integrity: String = conn.query_row("PRAGMA integrity_check", [], |row| row.get(0))?;
+
+    if integrity != "ok" {
+        return Err(Error::InvalidBackup(format!(
+            "the file is damaged ({})",
+            integrity
+        )));
+    }
+
+    for table in ["admin", "member"] {
+        if !db_operations::table_exists(&conn, table)? {
+            return Err(Error::InvalidBackup(format!(
+                "the \"{}\" table is missing",
+                table
+            )));
+        }
+    }
+
+    let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+    if version > db_operations::latest_schema_version() {
+        return Err(Error::InvalidBackup(
+            "it was made by a newer version of Family Banking".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Replaces the contents of the live database with the backup
+/// at `path`, once it has been validated. A snapshot of the current
+/// data is taken first, and backups made by older versions are
+/// brought up to date afterwards
+pub fn restore_backup(conn: &mut Connection, path: &Path) -> Result<()> {
+    validate_backup(path)?;
+
+    // Read the backup before taking the snapshot, since rotating
+    // the snapshots may delete the one that is being restored
+    let mut source = Connection::open_in_memory()?;
+    source.restore(
+        DatabaseName::Main,
+        path,
+        None::<fn(rusqlite::backup::Progress)>,
+    )?;
+
+    match create_snapshot(conn, "restore") {
+        Ok(_) => {
+            Backup::new(&source, conn)?.run_to_completion(
+                RESTORE_PAGES_PER_STEP,
+                Duration::ZERO,
+                None,
+            )?;
+        }
+        // A damaged database can neither be copied nor written over,
+        // and is most likely the reason the backup is being restored
+        Err(Error::Schema(_)) => replace_damaged_database(conn, &source)?,
+        Err(err) => return Err(err),
+    }
+
+    update_db_schema(conn)?;
+
+    Ok(())
+}
+
+/// Moves the damaged file of `conn` aside, with `.damaged` added to its
+/// name so that it can still be looked at, and writes `source` in its
+/// place. `conn` is reopened on whichever file is then at the path
+fn replace_damaged_database(conn: &mut Connection, source: &Connection) -> Result<()> {
+    let path = match conn.path() {
+        Some(path) if !path.as_os_str().is_empty() => path.to_path_buf(),
+        _ => {
+            return Err(Error::InvalidBackup(
+                "the open books are not kept in a file".to_string(),
+            ))
+        }
+    };
+    let mut damaged_path = path.clone().into_os_string();
+    damaged_path.push(DAMAGED_SUFFIX);
+
+    // The file has to be closed before it can be moved on some systems
+    std::mem::replace(conn, Connection::open_in_memory()?)
+        .close()
+        .map_err(|(_, err)| err)?;
+
+    let result = fs::rename(&path, &damaged_path)
+        .map_err(Error::from)
+        .and_then(|_| Ok(source.backup(DatabaseName::Main, &path, None)?));
+
+    *conn = Connection::open(&path)?;
+
+    result
+}
+
+/// Runs any pending migrations, taking a snapshot first
+/// if there is data that they could damage
+pub fn update_db_schema(conn: &Connection) -> Result<()> {
+    if db_operations::pending_migrations(conn)? > 0 && db_operations::has_members(conn)? {
+        create_snapshot(conn, "migration")?;
+    }
+
+    db_operations::update_db_schema(conn)?;
+
+    Ok(())
+}
@@ -0,0 +1,289 @@
+use std::{
+    env, fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Rule, Error, Result};
+
+/// Environment variable that can hold the path of the database
+pub const DATABASE_ENV_VAR: &str = "FAMILY_BANKING_DB";
+
+pub const USAGE: &str = "\
+Usage: family_banking [--db <file> | --new <file>]
+
+  --db <file>   Open an existing group file
+  --new <file>  Create a new group file and open it
+  --help        Show this message
+
+Without --db or --new the file is taken from the FAMILY_BANKING_DB
+environment variable, then from \"database\" in the config file, and
+is otherwise kept in the platform data folder.";
+
+const APP_FOLDER: &str = "family_banking";
+const CONFIG_FILE: &str = "config.json";
+const DEFAULT_DATABASE_FILE: &str = "data.store";
+const GROUPS_FOLDER: &str = "groups";
+const DATABASE_EXTENSION: &str = "store";
+
+/// Contents of the config file in the platform config folder
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Config {
+    /// Path of the database to open. A relative path is
+    /// taken from the folder the config file is in
+    #[serde(default)]
+    pub database: Option<PathBuf>,
+    /// Every group that has been opened, for the group picker
+    #[serde(default)]
+    pub groups: Vec<Group>,
+}
+
+/// A savings group, each kept in its own database
+/// with its own admin password
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Group {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl fmt::Display for Group {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// What the command line asked for
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Args {
+    pub database: Option<PathBuf>,
+    /// Create `database` instead of opening an existing file
+    pub create: bool,
+    pub help: bool,
+}
+
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Args> {
+    let mut parsed = Args::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--db" | "--new" => {
+                if parsed.database.is_some() {
+                    return Err(Error::Usage(Rule::OneDatabase));
+                }
+
+                parsed.create = arg == "--new";
+                parsed.database = match args.next() {
+                    Some(path) => Some(PathBuf::from(path)),
+                    None => return Err(Error::Usage(Rule::NeedsPath(arg))),
+                };
+            }
+            "-h" | "--help" => parsed.help = true,
+            _ => return Err(Error::Usage(Rule::UnknownArgument(arg))),
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// `<platform config folder>/family_banking/config.json`
+pub fn config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(APP_FOLDER).join(CONFIG_FILE))
+}
+
+/// Reads the config file. Having no config file is the
+/// same as having one with nothing set
+pub fn load_config() -> Result<Config> {
+    match config_path() {
+        Some(path) if path.is_file() => Ok(serde_json::from_str(&fs::read_to_string(path)?)?),
+        _ => Ok(Config::default()),
+    }
+}
+
+pub fn save_config(config: &Config) -> Result<()> {
+    let path = config_path().ok_or_else(|| Error::Usage(Rule::NoConfigFolder))?;
+
+    if let Some(folder) = path.parent() {
+        fs::create_dir_all(folder)?;
+    }
+
+    fs::write(path, serde_json::to_string_pretty(config)?)?;
+
+    Ok(())
+}
+
+/// Paths are compared in their absolute form so that
+/// one file is never listed as two groups
+fn absolute_path(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// The listed group kept in the file at `path`, adding it to the
+/// list, named after the file, if it has not been opened before
+pub fn find_or_add_group(config: &mut Config, path: &Path) -> Group {
+    let path = absolute_path(path);
+
+    match config.groups.iter().find(|group| group.path == path) {
+        Some(group) => group.clone(),
+        None => {
+            let group = Group {
+                name: path
+                    .file_stem()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| path.display().to_string()),
+                path,
+            };
+
+            config.groups.push(group.clone());
+            group
+        }
+    }
+}
+
+/// Where the file of a new group called `name` goes: the groups
+/// folder next to the default database, named after the group
+pub fn new_group_path(name: &str) -> PathBuf {
+    let folder = match default_database_path().parent() {
+        Some(folder) => folder.join(GROUPS_FOLDER),
+        None => PathBuf::from(GROUPS_FOLDER),
+    };
+    let mut file_name: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    if file_name.is_empty() {
+        file_name = "group".to_string();
+    }
+
+    let mut path = folder.join(format!("{}.{}", file_name, DATABASE_EXTENSION));
+    let mut n = 2;
+
+    while path.exists() {
+        path = folder.join(format!("{}_{}.{}", file_name, n, DATABASE_EXTENSION));
+        n += 1;
+    }
+
+    path
+}
+
+/// Creates the file for a new group called `name` and adds
+/// it to the list, returning the list with the new group last
+pub fn create_group(name: &str) -> Result<Vec<Group>> {
+    let name = name.trim();
+    let mut config = load_config()?;
+
+    if name.is_empty() {
+        return Err(Error::Validation(Rule::GroupName));
+    }
+
+    if config
+        .groups
+        .iter()
+        .any(|group| group.name.eq_ignore_ascii_case(name))
+    {
+        return Err(Error::Validation(Rule::GroupExists(name.to_string())));
+    }
+
+    let (path, _) = create_database(&new_group_path(name))?;
+
+    config.groups.push(Group {
+        name: name.to_string(),
+        path: absolute_path(&path),
+    });
+    save_config(&config)?;
+
+    Ok(config.groups)
+}
+
+/// Opens the file of a group from the list, which has to still exist
+pub fn open_group(group: &Group) -> Result<Connection> {
+    if !group.path.is_file() {
+        return Err(Error::Usage(Rule::GroupFileMissing(
+            group.name.clone(),
+            group.path.display().to_string(),
+        )));
+    }
+
+    Ok(Connection::open(&group.path)?)
+}
+
+/// The database used when nothing else has been set. Older versions
+/// always used `./data.store`, so that file is kept if it exists
+pub fn default_database_path() -> PathBuf {
+    let legacy_path = PathBuf::from(".").join(DEFAULT_DATABASE_FILE);
+
+    if legacy_path.is_file() {
+        return legacy_path;
+    }
+
+    match dirs::data_dir() {
+        Some(dir) => dir.join(APP_FOLDER).join(DEFAULT_DATABASE_FILE),
+        None => legacy_path,
+    }
+}
+
+/// Works out which database to open: the command line first,
+/// then the environment variable, the config file and the default
+pub fn database_path(args: &Args) -> Result<PathBuf> {
+    if let Some(path) = &args.database {
+        return Ok(path.clone());
+    }
+
+    if let Some(path) = env::var_os(DATABASE_ENV_VAR).filter(|path| !path.is_empty()) {
+        return Ok(PathBuf::from(path));
+    }
+
+    if let Some(path) = load_config()?.database {
+        return Ok(match config_path().as_deref().and_then(Path::parent) {
+            Some(config_dir) if path.is_relative() => config_dir.join(path),
+            _ => path,
+        });
+    }
+
+    Ok(default_database_path())
+}
+
+/// Opens the database the command line, environment or config file
+/// points at. A file named with `--db` has to exist already, so that a
+/// mistyped path is not silently turned into an empty group
+pub fn open_database(args: &Args) -> Result<(PathBuf, Connection)> {
+    let path = database_path(args)?;
+
+    if args.create {
+        create_database(&path)
+    } else if args.database.is_some() && !path.is_file() {
+        Err(Error::Usage(Rule::FileMissing(path.display().to_string())))
+    } else {
+        open_or_create_database(&path)
+    }
+}
+
+/// Creates a new, empty group file at `path`
+pub fn create_database(path: &Path) -> Result<(PathBuf, Connection)> {
+    if path.exists() {
+        return Err(Error::Usage(Rule::FileExists(path.display().to_string())));
+    }
+
+    open_or_create_database(path)
+}
+
+fn open_or_create_database(path: &Path) -> Result<(PathBuf, Connection)> {
+    if let Some(folder) = path
+        .parent()
+        .filter(|folder| !folder.as_os_str().is_empty())
+    {
+        fs::create_dir_all(folder)?;
+    }
+
+    Ok((path.to_path_buf(), Connection::open(path)?))
+}
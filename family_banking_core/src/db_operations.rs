@@ -0,0 +1,1034 @@
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::error::{Error, Result, Rule};
+
+use crate::ledger::{self, LedgerKind, LedgerRecord, TotalsPoint};
+use crate::model::{
+    AllocationRule, Category, Currency, Entry, EntryKind, Member, MemberDetails, MemberProfile,
+    MemberSort, Reconciliation, Repayment, Summary, INACTIVE_DAYS, OVERDUE_DAYS,
+};
+
+/// Schema changes made after the first release, in order. The
+/// number of migrations already applied to a database is kept
+/// in its `user_version` pragma
+const MIGRATIONS: &[&str] = &[
+    "
+    CREATE TABLE reconciliation (
+        reconciliationId    INTEGER PRIMARY KEY,
+        date                TEXT NOT NULL DEFAULT (date('now', 'localtime')),
+        computedCash        DECIMAL,
+        countedCash         DECIMAL,
+        bankBalance         DECIMAL,
+        adjustment          DECIMAL,
+        reason              TEXT NOT NULL
+    );",
+    "
+    CREATE TABLE category (
+        categoryId  INTEGER PRIMARY KEY,
+        name        TEXT NOT NULL,
+        kind        TEXT NOT NULL
+    );
+    INSERT INTO category (name, kind)
+    VALUES
+        ('Stationery', 'expense'),
+        ('Bank charges', 'expense'),
+        ('Transport', 'expense'),
+        ('Other', 'expense'),
+        ('Bank interest', 'income'),
+        ('Fines', 'income'),
+        ('Other', 'income');
+    CREATE TABLE entry (
+        entryId     INTEGER PRIMARY KEY,
+        date        TEXT NOT NULL DEFAULT (date('now', 'localtime')),
+        categoryId  INTEGER NOT NULL REFERENCES category(categoryId),
+        description TEXT NOT NULL,
+        amount      DECIMAL
+    );",
+    "
+    CREATE TABLE ledger (
+        ledgerId    INTEGER PRIMARY KEY,
+        date        TEXT NOT NULL DEFAULT (datetime('now', 'localtime')),
+        kind        TEXT NOT NULL,
+        memberId    INTEGER,
+        memberName  TEXT NOT NULL,
+        amount      DECIMAL,
+        interest    DECIMAL
+    );",
+    "
+    CREATE TABLE setting (
+        key     TEXT PRIMARY KEY,
+        value   TEXT NOT NULL
+    );",
+    "
+    ALTER TABLE ledger ADD COLUMN reverses INTEGER REFERENCES ledger(ledgerId);
+    ALTER TABLE entry ADD COLUMN ledgerId INTEGER REFERENCES ledger(ledgerId);",
+    "
+    ALTER TABLE member ADD COLUMN phone TEXT NOT NULL DEFAULT '';
+    ALTER TABLE member ADD COLUMN joined TEXT;
+    ALTER TABLE member ADD COLUMN notes TEXT NOT NULL DEFAULT '';
+    UPDATE member
+    SET joined = (
+        SELECT date(MIN(date))
+        FROM ledger
+        WHERE ledger.memberId = member.memberId
+    )
+    WHERE memberId != 0;",
+];
+
+/// Runs `operation` in a transaction, so that either all of its
+/// changes are made or, if any step fails, none of them are.
+/// Operations built from other operations share the transaction
+/// of the outermost one
+pub fn in_transaction<T>(
+    conn: &Connection,
+    operation: impl FnOnce(&Connection) -> Result<T>,
+) -> Result<T> {
+    if !conn.is_autocommit() {
+        return operation(conn);
+    }
+
+    // Dropping the transaction without committing rolls it back
+    let tx = conn.unchecked_transaction()?;
+    let result = operation(&tx)?;
+
+    tx.commit()?;
+
+    Ok(result)
+}
+
+/// Returns the admin password hash, or `None` if no password has
+/// been set. The tables are created the first time a new database
+/// is opened
+pub fn get_password(conn: &Connection) -> Result<Option<String>> {
+    if !table_exists(conn, "admin")? {
+        initialize_db_tables(conn)?;
+        return Ok(None);
+    }
+
+    let hash: Option<String> = conn
+        .query_row("SELECT passwordHash FROM admin WHERE id = 1", [], |row| {
+            row.get(0)
+        })
+        .optional()?;
+
+    match hash {
+        Some(hash) if hash != " " => Ok(Some(hash)),
+        Some(_) => Ok(None),
+        None => {
+            create_default_admin_row(conn)?;
+            Ok(None)
+        }
+    }
+}
+
+pub fn initialize_db_tables(conn: &Connection) -> Result<usize> {
+    in_transaction(conn, |conn| {
+        Ok(conn.execute(
+            "
+            CREATE TABLE admin (
+                id              INTEGER PRIMARY KEY,
+                passwordHash    TEXT NOT NULL
+            );",
+            [],
+        )? + conn.execute(
+            "
+            CREATE TABLE member (
+                memberId    INTEGER PRIMARY KEY,
+                name        TEXT NOT NULL,
+                share       DECIMAL,
+                loan        DECIMAL,
+                interest    DECIMAL
+            );",
+            [],
+        )? + conn.execute(
+            "
+            INSERT INTO member
+            VALUES (0, 'Profits', 0, 0, 0);",
+            [],
+        )? + create_default_admin_row(conn)?)
+    })
+}
+
+/// The schema version of a database with every migration applied
+pub fn latest_schema_version() -> usize {
+    MIGRATIONS.len()
+}
+
+/// The number of migrations that have not yet been applied
+pub fn pending_migrations(conn: &Connection) -> Result<usize> {
+    let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+    Ok(MIGRATIONS.len().saturating_sub(version))
+}
+
+pub fn table_exists(conn: &Connection, table: &str) -> Result<bool> {
+    let count: i32 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        params![table],
+        |row| row.get(0),
+    )?;
+
+    Ok(count > 0)
+}
+
+/// Whether any members, other than the profits row, have been added
+pub fn has_members(conn: &Connection) -> Result<bool> {
+    let count: i32 = conn.query_row(
+        "SELECT COUNT(*) FROM member WHERE memberId != 0",
+        [],
+        |row| row.get(0),
+    )?;
+
+    Ok(count > 0)
+}
+
+/// Brings the database tables up to date by running any
+/// migrations that have not yet been applied to it
+pub fn update_db_schema(conn: &Connection) -> Result<()> {
+    let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+    for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        in_transaction(conn, |conn| {
+            conn.execute_batch(migration)?;
+            conn.pragma_update(None, "user_version", i + 1)?;
+            Ok(())
+        })?;
+    }
+
+    Ok(())
+}
+
+pub fn create_default_admin_row(conn: &Connection) -> Result<usize> {
+    Ok(conn.execute(
+        "
+        INSERT INTO admin
+        VALUES (1, ' ');",
+        [],
+    )?)
+}
+
+pub fn store_password(conn: &Connection, password: String) -> Result<usize> {
+    let hash = argon2::hash_encoded(
+        password.as_bytes(),
+        crate::HASH_SALT,
+        &argon2::Config::default(),
+    )
+    .map_err(|err| Error::Validation(Rule::Password(err.to_string())))?;
+
+    Ok(conn.execute(
+        "UPDATE admin
+                        SET passwordHash = ?1
+                        WHERE id = 1",
+        params![hash],
+    )?)
+}
+
+/// Whether `password` is the one that `hash` was made from
+pub fn verify_password(hash: &str, password: &str) -> bool {
+    argon2::verify_encoded(hash, password.as_bytes()).unwrap_or(false)
+}
+
+/// Returns a list of all members with their details from
+/// the database, as well as a value for the profit
+fn fetch_members(conn: &Connection) -> Result<(Vec<Member>, f64)> {
+    let mut stmt = conn.prepare(
+        "
+        SELECT
+            memberId,
+            name,
+            share,
+            loan,
+            interest,
+            loan + interest > 0 AND NOT EXISTS (
+                SELECT 1 FROM ledger
+                WHERE ledger.memberId = member.memberId
+                    AND kind IN ('lend', 'repay')
+                    AND date >= datetime('now', 'localtime', ?1)
+            ),
+            NOT EXISTS (
+                SELECT 1 FROM ledger
+                WHERE ledger.memberId = member.memberId
+                    AND date >= datetime('now', 'localtime', ?2)
+            )
+        FROM member",
+    )?;
+
+    let members: Vec<Member> = stmt
+        .query_map(
+            params![
+                format!("-{} days", OVERDUE_DAYS),
+                format!("-{} days", INACTIVE_DAYS)
+            ],
+            |row| {
+                Ok(Member {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    shares: row.get(2)?,
+                    loan: row.get(3)?,
+                    interest: row.get(4)?,
+                    overdue: row.get(5)?,
+                    inactive: row.get(6)?,
+                    ..Member::default()
+                })
+            },
+        )?
+        .collect::<rusqlite::Result<_>>()?;
+
+    let profit_member = members
+        .iter()
+        .find(|member| member.id == 0)
+        .ok_or_else(|| Error::Schema("the Profits row is missing".to_string()))?
+        .clone();
+
+    let members: Vec<Member> = members.iter().filter(|m| m.id != 0).cloned().collect();
+
+    let total_shares = members.iter().fold(0.0, |acc, member| acc + member.shares);
+
+    Ok((
+        members
+            .into_iter()
+            .map(|member| Member {
+                // Until someone buys shares, nobody owns any part of the total
+                percent: if total_shares == 0.0 {
+                    0.0
+                } else {
+                    (member.shares / total_shares) * 100.0
+                },
+                ..member
+            })
+            .collect(),
+        profit_member.shares,
+    ))
+}
+
+pub fn members(conn: &Connection) -> Result<Vec<Member>> {
+    Ok(fetch_members(conn)?.0)
+}
+
+/// Returns everything shown on the page of a member
+pub fn member_details(conn: &Connection, id: i32) -> Result<MemberDetails> {
+    let (members, profit) = fetch_members(conn)?;
+    // Interest still owed becomes profit once it is paid
+    let owed_interest: f64 = members.iter().map(|member| member.interest).sum();
+    let member = members
+        .into_iter()
+        .find(|member| member.id == id)
+        .ok_or_else(|| Error::NotFound(format!("Member {}", id)))?;
+
+    let profile = conn.query_row(
+        "SELECT phone, joined, notes FROM member WHERE memberId = ?1",
+        params![id],
+        |row| {
+            Ok(MemberProfile {
+                phone: row.get(0)?,
+                joined: row.get(1)?,
+                notes: row.get(2)?,
+            })
+        },
+    )?;
+
+    let records: Vec<LedgerRecord> = conn
+        .prepare(
+            "
+            SELECT ledgerId, date, kind, memberId, memberName, amount, interest, reverses
+            FROM ledger
+            WHERE memberId = ?1
+            ORDER BY ledgerId",
+        )?
+        .query_map(params![id], ledger_record)?
+        .collect::<rusqlite::Result<_>>()?;
+
+    let of_kind = |kind: LedgerKind| -> Vec<LedgerRecord> {
+        records
+            .iter()
+            .filter(|record| record.kind == kind)
+            .cloned()
+            .collect()
+    };
+
+    Ok(MemberDetails {
+        share_out: member.shares + (profit + owed_interest) * member.percent / 100.0
+            - member.debt(),
+        loans: ledger::loan_progress(&records),
+        contributions: of_kind(LedgerKind::Shares),
+        repayments: of_kind(LedgerKind::Repay),
+        member,
+        profile,
+    })
+}
+
+/// Saves the contact details and notes of a member
+pub fn store_member_profile(conn: &Connection, id: i32, phone: &str, notes: &str) -> Result<usize> {
+    member_balance(conn, id)?;
+
+    let phone = phone.trim();
+
+    if !phone
+        .chars()
+        .all(|c| c.is_ascii_digit() || " +-()".contains(c))
+    {
+        return Err(Error::Validation(Rule::Phone));
+    }
+
+    Ok(conn.execute(
+        "
+        UPDATE member
+        SET phone = ?2, notes = ?3
+        WHERE memberId = ?1;",
+        params![id, phone, notes.trim()],
+    )?)
+}
+
+pub fn summary(conn: &Connection) -> Result<Summary> {
+    let (members, profit) = fetch_members(conn)?;
+    let (total_expenses, total_income) = entry_totals(conn)?;
+
+    let (total_shares, total_debt, total_loans) =
+        members.iter().fold((0.0, 0.0, 0.0), |acc, member| {
+            (
+                acc.0 + member.shares,
+                acc.1 + member.loan + member.interest,
+                acc.2 + member.loan,
+            )
+        });
+
+    Ok(Summary {
+        total_shares,
+        total_debt,
+        total_loans,
+        total_cash: total_shares - total_loans + profit,
+        profit,
+        total_expenses,
+        total_income,
+    })
+}
+
+/// The total shares, cash and profit after each record in the ledger
+pub fn totals_history(conn: &Connection) -> Result<Vec<TotalsPoint>> {
+    let summary = summary(conn)?;
+
+    Ok(ledger::totals_history(
+        &fetch_ledger(conn)?,
+        summary.total_shares,
+        summary.total_cash,
+        summary.profit,
+    ))
+}
+
+/// Returns the shares, loan and interest of a member,
+/// or `Error::NotFound` if there is no such member
+fn member_balance(conn: &Connection, id: i32) -> Result<(f64, f64, f64)> {
+    conn.query_row(
+        "SELECT share, loan, interest FROM member WHERE memberId = ?1 AND memberId != 0",
+        params![id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )
+    .optional()?
+    .ok_or_else(|| Error::NotFound(format!("Member {}", id)))
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    if name.trim().is_empty() {
+        Err(Error::Validation(Rule::Username))
+    } else {
+        Ok(())
+    }
+}
+
+/// Shares can be taken out, but no member can hold fewer than none
+fn validate_shares(shares: f64) -> Result<()> {
+    if shares < 0.0 {
+        Err(Error::Validation(Rule::SharesNegative))
+    } else {
+        Ok(())
+    }
+}
+
+/// Adds a member with `shares` bought with cash, returning their id
+pub fn store_new_user(conn: &Connection, name: String, shares: f64) -> Result<i32> {
+    in_transaction(conn, |conn| {
+        validate_name(&name)?;
+        validate_shares(shares)?;
+
+        conn.execute(
+            "
+            INSERT INTO member (name, share, loan, interest, joined)
+            VALUES (?1, ?2, 0, 0, date('now', 'localtime'));",
+            params![name, shares],
+        )?;
+
+        let id = conn.last_insert_rowid() as i32;
+
+        log_transaction(conn, LedgerKind::Shares, id, shares, 0.0)?;
+        Ok(id)
+    })
+}
+
+pub fn edit_user(conn: &Connection, id: i32, name: String, shares: f64) -> Result<usize> {
+    in_transaction(conn, |conn| {
+        validate_name(&name)?;
+        validate_shares(shares)?;
+
+        let (old_shares, _, _) = member_balance(conn, id)?;
+
+        Ok(conn.execute(
+            "
+            UPDATE member
+            SET name = ?2, share = ?3
+            WHERE memberId = ?1;",
+            params![id, name, shares],
+        )? + if shares != old_shares {
+            log_transaction(conn, LedgerKind::Shares, id, shares - old_shares, 0.0)?
+        } else {
+            0
+        })
+    })
+}
+
+pub fn delete_user(conn: &Connection, id: i32) -> Result<usize> {
+    in_transaction(conn, |conn| {
+        let (share, loan, interest) = member_balance(conn, id)?;
+
+        let mut changes = log_transaction(conn, LedgerKind::Shares, id, -share, 0.0)?;
+
+        if loan != 0.0 || interest != 0.0 {
+            changes += log_transaction(conn, LedgerKind::WriteOff, id, loan, interest)?;
+        }
+
+        // The records keep the member's name, but no longer point
+        // at the row, whose id may be given to a new member later
+        changes += conn.execute(
+            "
+            UPDATE ledger
+            SET memberId = NULL
+            WHERE memberId = ?1;",
+            params![id],
+        )?;
+
+        Ok(changes
+            + conn.execute(
+                "
+            DELETE FROM member
+            WHERE memberId = ?1;",
+                params![id],
+            )?)
+    })
+}
+
+pub fn borrow_debt(conn: &Connection, user_id: i32, loan: f64, interest: f64) -> Result<usize> {
+    in_transaction(conn, |conn| {
+        if loan < 0.0 {
+            return Err(Error::Validation(Rule::LoanNegative));
+        } else if interest < 0.0 {
+            return Err(Error::Validation(Rule::InterestNegative));
+        }
+
+        member_balance(conn, user_id)?;
+
+        Ok(conn.execute(
+            "
+            UPDATE member
+            SET loan = loan + ?2, interest = interest + ?3
+            WHERE memberId = ?1;",
+            params![user_id, loan, interest],
+        )? + log_transaction(conn, LedgerKind::Lend, user_id, loan, interest)?)
+    })
+}
+
+/// Takes a repayment of `loan` and `interest` from a member.
+/// Neither can be more than the member owes of it
+pub fn repay_debt(conn: &Connection, user_id: i32, loan: f64, interest: f64) -> Result<usize> {
+    in_transaction(conn, |conn| {
+        let (_, owed_loan, owed_interest) = member_balance(conn, user_id)?;
+
+        if loan < 0.0 || interest < 0.0 {
+            return Err(Error::Validation(Rule::RepaymentNegative));
+        } else if loan > owed_loan || interest > owed_interest {
+            return Err(Error::Validation(Rule::RepaymentTooHigh));
+        }
+
+        record_repayment(conn, user_id, loan, interest)
+    })
+}
+
+/// Writes a repayment that has already been checked
+fn record_repayment(conn: &Connection, user_id: i32, loan: f64, interest: f64) -> Result<usize> {
+    in_transaction(conn, |conn| {
+        Ok(conn.execute(
+            "
+            UPDATE member
+            SET loan = loan - ?2, interest = interest - ?3
+            WHERE memberId = ?1;",
+            params![user_id, loan, interest],
+        )? + conn.execute(
+            "
+            UPDATE member
+            SET share = share + ?1
+            WHERE memberId = 0;",
+            params![interest],
+        )? + log_transaction(conn, LedgerKind::Repay, user_id, loan, interest)?)
+    })
+}
+
+/// Takes a repayment of `amount` from a member, split between
+/// their loan and its interest by the group's allocation rule
+pub fn repay(conn: &Connection, user_id: i32, amount: f64) -> Result<Repayment> {
+    in_transaction(conn, |conn| {
+        let (_, loan, interest) = member_balance(conn, user_id)?;
+
+        if amount < 0.0 {
+            return Err(Error::Validation(Rule::RepaymentNegative));
+        } else if amount > loan + interest {
+            return Err(Error::Validation(Rule::RepaymentTooHigh));
+        }
+
+        let repayment = Repayment::allocate(allocation_rule(conn)?, loan, interest, amount);
+
+        // Checked as a whole above, as the parts of a full repayment
+        // can each be off from what is owed by a rounding error
+        record_repayment(conn, user_id, repayment.loan, repayment.interest)?;
+
+        Ok(repayment)
+    })
+}
+
+/// Adds a record of an operation to the ledger. The member's
+/// name is copied so the record still reads well once the
+/// member has been deleted
+fn log_transaction(
+    conn: &Connection,
+    kind: LedgerKind,
+    member_id: i32,
+    amount: f64,
+    interest: f64,
+) -> Result<usize> {
+    Ok(conn.execute(
+        "
+        INSERT INTO ledger (kind, memberId, memberName, amount, interest)
+        VALUES (
+            ?1,
+            ?2,
+            COALESCE((SELECT name FROM member WHERE memberId = ?2), ''),
+            ?3,
+            ?4
+        );",
+        params![kind.as_str(), member_id, amount, interest],
+    )?)
+}
+
+/// Reads a ledger record from a row of the columns selected by `fetch_ledger`
+fn ledger_record(row: &rusqlite::Row) -> rusqlite::Result<LedgerRecord> {
+    let kind: String = row.get(2)?;
+
+    Ok(LedgerRecord {
+        id: row.get(0)?,
+        date: row.get(1)?,
+        kind: LedgerKind::parse(&kind).ok_or_else(|| {
+            rusqlite::Error::InvalidColumnType(2, "kind".to_string(), rusqlite::types::Type::Text)
+        })?,
+        member_id: row.get(3)?,
+        member_name: row.get(4)?,
+        amount: row.get(5)?,
+        interest: row.get(6)?,
+        reverses: row.get(7)?,
+    })
+}
+
+/// Returns every record in the ledger, oldest first
+pub fn fetch_ledger(conn: &Connection) -> Result<Vec<LedgerRecord>> {
+    let mut stmt = conn.prepare(
+        "
+        SELECT ledgerId, date, kind, memberId, memberName, amount, interest, reverses
+        FROM ledger
+        ORDER BY ledgerId",
+    )?;
+
+    let records = stmt
+        .query_map([], ledger_record)?
+        .collect::<rusqlite::Result<_>>()?;
+
+    Ok(records)
+}
+
+/// Returns the id of the newest record in the ledger
+pub fn last_ledger_id(conn: &Connection) -> Result<Option<i64>> {
+    Ok(conn.query_row("SELECT MAX(ledgerId) FROM ledger", [], |row| row.get(0))?)
+}
+
+/// Posts a transaction that cancels out the ledger record `id`, so
+/// that a mistake can be undone while the ledger keeps both. Returns
+/// the id of the new record, which can itself be reversed to make
+/// the change again
+pub fn reverse_transaction(conn: &Connection, id: i64) -> Result<i64> {
+    in_transaction(conn, |conn| {
+        let LedgerRecord {
+            kind,
+            member_id,
+            member_name,
+            amount,
+            interest,
+            ..
+        } = conn
+            .query_row(
+                "
+                SELECT ledgerId, date, kind, memberId, memberName, amount, interest, reverses
+                FROM ledger
+                WHERE ledgerId = ?1",
+                params![id],
+                ledger_record,
+            )
+            .optional()?
+            .ok_or_else(|| Error::NotFound(format!("Transaction {}", id)))?;
+
+        let already_reversed: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM ledger WHERE reverses = ?1)",
+            params![id],
+            |row| row.get(0),
+        )?;
+
+        if already_reversed {
+            return Err(Error::Validation(Rule::AlreadyUndone));
+        }
+
+        let deleted = || Error::Validation(Rule::MemberDeleted(member_name.clone()));
+        // Deleting a member sets the member of their records to NULL
+        let member = || member_id.ok_or_else(deleted);
+
+        match kind {
+            LedgerKind::Shares => {
+                let member_id = member()?;
+
+                member_balance(conn, member_id)?;
+                conn.execute(
+                    "
+                    UPDATE member
+                    SET share = share - ?2
+                    WHERE memberId = ?1;",
+                    params![member_id, amount],
+                )?;
+            }
+            LedgerKind::Lend | LedgerKind::Repay => {
+                let member_id = member()?;
+                let (_, loan, owed_interest) = member_balance(conn, member_id)?;
+                // Undoing a loan takes it away again, while undoing
+                // a repayment puts the debt back
+                let sign = if kind == LedgerKind::Lend { -1.0 } else { 1.0 };
+
+                if loan + sign * amount < 0.0 || owed_interest + sign * interest < 0.0 {
+                    return Err(Error::Validation(Rule::LaterRepayments));
+                }
+
+                conn.execute(
+                    "
+                    UPDATE member
+                    SET loan = loan + ?2, interest = interest + ?3
+                    WHERE memberId = ?1;",
+                    params![member_id, sign * amount, sign * interest],
+                )?;
+
+                if kind == LedgerKind::Repay {
+                    conn.execute(
+                        "
+                        UPDATE member
+                        SET share = share - ?1
+                        WHERE memberId = 0;",
+                        params![interest],
+                    )?;
+                }
+            }
+            LedgerKind::WriteOff => return Err(deleted()),
+            LedgerKind::Entry | LedgerKind::Adjustment => {
+                conn.execute(
+                    "
+                    UPDATE member
+                    SET share = share - ?1
+                    WHERE memberId = 0;",
+                    params![amount],
+                )?;
+            }
+        }
+
+        conn.execute(
+            "
+            INSERT INTO ledger (kind, memberId, memberName, amount, interest, reverses)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6);",
+            params![
+                kind.as_str(),
+                member_id,
+                member_name,
+                -amount,
+                -interest,
+                id
+            ],
+        )?;
+
+        let reversal_id = conn.last_insert_rowid();
+
+        if kind == LedgerKind::Entry {
+            // The cash book gets an entry of the opposite amount, so
+            // its totals still agree with the profits
+            let changes = conn.execute(
+                "
+                INSERT INTO entry (categoryId, description, amount, ledgerId)
+                SELECT categoryId, description, -amount, ?2
+                FROM entry
+                WHERE ledgerId = ?1;",
+                params![id, reversal_id],
+            )?;
+
+            if changes == 0 {
+                return Err(Error::Validation(Rule::EntryTooOld));
+            }
+        }
+
+        Ok(reversal_id)
+    })
+}
+
+/// Returns every reconciliation, newest first
+pub fn fetch_reconciliations(conn: &Connection) -> Result<Vec<Reconciliation>> {
+    let mut stmt = conn.prepare(
+        "
+        SELECT date, computedCash, countedCash, bankBalance, adjustment, reason
+        FROM reconciliation
+        ORDER BY reconciliationId DESC",
+    )?;
+
+    let history = stmt
+        .query_map([], |row| {
+            Ok(Reconciliation {
+                date: row.get(0)?,
+                computed_cash: row.get(1)?,
+                counted_cash: row.get(2)?,
+                bank_balance: row.get(3)?,
+                adjustment: row.get(4)?,
+                reason: row.get(5)?,
+            })
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+
+    Ok(history)
+}
+
+/// Records a count of the physical cash and bank balance. A non-zero
+/// `adjustment` is posted to the profits so that the computed cash
+/// matches what was counted
+pub fn record_reconciliation(
+    conn: &Connection,
+    computed_cash: f64,
+    counted_cash: f64,
+    bank_balance: f64,
+    adjustment: f64,
+    reason: String,
+) -> Result<usize> {
+    in_transaction(conn, |conn| {
+        if counted_cash < 0.0 || bank_balance < 0.0 {
+            return Err(Error::Validation(Rule::AmountNegative));
+        }
+
+        Ok(conn.execute(
+            "
+            INSERT INTO reconciliation (computedCash, countedCash, bankBalance, adjustment, reason)
+            VALUES (?1, ?2, ?3, ?4, ?5);",
+            params![
+                computed_cash,
+                counted_cash,
+                bank_balance,
+                adjustment,
+                reason
+            ],
+        )? + conn.execute(
+            "
+            UPDATE member
+            SET share = share + ?1
+            WHERE memberId = 0;",
+            params![adjustment],
+        )? + if adjustment != 0.0 {
+            log_transaction(conn, LedgerKind::Adjustment, 0, adjustment, 0.0)?
+        } else {
+            0
+        })
+    })
+}
+
+/// Returns the totals of all expenses and all other income recorded
+fn entry_totals(conn: &Connection) -> Result<(f64, f64)> {
+    let mut stmt = conn.prepare(
+        "
+        SELECT category.kind, SUM(entry.amount)
+        FROM entry
+        JOIN category ON category.categoryId = entry.categoryId
+        GROUP BY category.kind",
+    )?;
+
+    let mut rows = stmt.query([])?;
+    let mut totals = (0.0, 0.0);
+
+    while let Some(row) = rows.next()? {
+        let kind: String = row.get(0)?;
+        let amount: f64 = row.get(1)?;
+
+        match EntryKind::parse(&kind) {
+            EntryKind::Expense => totals.0 += amount,
+            EntryKind::Income => totals.1 += amount,
+        }
+    }
+
+    Ok(totals)
+}
+
+pub fn fetch_categories(conn: &Connection) -> Result<Vec<Category>> {
+    let mut stmt = conn.prepare("SELECT categoryId, name, kind FROM category ORDER BY name")?;
+
+    let categories = stmt
+        .query_map([], |row| {
+            let kind: String = row.get(2)?;
+
+            Ok(Category {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                kind: EntryKind::parse(&kind),
+            })
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+
+    Ok(categories)
+}
+
+/// Returns every expense and other income, newest first
+pub fn fetch_entries(conn: &Connection) -> Result<Vec<Entry>> {
+    let mut stmt = conn.prepare(
+        "
+        SELECT entry.date, category.name, category.kind, entry.description, entry.amount,
+            entry.ledgerId
+        FROM entry
+        JOIN category ON category.categoryId = entry.categoryId
+        ORDER BY entry.entryId DESC",
+    )?;
+
+    let entries = stmt
+        .query_map([], |row| {
+            let kind: String = row.get(2)?;
+
+            Ok(Entry {
+                date: row.get(0)?,
+                category: row.get(1)?,
+                kind: EntryKind::parse(&kind),
+                description: row.get(3)?,
+                amount: row.get(4)?,
+                ledger_id: row.get(5)?,
+            })
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+
+    Ok(entries)
+}
+
+/// Records an expense or other income. Expenses are taken out of
+/// the profits, while other income is added to them
+pub fn store_entry(
+    conn: &Connection,
+    category: &Category,
+    description: String,
+    amount: f64,
+) -> Result<usize> {
+    in_transaction(conn, |conn| {
+        if amount <= 0.0 {
+            return Err(Error::Validation(Rule::AmountNotPositive));
+        }
+
+        let signed_amount = match category.kind {
+            EntryKind::Expense => -amount,
+            EntryKind::Income => amount,
+        };
+
+        // The entry points at its ledger record, so that it can be undone
+        let changes = log_transaction(conn, LedgerKind::Entry, 0, signed_amount, 0.0)?;
+
+        Ok(changes
+            + conn.execute(
+                "
+            INSERT INTO entry (categoryId, description, amount, ledgerId)
+            VALUES (?1, ?2, ?3, ?4);",
+                params![category.id, description, amount, conn.last_insert_rowid()],
+            )?
+            + conn.execute(
+                "
+            UPDATE member
+            SET share = share + ?1
+            WHERE memberId = 0;",
+                params![signed_amount],
+            )?)
+    })
+}
+
+pub fn get_setting(conn: &Connection, key: &str) -> Result<Option<String>> {
+    let mut stmt = conn.prepare("SELECT value FROM setting WHERE key = ?1")?;
+    let mut rows = stmt.query(params![key])?;
+
+    match rows.next()? {
+        Some(row) => Ok(Some(row.get(0)?)),
+        None => Ok(None),
+    }
+}
+
+pub fn store_setting(conn: &Connection, key: &str, value: &str) -> Result<usize> {
+    Ok(conn.execute(
+        "
+        INSERT INTO setting (key, value)
+        VALUES (?1, ?2)
+        ON CONFLICT (key) DO UPDATE SET value = excluded.value;",
+        params![key, value],
+    )?)
+}
+
+/// How the group splits repayments between loans and interest
+pub fn allocation_rule(conn: &Connection) -> Result<AllocationRule> {
+    Ok(get_setting(conn, AllocationRule::SETTING)?
+        .map(|setting| AllocationRule::from_setting(&setting))
+        .unwrap_or_default())
+}
+
+pub fn store_allocation_rule(conn: &Connection, rule: AllocationRule) -> Result<usize> {
+    store_setting(conn, AllocationRule::SETTING, rule.as_setting())
+}
+
+/// Returns the order the member table called `table` was last
+/// sorted in, or the default if it has not been sorted yet
+pub fn member_sort(conn: &Connection, table: &str) -> Result<MemberSort> {
+    Ok(get_setting(conn, &format!("sort.{}", table))?
+        .and_then(|value| MemberSort::from_setting(&value))
+        .unwrap_or_default())
+}
+
+pub fn store_member_sort(conn: &Connection, table: &str, sort: MemberSort) -> Result<usize> {
+    store_setting(conn, &format!("sort.{}", table), &sort.as_setting())
+}
+
+/// Returns how the group writes amounts of money, or the
+/// default for anything that has not been set
+pub fn currency(conn: &Connection) -> Result<Currency> {
+    let mut stmt = conn.prepare("SELECT key, value FROM setting WHERE key LIKE 'currency.%'")?;
+    let settings = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<Vec<(String, String)>>>()?;
+
+    Currency::from_settings(|key| {
+        settings
+            .iter()
+            .find(|(setting, _)| setting == key)
+            .map(|(_, value)| value.clone())
+    })
+}
+
+pub fn store_currency(conn: &Connection, currency: &Currency) -> Result<usize> {
+    currency.validate()?;
+
+    in_transaction(conn, |conn| {
+        currency
+            .as_settings()
+            .iter()
+            .map(|(key, value)| store_setting(conn, key, value))
+            .sum()
+    })
+}
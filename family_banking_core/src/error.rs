@@ -0,0 +1,260 @@
+use std::{fmt, io};
+
+use rusqlite::ErrorCode;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Why an operation on the books failed
+#[derive(Debug)]
+pub enum Error {
+    /// The database file could not be opened, read or written
+    Io(io::Error),
+    /// The file is damaged, is not a Family Banking database,
+    /// or is missing a table or row that it should have
+    Schema(String),
+    /// The member or record that was asked for does not exist
+    NotFound(String),
+    /// The values given break one of the rules of the books
+    Validation(Rule),
+    /// Another program is writing to the database
+    Locked,
+    /// Any other error reported by SQLite
+    Database(rusqlite::Error),
+    /// A file that should hold JSON, such as an export
+    /// or the config file, could not be read as JSON
+    Json(serde_json::Error),
+    /// An export file in a format or version this app cannot read
+    UnsupportedFormat(String, u32),
+    /// An export file whose records do not agree with each other
+    InvalidDocument(String),
+    /// A file that cannot be restored as a backup
+    InvalidBackup(String),
+    /// The command line, config file or group list asks for
+    /// something that cannot be done
+    Usage(Rule),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "File error: {}", err),
+            Error::Schema(reason) => write!(f, "The books are damaged: {}", reason),
+            Error::NotFound(what) => write!(f, "{} could not be found", what),
+            Error::Validation(rule) => write!(f, "{}", rule),
+            Error::Locked => write!(f, "The books are in use by another program"),
+            Error::Database(err) => write!(f, "Database error: {}", err),
+            Error::Json(err) => write!(f, "Not a valid JSON file: {}", err),
+            Error::UnsupportedFormat(format, version) => write!(
+                f,
+                "Unsupported export format \"{}\" version {}",
+                format, version
+            ),
+            Error::InvalidDocument(reason) => write!(f, "Export file is inconsistent: {}", reason),
+            Error::InvalidBackup(reason) => write!(f, "Not a usable backup: {}", reason),
+            Error::Usage(rule) => write!(f, "{}", rule),
+        }
+    }
+}
+
+/// A rule of the books, or of the command line and group files, that
+/// the values given break. Each has a code that does not change, so
+/// front-ends can show the message in their own language; `Display`
+/// gives it in English
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rule {
+    /// A member's name is blank
+    Username,
+    Phone,
+    SharesNegative,
+    LoanNegative,
+    InterestNegative,
+    RepaymentNegative,
+    RepaymentTooHigh,
+    AmountNegative,
+    AmountNotPositive,
+    AlreadyUndone,
+    /// The member of the record to undo has been deleted
+    MemberDeleted(String),
+    LaterRepayments,
+    EntryTooOld,
+    CurrencySymbol,
+    /// More decimal places than the most an amount can have
+    TooManyDecimals(u8),
+    DecimalSeparator,
+    ThousandsSeparator,
+    SameSeparators,
+    /// The setting with this key cannot be read
+    Setting(String),
+    /// The reason the password could not be hashed
+    Password(String),
+    /// A new group's name is blank
+    GroupName,
+    /// There is already a group with this name
+    GroupExists(String),
+    /// More than one database file is given on the command line
+    OneDatabase,
+    /// The command line option that needs a file path after it
+    NeedsPath(String),
+    UnknownArgument(String),
+    NoConfigFolder,
+    /// The name and path of a group whose file has gone
+    GroupFileMissing(String, String),
+    /// The path of a file to open that does not exist
+    FileMissing(String),
+    /// The path of a file to create that is already there
+    FileExists(String),
+}
+
+impl Rule {
+    /// The code of the rule, which stays the same between versions
+    pub fn code(&self) -> &'static str {
+        match self {
+            Rule::Username => "username",
+            Rule::Phone => "phone",
+            Rule::SharesNegative => "shares_negative",
+            Rule::LoanNegative => "loan_negative",
+            Rule::InterestNegative => "interest_negative",
+            Rule::RepaymentNegative => "repayment_negative",
+            Rule::RepaymentTooHigh => "repayment_too_high",
+            Rule::AmountNegative => "amount_negative",
+            Rule::AmountNotPositive => "amount_not_positive",
+            Rule::AlreadyUndone => "already_undone",
+            Rule::MemberDeleted(_) => "member_deleted",
+            Rule::LaterRepayments => "later_repayments",
+            Rule::EntryTooOld => "entry_too_old",
+            Rule::CurrencySymbol => "currency_symbol",
+            Rule::TooManyDecimals(_) => "too_many_decimals",
+            Rule::DecimalSeparator => "decimal_separator",
+            Rule::ThousandsSeparator => "thousands_separator",
+            Rule::SameSeparators => "same_separators",
+            Rule::Setting(_) => "setting",
+            Rule::Password(_) => "password",
+            Rule::GroupName => "group_name",
+            Rule::GroupExists(_) => "group_exists",
+            Rule::OneDatabase => "one_database",
+            Rule::NeedsPath(_) => "needs_path",
+            Rule::UnknownArgument(_) => "unknown_argument",
+            Rule::NoConfigFolder => "no_config_folder",
+            Rule::GroupFileMissing(_, _) => "group_file_missing",
+            Rule::FileMissing(_) => "file_missing",
+            Rule::FileExists(_) => "file_exists",
+        }
+    }
+
+    /// The values that go in the `{}`s of the message, in order
+    pub fn arguments(&self) -> Vec<String> {
+        match self {
+            Rule::MemberDeleted(value)
+            | Rule::Setting(value)
+            | Rule::Password(value)
+            | Rule::GroupExists(value)
+            | Rule::NeedsPath(value)
+            | Rule::UnknownArgument(value)
+            | Rule::FileMissing(value)
+            | Rule::FileExists(value) => vec![value.clone()],
+            Rule::TooManyDecimals(decimals) => vec![decimals.to_string()],
+            Rule::GroupFileMissing(name, path) => vec![name.clone(), path.clone()],
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rule::Username => write!(f, "Enter valid username"),
+            Rule::Phone => write!(f, "Enter a valid phone number"),
+            Rule::SharesNegative => write!(f, "Shares cannot be negative"),
+            Rule::LoanNegative => write!(f, "Loan ammount cannot be negative"),
+            Rule::InterestNegative => write!(f, "Interest cannot be negative"),
+            Rule::RepaymentNegative => write!(f, "Repayment ammount cannot be negative"),
+            Rule::RepaymentTooHigh => write!(f, "Repayment is higher than debt"),
+            Rule::AmountNegative => write!(f, "Amounts cannot be negative"),
+            Rule::AmountNotPositive => write!(f, "Amount must be more than zero"),
+            Rule::AlreadyUndone => write!(f, "This has already been undone"),
+            Rule::MemberDeleted(name) => write!(
+                f,
+                "{} has been deleted, restore a snapshot to bring them back",
+                name
+            ),
+            Rule::LaterRepayments => write!(f, "Later repayments depend on this, undo them first"),
+            Rule::EntryTooOld => {
+                write!(f, "This entry was recorded before entries could be undone")
+            }
+            Rule::CurrencySymbol => {
+                write!(f, "The currency symbol cannot have digits or signs in it")
+            }
+            Rule::TooManyDecimals(decimals) => {
+                write!(f, "Amounts can have at most {} decimal places", decimals)
+            }
+            Rule::DecimalSeparator => {
+                write!(f, "The decimal separator must be a mark such as . or ,")
+            }
+            Rule::ThousandsSeparator => write!(
+                f,
+                "The thousands separator must be a mark such as , or a space"
+            ),
+            Rule::SameSeparators => {
+                write!(f, "The thousands and decimal separators must be different")
+            }
+            Rule::Setting(key) => write!(f, "The setting {} is not valid", key),
+            Rule::Password(reason) => write!(f, "Password cannot be used: {}", reason),
+            Rule::GroupName => write!(f, "Enter a name for the group"),
+            Rule::GroupExists(name) => write!(f, "{} already exists", name),
+            Rule::OneDatabase => write!(f, "Only one database file can be given"),
+            Rule::NeedsPath(arg) => write!(f, "{} needs a file path", arg),
+            Rule::UnknownArgument(arg) => write!(f, "Unknown argument \"{}\"", arg),
+            Rule::NoConfigFolder => write!(f, "There is no config folder on this system"),
+            Rule::GroupFileMissing(name, path) => {
+                write!(f, "The file of {} is missing ({})", name, path)
+            }
+            Rule::FileMissing(path) => {
+                write!(f, "{} does not exist, use --new to create it", path)
+            }
+            Rule::FileExists(path) => write!(f, "{} already exists", path),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(err: rusqlite::Error) -> Self {
+        let (code, message) = match &err {
+            rusqlite::Error::SqliteFailure(failure, message) => (failure.code, message.clone()),
+            _ => return Error::Database(err),
+        };
+
+        match code {
+            ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked => Error::Locked,
+            ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase => {
+                Error::Schema("the file is not a readable Family Banking database".to_string())
+            }
+            ErrorCode::CannotOpen
+            | ErrorCode::PermissionDenied
+            | ErrorCode::ReadOnly
+            | ErrorCode::DiskFull
+            | ErrorCode::SystemIoFailure => Error::Io(io::Error::other(err.to_string())),
+            // SQLite gives no code of its own for a missing table or column
+            _ => match message {
+                Some(message)
+                    if message.starts_with("no such table")
+                        || message.starts_with("no such column") =>
+                {
+                    Error::Schema(message)
+                }
+                _ => Error::Database(err),
+            },
+        }
+    }
+}
@@ -0,0 +1,484 @@
+use std::{collections::BTreeMap, collections::HashSet, fs, path::Path};
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::{db_operations, model::Currency, Error, Result};
+
+/// Version of the JSON document format. Bump this whenever
+/// the layout of `Document` changes. Documents of older versions
+/// can still be imported, as the fields added since are optional:
+///
+/// - 2 added `reverses` to transactions and `transaction_id` to entries
+/// - 3 added `phone`, `joined` and `notes` to members
+pub const FORMAT_VERSION: u32 = 3;
+
+const FORMAT_NAME: &str = "family_banking";
+
+/// Everything in the books, apart from the admin password
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Document {
+    pub format: String,
+    pub version: u32,
+    pub settings: BTreeMap<String, String>,
+    pub profit: f64,
+    pub members: Vec<MemberRecord>,
+    pub loans: Vec<LoanRecord>,
+    pub transactions: Vec<TransactionRecord>,
+    pub categories: Vec<CategoryRecord>,
+    pub entries: Vec<EntryRecord>,
+    pub reconciliations: Vec<ReconciliationRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemberRecord {
+    pub id: i32,
+    pub name: String,
+    pub share: f64,
+    #[serde(default)]
+    pub phone: String,
+    #[serde(default)]
+    pub joined: Option<String>,
+    #[serde(default)]
+    pub notes: String,
+}
+
+/// The outstanding loan of a member
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoanRecord {
+    pub member_id: i32,
+    pub principal: f64,
+    pub interest: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionRecord {
+    pub id: i64,
+    pub date: String,
+    pub kind: String,
+    pub member_id: Option<i32>,
+    pub member_name: String,
+    pub amount: f64,
+    pub interest: f64,
+    /// Set on records posted to undo an earlier one
+    #[serde(default)]
+    pub reverses: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CategoryRecord {
+    pub id: i32,
+    pub name: String,
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntryRecord {
+    pub id: i64,
+    pub date: String,
+    pub category_id: i32,
+    pub description: String,
+    pub amount: f64,
+    #[serde(default)]
+    pub transaction_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReconciliationRecord {
+    pub id: i64,
+    pub date: String,
+    pub computed_cash: f64,
+    pub counted_cash: f64,
+    pub bank_balance: f64,
+    pub adjustment: f64,
+    pub reason: String,
+}
+
+/// Reads the whole of the books into a `Document`
+pub fn export_document(conn: &Connection) -> Result<Document> {
+    let settings = conn
+        .prepare("SELECT key, value FROM setting ORDER BY key")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<BTreeMap<String, String>>>()?;
+
+    let profit: f64 = conn.query_row("SELECT share FROM member WHERE memberId = 0", [], |row| {
+        row.get(0)
+    })?;
+
+    let mut members = Vec::new();
+    let mut loans = Vec::new();
+
+    let mut stmt = conn.prepare(
+        "
+        SELECT memberId, name, share, loan, interest, phone, joined, notes
+        FROM member
+        WHERE memberId != 0
+        ORDER BY memberId",
+    )?;
+    let mut rows = stmt.query([])?;
+
+    while let Some(row) = rows.next()? {
+        let member = MemberRecord {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            share: row.get(2)?,
+            phone: row.get(5)?,
+            joined: row.get(6)?,
+            notes: row.get(7)?,
+        };
+        let loan = LoanRecord {
+            member_id: member.id,
+            principal: row.get(3)?,
+            interest: row.get(4)?,
+        };
+
+        if loan.principal != 0.0 || loan.interest != 0.0 {
+            loans.push(loan);
+        }
+        members.push(member);
+    }
+
+    let transactions = conn
+        .prepare(
+            "
+            SELECT ledgerId, date, kind, memberId, memberName, amount, interest, reverses
+            FROM ledger
+            ORDER BY ledgerId",
+        )?
+        .query_map([], |row| {
+            Ok(TransactionRecord {
+                id: row.get(0)?,
+                date: row.get(1)?,
+                kind: row.get(2)?,
+                member_id: row.get(3)?,
+                member_name: row.get(4)?,
+                amount: row.get(5)?,
+                interest: row.get(6)?,
+                reverses: row.get(7)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<TransactionRecord>>>()?;
+
+    let categories = conn
+        .prepare("SELECT categoryId, name, kind FROM category ORDER BY categoryId")?
+        .query_map([], |row| {
+            Ok(CategoryRecord {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                kind: row.get(2)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<CategoryRecord>>>()?;
+
+    let entries = conn
+        .prepare(
+            "
+            SELECT entryId, date, categoryId, description, amount, ledgerId
+            FROM entry
+            ORDER BY entryId",
+        )?
+        .query_map([], |row| {
+            Ok(EntryRecord {
+                id: row.get(0)?,
+                date: row.get(1)?,
+                category_id: row.get(2)?,
+                description: row.get(3)?,
+                amount: row.get(4)?,
+                transaction_id: row.get(5)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<EntryRecord>>>()?;
+
+    let reconciliations = conn
+        .prepare(
+            "
+            SELECT reconciliationId, date, computedCash, countedCash, bankBalance, adjustment, reason
+            FROM reconciliation
+            ORDER BY reconciliationId",
+        )?
+        .query_map([], |row| {
+            Ok(ReconciliationRecord {
+                id: row.get(0)?,
+                date: row.get(1)?,
+                computed_cash: row.get(2)?,
+                counted_cash: row.get(3)?,
+                bank_balance: row.get(4)?,
+                adjustment: row.get(5)?,
+                reason: row.get(6)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<ReconciliationRecord>>>()?;
+
+    Ok(Document {
+        format: FORMAT_NAME.to_string(),
+        version: FORMAT_VERSION,
+        settings,
+        profit,
+        members,
+        loans,
+        transactions,
+        categories,
+        entries,
+        reconciliations,
+    })
+}
+
+/// Checks that the document can be imported without leaving the
+/// books inconsistent, before anything is written
+pub fn validate_document(document: &Document) -> Result<()> {
+    if document.format != FORMAT_NAME || document.version > FORMAT_VERSION {
+        return Err(Error::UnsupportedFormat(
+            document.format.clone(),
+            document.version,
+        ));
+    }
+
+    let invalid = |reason: String| Err(Error::InvalidDocument(reason));
+
+    let mut member_ids = HashSet::new();
+
+    for member in &document.members {
+        if member.id <= 0 {
+            return invalid(format!(
+                "member \"{}\" has invalid id {}",
+                member.name, member.id
+            ));
+        }
+        if !member_ids.insert(member.id) {
+            return invalid(format!("member id {} is used more than once", member.id));
+        }
+        if !member.share.is_finite() {
+            return invalid(format!("member \"{}\" has an invalid share", member.name));
+        }
+    }
+
+    let mut loan_member_ids = HashSet::new();
+
+    for loan in &document.loans {
+        if !member_ids.contains(&loan.member_id) {
+            return invalid(format!("loan refers to unknown member {}", loan.member_id));
+        }
+        if !loan_member_ids.insert(loan.member_id) {
+            return invalid(format!("member {} has more than one loan", loan.member_id));
+        }
+        if !loan.principal.is_finite() || !loan.interest.is_finite() {
+            return invalid(format!("loan of member {} is not a number", loan.member_id));
+        }
+    }
+
+    let mut transaction_ids = HashSet::new();
+
+    for transaction in &document.transactions {
+        if !transaction_ids.insert(transaction.id) {
+            return invalid(format!(
+                "transaction id {} is used more than once",
+                transaction.id
+            ));
+        }
+        if crate::ledger::LedgerKind::parse(&transaction.kind).is_none() {
+            return invalid(format!(
+                "transaction {} has unknown kind \"{}\"",
+                transaction.id, transaction.kind
+            ));
+        }
+        if let Some(member_id) = transaction.member_id {
+            if member_id != 0 && !member_ids.contains(&member_id) {
+                return invalid(format!(
+                    "transaction {} refers to unknown member {}",
+                    transaction.id, member_id
+                ));
+            }
+        }
+        if let Some(reversed_id) = transaction.reverses {
+            if !transaction_ids.contains(&reversed_id) || reversed_id == transaction.id {
+                return invalid(format!(
+                    "transaction {} reverses unknown transaction {}",
+                    transaction.id, reversed_id
+                ));
+            }
+        }
+    }
+
+    let mut category_ids = HashSet::new();
+
+    for category in &document.categories {
+        if !category_ids.insert(category.id) {
+            return invalid(format!(
+                "category id {} is used more than once",
+                category.id
+            ));
+        }
+        if category.kind != "expense" && category.kind != "income" {
+            return invalid(format!(
+                "category \"{}\" has unknown kind \"{}\"",
+                category.name, category.kind
+            ));
+        }
+    }
+
+    let mut entry_ids = HashSet::new();
+
+    for entry in &document.entries {
+        if !entry_ids.insert(entry.id) {
+            return invalid(format!("entry id {} is used more than once", entry.id));
+        }
+        if !category_ids.contains(&entry.category_id) {
+            return invalid(format!(
+                "entry {} refers to unknown category {}",
+                entry.id, entry.category_id
+            ));
+        }
+        if let Some(transaction_id) = entry.transaction_id {
+            if !transaction_ids.contains(&transaction_id) {
+                return invalid(format!(
+                    "entry {} refers to unknown transaction {}",
+                    entry.id, transaction_id
+                ));
+            }
+        }
+    }
+
+    let mut reconciliation_ids = HashSet::new();
+
+    for reconciliation in &document.reconciliations {
+        if !reconciliation_ids.insert(reconciliation.id) {
+            return invalid(format!(
+                "reconciliation id {} is used more than once",
+                reconciliation.id
+            ));
+        }
+    }
+
+    if !document.profit.is_finite() {
+        return invalid("profit is not a number".to_string());
+    }
+
+    if let Err(err) = Currency::from_settings(|key| document.settings.get(key).cloned()) {
+        return invalid(format!("the currency cannot be used: {}", err));
+    }
+
+    Ok(())
+}
+
+/// Replaces the books with the contents of the document. Nothing
+/// is changed unless the whole document is valid
+pub fn import_document(conn: &Connection, document: &Document) -> Result<()> {
+    validate_document(document)?;
+
+    db_operations::in_transaction(conn, |conn| {
+        conn.execute_batch(
+            "
+            DELETE FROM setting;
+            DELETE FROM ledger;
+            DELETE FROM entry;
+            DELETE FROM category;
+            DELETE FROM reconciliation;
+            DELETE FROM member;",
+        )?;
+
+        for (key, value) in &document.settings {
+            conn.execute(
+                "INSERT INTO setting (key, value) VALUES (?1, ?2);",
+                params![key, value],
+            )?;
+        }
+
+        conn.execute(
+            "INSERT INTO member (memberId, name, share, loan, interest) VALUES (0, 'Profits', ?1, 0, 0);",
+            params![document.profit],
+        )?;
+
+        for member in &document.members {
+            let loan = document
+                .loans
+                .iter()
+                .find(|loan| loan.member_id == member.id);
+
+            conn.execute(
+                "
+                INSERT INTO member (memberId, name, share, loan, interest, phone, joined, notes)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8);",
+                params![
+                    member.id,
+                    member.name,
+                    member.share,
+                    loan.map_or(0.0, |loan| loan.principal),
+                    loan.map_or(0.0, |loan| loan.interest),
+                    member.phone,
+                    member.joined,
+                    member.notes
+                ],
+            )?;
+        }
+
+        for transaction in &document.transactions {
+            conn.execute(
+                "INSERT INTO ledger VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8);",
+                params![
+                    transaction.id,
+                    transaction.date,
+                    transaction.kind,
+                    transaction.member_id,
+                    transaction.member_name,
+                    transaction.amount,
+                    transaction.interest,
+                    transaction.reverses
+                ],
+            )?;
+        }
+
+        for category in &document.categories {
+            conn.execute(
+                "INSERT INTO category VALUES (?1, ?2, ?3);",
+                params![category.id, category.name, category.kind],
+            )?;
+        }
+
+        for entry in &document.entries {
+            conn.execute(
+                "INSERT INTO entry VALUES (?1, ?2, ?3, ?4, ?5, ?6);",
+                params![
+                    entry.id,
+                    entry.date,
+                    entry.category_id,
+                    entry.description,
+                    entry.amount,
+                    entry.transaction_id
+                ],
+            )?;
+        }
+
+        for reconciliation in &document.reconciliations {
+            conn.execute(
+                "INSERT INTO reconciliation VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7);",
+                params![
+                    reconciliation.id,
+                    reconciliation.date,
+                    reconciliation.computed_cash,
+                    reconciliation.counted_cash,
+                    reconciliation.bank_balance,
+                    reconciliation.adjustment,
+                    reconciliation.reason
+                ],
+            )?;
+        }
+
+        Ok(())
+    })
+}
+
+/// Writes the books to a JSON file at `path`
+pub fn export_to_file(conn: &Connection, path: &Path) -> Result<()> {
+    let document = export_document(conn)?;
+
+    fs::write(path, serde_json::to_string_pretty(&document)?)?;
+
+    Ok(())
+}
+
+/// Replaces the books with the contents of the JSON file at `path`
+pub fn import_from_file(conn: &Connection, path: &Path) -> Result<()> {
+    let document: Document = serde_json::from_str(&fs::read_to_string(path)?)?;
+
+    import_document(conn, &document)
+}
@@ -0,0 +1,182 @@
+/// The kinds of records kept in the `ledger` table. Every
+/// operation that moves money adds one of these
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LedgerKind {
+    /// Shares bought by a member, or taken out if negative
+    Shares,
+    /// A loan given to a member, with the interest charged on it
+    Lend,
+    /// A repayment, split into the principal and the interest
+    Repay,
+    /// The outstanding debt of a member who was deleted
+    WriteOff,
+    /// An expense (negative) or other income (positive)
+    Entry,
+    /// A correction posted while reconciling the cash book
+    Adjustment,
+}
+
+impl LedgerKind {
+    /// The value stored in the `kind` column of the ledger table
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LedgerKind::Shares => "shares",
+            LedgerKind::Lend => "lend",
+            LedgerKind::Repay => "repay",
+            LedgerKind::WriteOff => "writeoff",
+            LedgerKind::Entry => "entry",
+            LedgerKind::Adjustment => "adjustment",
+        }
+    }
+
+    /// Reads a value stored by `as_str`
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "shares" => Some(LedgerKind::Shares),
+            "lend" => Some(LedgerKind::Lend),
+            "repay" => Some(LedgerKind::Repay),
+            "writeoff" => Some(LedgerKind::WriteOff),
+            "entry" => Some(LedgerKind::Entry),
+            "adjustment" => Some(LedgerKind::Adjustment),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LedgerRecord {
+    pub id: i64,
+    pub date: String,
+    pub kind: LedgerKind,
+    pub member_id: Option<i32>,
+    pub member_name: String,
+    pub amount: f64,
+    pub interest: f64,
+    /// The record this one cancels out, if it was posted to undo it
+    pub reverses: Option<i64>,
+}
+
+impl LedgerRecord {
+    /// How much this record changed the total shares,
+    /// the available cash and the profit, in that order
+    pub fn effect(&self) -> (f64, f64, f64) {
+        match self.kind {
+            LedgerKind::Shares => (self.amount, self.amount, 0.0),
+            LedgerKind::Lend => (0.0, -self.amount, 0.0),
+            LedgerKind::Repay => (0.0, self.amount + self.interest, self.interest),
+            LedgerKind::WriteOff => (0.0, self.amount, 0.0),
+            LedgerKind::Entry | LedgerKind::Adjustment => (0.0, self.amount, self.amount),
+        }
+    }
+}
+
+/// A loan given to a member, and how much has been paid back
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LoanProgress {
+    /// The ledger record of the loan
+    pub id: i64,
+    pub date: String,
+    pub loan: f64,
+    pub interest: f64,
+    pub repaid: f64,
+}
+
+impl LoanProgress {
+    pub fn remaining(&self) -> f64 {
+        self.loan + self.interest - self.repaid
+    }
+}
+
+/// Follows the loans and repayments of one member through their ledger
+/// `records`. The ledger only keeps the total a member owes, so each
+/// repayment is counted against the oldest loan that is still owed,
+/// and an undone repayment is taken back from the newest loans first
+pub fn loan_progress(records: &[LedgerRecord]) -> Vec<LoanProgress> {
+    let mut loans: Vec<LoanProgress> = Vec::new();
+
+    for record in records {
+        match record.kind {
+            LedgerKind::Lend if record.amount >= 0.0 => loans.push(LoanProgress {
+                id: record.id,
+                date: record.date.clone(),
+                loan: record.amount,
+                interest: record.interest,
+                repaid: 0.0,
+            }),
+            // An undone loan is no longer owed at all
+            LedgerKind::Lend => loans.retain(|loan| Some(loan.id) != record.reverses),
+            LedgerKind::Repay if record.amount + record.interest >= 0.0 => {
+                let mut amount = record.amount + record.interest;
+
+                for loan in loans.iter_mut() {
+                    let paid = amount.min(loan.remaining()).max(0.0);
+
+                    loan.repaid += paid;
+                    amount -= paid;
+                }
+            }
+            LedgerKind::Repay => {
+                let mut amount = -(record.amount + record.interest);
+
+                for loan in loans.iter_mut().rev() {
+                    let taken_back = amount.min(loan.repaid);
+
+                    loan.repaid -= taken_back;
+                    amount -= taken_back;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    loans
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TotalsPoint {
+    pub date: String,
+    pub shares: f64,
+    pub cash: f64,
+    pub profit: f64,
+}
+
+/// Works backwards from the current totals through the ledger
+/// to find what the totals were after each record. The first
+/// point is the state before the oldest record
+pub fn totals_history(
+    records: &[LedgerRecord],
+    shares: f64,
+    cash: f64,
+    profit: f64,
+) -> Vec<TotalsPoint> {
+    let mut current = (shares, cash, profit);
+    let mut points = Vec::with_capacity(records.len() + 1);
+
+    for record in records.iter().rev() {
+        points.push(TotalsPoint {
+            date: record.date.clone(),
+            shares: current.0,
+            cash: current.1,
+            profit: current.2,
+        });
+
+        let effect = record.effect();
+        current = (
+            current.0 - effect.0,
+            current.1 - effect.1,
+            current.2 - effect.2,
+        );
+    }
+
+    if let Some(first) = records.first() {
+        points.push(TotalsPoint {
+            date: first.date.clone(),
+            shares: current.0,
+            cash: current.1,
+            profit: current.2,
+        });
+    }
+
+    points.reverse();
+    points
+}
@@ -0,0 +1,23 @@
+//! The books of a savings group: members, their shares and loans,
+//! the cash book and the ledger, kept in an SQLite database. This
+//! crate has no user interface, so the same rules can be used by the
+//! window app, the command line and any other front-end.
+
+pub mod backup;
+pub mod config;
+pub mod db_operations;
+pub mod error;
+pub mod export;
+pub mod ledger;
+pub mod model;
+
+pub use error::{Error, Result};
+
+const HASH_SALT: &[u8; 17] = b"5aP3v*4!1bN<x4i&3";
+
+/// Rounds number to 2 decimal places and adds commas for
+/// every thousands. Amounts of money are written with the
+/// group's `model::Currency` instead
+pub fn format_decimal(number: f64) -> String {
+    model::Currency::default().format_number(number)
+}
@@ -0,0 +1,598 @@
+use std::{cmp::Ordering, fmt};
+
+use crate::{
+    error::{Error, Result, Rule},
+    ledger::{LedgerRecord, LoanProgress},
+};
+
+/// A member of the group, with their share of the total
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Member {
+    pub id: i32,
+    pub name: String,
+    pub shares: f64,
+    /// Percentage of all shares owned by the member
+    pub percent: f64,
+    pub loan: f64,
+    pub interest: f64,
+    /// Owes money, but has neither borrowed nor
+    /// repaid anything in the last `OVERDUE_DAYS`
+    pub overdue: bool,
+    /// Nothing has been recorded for the member in the last `INACTIVE_DAYS`
+    pub inactive: bool,
+}
+
+impl Member {
+    /// Everything the member still owes
+    pub fn debt(&self) -> f64 {
+        self.loan + self.interest
+    }
+}
+
+/// Days after their last loan or repayment that a member
+/// who still owes money is counted as overdue
+pub const OVERDUE_DAYS: u32 = 30;
+
+/// Days without any transactions after which a member is inactive
+pub const INACTIVE_DAYS: u32 = 90;
+
+/// The quick filters above the tables of members
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MemberFilter {
+    #[default]
+    All,
+    HasDebt,
+    Overdue,
+    ZeroShares,
+    Inactive,
+}
+
+impl MemberFilter {
+    pub const ALL: [MemberFilter; 5] = [
+        MemberFilter::All,
+        MemberFilter::HasDebt,
+        MemberFilter::Overdue,
+        MemberFilter::ZeroShares,
+        MemberFilter::Inactive,
+    ];
+
+    pub fn matches(&self, member: &Member) -> bool {
+        match self {
+            MemberFilter::All => true,
+            MemberFilter::HasDebt => member.debt() > 0.0,
+            MemberFilter::Overdue => member.overdue,
+            MemberFilter::ZeroShares => member.shares == 0.0,
+            MemberFilter::Inactive => member.inactive,
+        }
+    }
+}
+
+/// What the tables of members are narrowed down to: the members
+/// whose name contains the search text and who pass the filter
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MemberQuery {
+    pub search: String,
+    pub filter: MemberFilter,
+}
+
+impl MemberQuery {
+    pub fn matches(&self, member: &Member) -> bool {
+        member
+            .name
+            .to_lowercase()
+            .contains(&self.search.trim().to_lowercase())
+            && self.filter.matches(member)
+    }
+}
+
+/// A column the tables of members can be sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortColumn {
+    Name,
+    Shares,
+    Percent,
+    Debt,
+}
+
+impl SortColumn {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortColumn::Name => "name",
+            SortColumn::Shares => "shares",
+            SortColumn::Percent => "percent",
+            SortColumn::Debt => "debt",
+        }
+    }
+
+    /// Reads a value stored by `as_str`
+    pub fn parse(column: &str) -> Option<Self> {
+        match column {
+            "name" => Some(SortColumn::Name),
+            "shares" => Some(SortColumn::Shares),
+            "percent" => Some(SortColumn::Percent),
+            "debt" => Some(SortColumn::Debt),
+            _ => None,
+        }
+    }
+}
+
+/// The order a table of members is shown in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemberSort {
+    pub column: SortColumn,
+    pub descending: bool,
+}
+
+impl Default for MemberSort {
+    fn default() -> Self {
+        MemberSort {
+            column: SortColumn::Name,
+            descending: false,
+        }
+    }
+}
+
+impl MemberSort {
+    /// The order after the header of `column` is clicked. Clicking the
+    /// sorted column again reverses it, while amounts are first sorted
+    /// from the largest down and names from A to Z
+    pub fn toggled(self, column: SortColumn) -> Self {
+        MemberSort {
+            column,
+            descending: if column == self.column {
+                !self.descending
+            } else {
+                column != SortColumn::Name
+            },
+        }
+    }
+
+    /// Sorts `members` in this order. Members that are equal
+    /// keep the order they were added in
+    pub fn sort(&self, members: &mut [Member]) {
+        members.sort_by(|a, b| {
+            let ordering = match self.column {
+                SortColumn::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+                SortColumn::Shares => a.shares.partial_cmp(&b.shares).unwrap_or(Ordering::Equal),
+                SortColumn::Percent => a.percent.partial_cmp(&b.percent).unwrap_or(Ordering::Equal),
+                SortColumn::Debt => a.debt().partial_cmp(&b.debt()).unwrap_or(Ordering::Equal),
+            };
+
+            if self.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+    }
+
+    /// The value kept in the setting table, such as "shares desc"
+    pub fn as_setting(&self) -> String {
+        format!(
+            "{} {}",
+            self.column.as_str(),
+            if self.descending { "desc" } else { "asc" }
+        )
+    }
+
+    pub fn from_setting(value: &str) -> Option<Self> {
+        let (column, order) = value.split_once(' ')?;
+
+        Some(MemberSort {
+            column: SortColumn::parse(column)?,
+            descending: match order {
+                "asc" => false,
+                "desc" => true,
+                _ => return None,
+            },
+        })
+    }
+}
+
+/// The contact details and notes kept for a member
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MemberProfile {
+    pub phone: String,
+    /// The day the member was added, if it is known
+    pub joined: Option<String>,
+    pub notes: String,
+}
+
+/// Everything shown on the page of one member
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MemberDetails {
+    pub member: Member,
+    pub profile: MemberProfile,
+    /// Shares bought, or taken out if negative, oldest first
+    pub contributions: Vec<LedgerRecord>,
+    pub loans: Vec<LoanProgress>,
+    pub repayments: Vec<LedgerRecord>,
+    /// What the member would be paid if the group shared out today,
+    /// once every loan has been repaid: their shares and their part
+    /// of the profit, less what they owe
+    pub share_out: f64,
+}
+
+/// How a repayment is divided between the loan and its interest
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Repayment {
+    pub loan: f64,
+    pub interest: f64,
+}
+
+impl Repayment {
+    /// Splits `amount` paid against `loan`. The loan is
+    /// paid off first, and anything over it goes to interest
+    pub fn split(loan: f64, amount: f64) -> Self {
+        if loan > amount {
+            Repayment {
+                loan: amount,
+                interest: 0.0,
+            }
+        } else {
+            Repayment {
+                loan,
+                interest: amount - loan,
+            }
+        }
+    }
+
+    /// Splits `amount` paid against a debt of `loan` and `interest`
+    /// the way `rule` says. Paying off the whole debt clears both,
+    /// whatever the rule
+    pub fn allocate(rule: AllocationRule, loan: f64, interest: f64, amount: f64) -> Self {
+        let debt = loan + interest;
+
+        if amount >= debt {
+            return Repayment::split(loan, amount);
+        }
+
+        match rule {
+            AllocationRule::PrincipalFirst => Repayment::split(loan, amount),
+            AllocationRule::InterestFirst => {
+                let interest = interest.min(amount);
+
+                Repayment {
+                    loan: amount - interest,
+                    interest,
+                }
+            }
+            AllocationRule::Proportional => {
+                let loan = amount * loan / debt;
+
+                Repayment {
+                    loan,
+                    interest: amount - loan,
+                }
+            }
+        }
+    }
+}
+
+/// Which part of a debt a repayment goes to first. Each group
+/// chooses its own, and pays the loan off first by default
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AllocationRule {
+    #[default]
+    PrincipalFirst,
+    InterestFirst,
+    /// Shared between the loan and the interest in the
+    /// same proportion as they are owed
+    Proportional,
+}
+
+impl AllocationRule {
+    pub const ALL: [AllocationRule; 3] = [
+        AllocationRule::PrincipalFirst,
+        AllocationRule::InterestFirst,
+        AllocationRule::Proportional,
+    ];
+    /// The key the rule is saved under in the settings
+    pub const SETTING: &'static str = "repayment.allocation";
+
+    pub fn as_setting(self) -> &'static str {
+        match self {
+            AllocationRule::PrincipalFirst => "principal_first",
+            AllocationRule::InterestFirst => "interest_first",
+            AllocationRule::Proportional => "proportional",
+        }
+    }
+
+    /// The rule saved as `setting`, or the default if it is not known
+    pub fn from_setting(setting: &str) -> Self {
+        AllocationRule::ALL
+            .iter()
+            .copied()
+            .find(|rule| rule.as_setting() == setting)
+            .unwrap_or_default()
+    }
+}
+
+/// The totals shown on the Home tab
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Summary {
+    pub total_shares: f64,
+    /// Loans and interest still owed by the members
+    pub total_debt: f64,
+    pub total_loans: f64,
+    pub total_cash: f64,
+    pub profit: f64,
+    pub total_expenses: f64,
+    pub total_income: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EntryKind {
+    #[default]
+    Expense,
+    Income,
+}
+
+impl EntryKind {
+    /// Reads the value stored in the `kind` column of the category table
+    pub fn parse(kind: &str) -> Self {
+        match kind {
+            "income" => EntryKind::Income,
+            _ => EntryKind::Expense,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Category {
+    pub id: i32,
+    pub name: String,
+    pub kind: EntryKind,
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// An expense or other income recorded in the cash book
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Entry {
+    pub date: String,
+    pub category: String,
+    pub kind: EntryKind,
+    pub description: String,
+    pub amount: f64,
+    /// The ledger record the entry was posted as, unless it
+    /// was recorded before entries were linked to the ledger
+    pub ledger_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Reconciliation {
+    pub date: String,
+    pub computed_cash: f64,
+    pub counted_cash: f64,
+    pub bank_balance: f64,
+    pub adjustment: f64,
+    pub reason: String,
+}
+
+impl Reconciliation {
+    /// How far the counted cash and bank balance were
+    /// from the cash computed from the books
+    pub fn variance(&self) -> f64 {
+        self.counted_cash + self.bank_balance - self.computed_cash
+    }
+}
+
+/// Whether the currency symbol goes before or after an amount
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SymbolPosition {
+    #[default]
+    Before,
+    After,
+}
+
+impl SymbolPosition {
+    pub const ALL: [SymbolPosition; 2] = [SymbolPosition::Before, SymbolPosition::After];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SymbolPosition::Before => "before",
+            SymbolPosition::After => "after",
+        }
+    }
+}
+
+/// How a group writes amounts of money, used both to show
+/// them and to read the amounts typed in. Kwacha by default
+#[derive(Debug, Clone, PartialEq)]
+pub struct Currency {
+    pub symbol: String,
+    pub position: SymbolPosition,
+    /// Amounts are rounded to this many places
+    pub decimals: u8,
+    /// Put between each group of three digits, if set
+    pub grouping: Option<char>,
+    pub decimal_separator: char,
+}
+
+impl Currency {
+    pub const MAX_DECIMALS: u8 = 4;
+    /// The keys the currency is saved under in the settings
+    pub const SYMBOL_SETTING: &'static str = "currency.symbol";
+    pub const POSITION_SETTING: &'static str = "currency.position";
+    pub const DECIMALS_SETTING: &'static str = "currency.decimals";
+    pub const GROUPING_SETTING: &'static str = "currency.grouping";
+    pub const DECIMAL_SEPARATOR_SETTING: &'static str = "currency.decimal_separator";
+
+    /// Checks that amounts written this way can be read back
+    pub fn validate(&self) -> Result<()> {
+        let is_number_part = |c: char| c.is_ascii_digit() || c == '-' || c == '+';
+
+        if self.symbol.chars().any(is_number_part) {
+            return Err(Error::Validation(Rule::CurrencySymbol));
+        }
+        if self.decimals > Currency::MAX_DECIMALS {
+            return Err(Error::Validation(Rule::TooManyDecimals(
+                Currency::MAX_DECIMALS,
+            )));
+        }
+        if is_number_part(self.decimal_separator) || self.decimal_separator.is_whitespace() {
+            return Err(Error::Validation(Rule::DecimalSeparator));
+        }
+        match self.grouping {
+            Some(grouping) if is_number_part(grouping) => {
+                Err(Error::Validation(Rule::ThousandsSeparator))
+            }
+            Some(grouping) if grouping == self.decimal_separator => {
+                Err(Error::Validation(Rule::SameSeparators))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// `number` rounded and grouped, without the currency symbol
+    pub fn format_number(&self, number: f64) -> String {
+        let scale = 10f64.powi(self.decimals.into());
+        let rounded = (number.abs() * scale).round() / scale;
+        let digits = format!("{:.*}", self.decimals.into(), rounded);
+        let (whole, fraction) = digits.split_once('.').unwrap_or((&digits, ""));
+        let mut formatted = String::new();
+
+        if number < 0.0 && rounded > 0.0 {
+            formatted.push('-');
+        }
+        for (i, digit) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i) % 3 == 0 {
+                formatted.extend(self.grouping);
+            }
+            formatted.push(digit);
+        }
+        if !fraction.is_empty() {
+            formatted.push(self.decimal_separator);
+            formatted.push_str(fraction);
+        }
+
+        formatted
+    }
+
+    /// `amount` as the group writes money, with the currency symbol
+    pub fn format(&self, amount: f64) -> String {
+        let number = self.format_number(amount);
+        let (sign, number) = match number.strip_prefix('-') {
+            Some(number) => ("-", number),
+            None => ("", number.as_str()),
+        };
+
+        match self.position {
+            _ if self.symbol.is_empty() => format!("{}{}", sign, number),
+            SymbolPosition::Before => format!("{}{}{}", sign, self.symbol, number),
+            SymbolPosition::After => format!("{}{} {}", sign, number, self.symbol),
+        }
+    }
+
+    /// Reads an amount typed in, with or without the currency symbol
+    /// and the thousands separators. Returns `None` if it is not one
+    pub fn parse(&self, text: &str) -> Option<f64> {
+        let text = if self.symbol.is_empty() {
+            text.to_string()
+        } else {
+            text.replace(&self.symbol, "")
+        };
+        let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+        let (whole, fraction) = match text.split_once(self.decimal_separator) {
+            Some((whole, fraction)) => (whole, Some(fraction)),
+            None => (text.as_str(), None),
+        };
+        // Thousands are only grouped before the decimal separator
+        let mut number: String = whole
+            .chars()
+            .filter(|c| Some(*c) != self.grouping)
+            .collect();
+
+        if let Some(fraction) = fraction {
+            number.push('.');
+            number.push_str(fraction);
+        }
+
+        number
+            .parse::<f64>()
+            .ok()
+            .filter(|number| number.is_finite())
+    }
+
+    /// The settings the currency is saved as
+    pub fn as_settings(&self) -> Vec<(&'static str, String)> {
+        vec![
+            (Currency::SYMBOL_SETTING, self.symbol.clone()),
+            (
+                Currency::POSITION_SETTING,
+                self.position.as_str().to_string(),
+            ),
+            (Currency::DECIMALS_SETTING, self.decimals.to_string()),
+            (
+                Currency::GROUPING_SETTING,
+                self.grouping.map(String::from).unwrap_or_default(),
+            ),
+            (
+                Currency::DECIMAL_SEPARATOR_SETTING,
+                self.decimal_separator.to_string(),
+            ),
+        ]
+    }
+
+    /// The currency saved in `settings`, looked up with `setting`.
+    /// Whatever is not saved is left as it is by default
+    pub fn from_settings(setting: impl Fn(&str) -> Option<String>) -> Result<Currency> {
+        let default = Currency::default();
+        let invalid = |key: &str| Error::Validation(Rule::Setting(key.to_string()));
+        let single_char = |key: &str, value: String| {
+            let mut chars = value.chars();
+
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Ok(c),
+                _ => Err(invalid(key)),
+            }
+        };
+
+        let currency = Currency {
+            symbol: setting(Currency::SYMBOL_SETTING).unwrap_or(default.symbol),
+            position: match setting(Currency::POSITION_SETTING) {
+                Some(value) => SymbolPosition::ALL
+                    .iter()
+                    .copied()
+                    .find(|position| position.as_str() == value)
+                    .ok_or_else(|| invalid(Currency::POSITION_SETTING))?,
+                None => default.position,
+            },
+            decimals: match setting(Currency::DECIMALS_SETTING) {
+                Some(value) => value
+                    .parse()
+                    .map_err(|_| invalid(Currency::DECIMALS_SETTING))?,
+                None => default.decimals,
+            },
+            grouping: match setting(Currency::GROUPING_SETTING) {
+                Some(value) if value.is_empty() => None,
+                Some(value) => Some(single_char(Currency::GROUPING_SETTING, value)?),
+                None => default.grouping,
+            },
+            decimal_separator: match setting(Currency::DECIMAL_SEPARATOR_SETTING) {
+                Some(value) => single_char(Currency::DECIMAL_SEPARATOR_SETTING, value)?,
+                None => default.decimal_separator,
+            },
+        };
+
+        currency.validate()?;
+        Ok(currency)
+    }
+}
+
+impl Default for Currency {
+    fn default() -> Self {
+        Currency {
+            symbol: "K".to_string(),
+            position: SymbolPosition::Before,
+            decimals: 2,
+            grouping: Some(','),
+            decimal_separator: '.',
+        }
+    }
+}
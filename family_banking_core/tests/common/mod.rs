@@ -0,0 +1,37 @@
+//! Books kept in memory for the tests, so that each test
+//! starts from its own empty, fully migrated database
+
+#![allow(dead_code)]
+
+use family_banking_core::db_operations;
+use rusqlite::Connection;
+
+/// Money is compared to the nearest thousandth, as the sums of
+/// many floating point amounts are rarely exact
+pub const TOLERANCE: f64 = 1e-3;
+
+pub fn new_books() -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+
+    db_operations::get_password(&conn).unwrap();
+    db_operations::update_db_schema(&conn).unwrap();
+    conn
+}
+
+/// Books with one member, Ann, who has shares and an outstanding loan
+pub fn books_with_a_loan() -> Connection {
+    let conn = new_books();
+
+    db_operations::store_new_user(&conn, "Ann".to_string(), 500.0).unwrap();
+    db_operations::borrow_debt(&conn, 1, 200.0, 20.0).unwrap();
+    conn
+}
+
+pub fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < TOLERANCE,
+        "expected {}, got {}",
+        expected,
+        actual
+    );
+}
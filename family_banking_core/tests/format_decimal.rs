@@ -0,0 +1,104 @@
+use family_banking_core::{
+    format_decimal,
+    model::{Currency, SymbolPosition},
+};
+
+#[test]
+fn whole_numbers_get_a_comma_every_three_digits() {
+    assert_eq!(format_decimal(0.0), "0.00");
+    assert_eq!(format_decimal(999.0), "999.00");
+    assert_eq!(format_decimal(1000.0), "1,000.00");
+    assert_eq!(format_decimal(123456.0), "123,456.00");
+    assert_eq!(format_decimal(1234567.0), "1,234,567.00");
+}
+
+#[test]
+fn decimals_are_rounded_to_two_places() {
+    assert_eq!(format_decimal(0.5), "0.50");
+    assert_eq!(format_decimal(12.34), "12.34");
+    assert_eq!(format_decimal(12.349), "12.35");
+    assert_eq!(format_decimal(0.999), "1.00");
+    assert_eq!(format_decimal(1234.5678), "1,234.57");
+    assert_eq!(format_decimal(999.996), "1,000.00");
+}
+
+#[test]
+fn commas_only_go_in_the_whole_part() {
+    assert_eq!(format_decimal(100.1234), "100.12");
+    assert_eq!(format_decimal(1000000.01), "1,000,000.01");
+}
+
+#[test]
+fn floating_point_noise_is_hidden() {
+    assert_eq!(format_decimal(0.1 + 0.2), "0.30");
+}
+
+#[test]
+fn negative_numbers_keep_their_sign_in_front() {
+    assert_eq!(format_decimal(-5.0), "-5.00");
+    assert_eq!(format_decimal(-1234.5), "-1,234.50");
+    assert_eq!(format_decimal(-999999.994), "-999,999.99");
+    assert_eq!(format_decimal(-0.001), "0.00");
+}
+
+fn euro() -> Currency {
+    Currency {
+        symbol: "€".to_string(),
+        position: SymbolPosition::After,
+        decimals: 2,
+        grouping: Some('.'),
+        decimal_separator: ',',
+    }
+}
+
+#[test]
+fn amounts_are_written_the_way_the_group_chose() {
+    assert_eq!(Currency::default().format(1234.5), "K1,234.50");
+    assert_eq!(Currency::default().format(-20.0), "-K20.00");
+    assert_eq!(euro().format(1234567.891), "1.234.567,89 €");
+    assert_eq!(euro().format(-0.5), "-0,50 €");
+
+    let whole_shillings = Currency {
+        symbol: String::new(),
+        decimals: 0,
+        grouping: None,
+        ..Currency::default()
+    };
+
+    assert_eq!(whole_shillings.format(12345.5), "12346");
+}
+
+#[test]
+fn amounts_typed_in_are_read_the_way_the_group_writes_them() {
+    assert_eq!(Currency::default().parse("K1,234.50"), Some(1234.5));
+    assert_eq!(Currency::default().parse(" 20 "), Some(20.0));
+    assert_eq!(Currency::default().parse("-5"), Some(-5.0));
+    assert_eq!(euro().parse("1.234,5 €"), Some(1234.5));
+    assert_eq!(euro().parse("12,75"), Some(12.75));
+    assert_eq!(euro().parse("1,2,3"), None);
+    assert_eq!(Currency::default().parse(""), None);
+    assert_eq!(Currency::default().parse("inf"), None);
+    assert_eq!(Currency::default().parse("twenty"), None);
+}
+
+#[test]
+fn currencies_that_cannot_be_read_back_are_refused() {
+    let same_separators = Currency {
+        grouping: Some('.'),
+        ..Currency::default()
+    };
+    let too_precise = Currency {
+        decimals: 5,
+        ..Currency::default()
+    };
+    let digit_symbol = Currency {
+        symbol: "K1".to_string(),
+        ..Currency::default()
+    };
+
+    assert!(Currency::default().validate().is_ok());
+    assert!(euro().validate().is_ok());
+    assert!(same_separators.validate().is_err());
+    assert!(too_precise.validate().is_err());
+    assert!(digit_symbol.validate().is_err());
+}
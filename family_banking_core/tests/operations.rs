@@ -0,0 +1,897 @@
+//! The accounting rules of each operation in `db_operations`,
+//! checked against books kept in memory
+
+use family_banking_core::{
+    db_operations, export,
+    ledger::LedgerKind,
+    model::{
+        AllocationRule, Currency, EntryKind, MemberFilter, MemberQuery, MemberSort, Repayment,
+        SortColumn, SymbolPosition,
+    },
+    Error,
+};
+use rusqlite::Connection;
+
+mod common;
+
+use common::{assert_close, books_with_a_loan, new_books};
+
+fn category(
+    conn: &Connection,
+    name: &str,
+    kind: EntryKind,
+) -> family_banking_core::model::Category {
+    db_operations::fetch_categories(conn)
+        .unwrap()
+        .into_iter()
+        .find(|category| category.name == name && category.kind == kind)
+        .unwrap()
+}
+
+fn ledger_kinds(conn: &Connection) -> Vec<LedgerKind> {
+    db_operations::fetch_ledger(conn)
+        .unwrap()
+        .iter()
+        .map(|record| record.kind)
+        .collect()
+}
+
+#[test]
+fn new_books_have_every_table_and_no_members() {
+    let conn = new_books();
+
+    for table in [
+        "admin",
+        "member",
+        "reconciliation",
+        "category",
+        "entry",
+        "ledger",
+        "setting",
+    ] {
+        assert!(
+            db_operations::table_exists(&conn, table).unwrap(),
+            "{}",
+            table
+        );
+    }
+
+    assert_eq!(db_operations::pending_migrations(&conn).unwrap(), 0);
+    assert!(!db_operations::has_members(&conn).unwrap());
+    assert!(db_operations::members(&conn).unwrap().is_empty());
+    assert_eq!(db_operations::summary(&conn).unwrap(), Default::default());
+}
+
+#[test]
+fn update_db_schema_can_be_run_again() {
+    let conn = new_books();
+
+    db_operations::update_db_schema(&conn).unwrap();
+
+    assert_eq!(db_operations::pending_migrations(&conn).unwrap(), 0);
+}
+
+#[test]
+fn a_new_database_has_no_password_until_one_is_stored() {
+    let conn = new_books();
+
+    assert_eq!(db_operations::get_password(&conn).unwrap(), None);
+
+    db_operations::store_password(&conn, "secret1".to_string()).unwrap();
+
+    let hash = db_operations::get_password(&conn).unwrap().unwrap();
+
+    assert!(db_operations::verify_password(&hash, "secret1"));
+    assert!(!db_operations::verify_password(&hash, "secret2"));
+    assert!(!db_operations::verify_password("not a hash", "secret1"));
+}
+
+#[test]
+fn get_password_adds_a_missing_admin_row() {
+    let conn = new_books();
+
+    conn.execute("DELETE FROM admin", []).unwrap();
+
+    assert_eq!(db_operations::get_password(&conn).unwrap(), None);
+    assert_eq!(
+        conn.query_row("SELECT COUNT(*) FROM admin", [], |row| row.get::<_, i32>(0))
+            .unwrap(),
+        1
+    );
+}
+
+#[test]
+fn a_missing_profits_row_is_a_schema_error() {
+    let conn = new_books();
+
+    conn.execute("DELETE FROM member WHERE memberId = 0", [])
+        .unwrap();
+
+    assert!(matches!(
+        db_operations::members(&conn),
+        Err(Error::Schema(_))
+    ));
+}
+
+#[test]
+fn store_new_user_buys_shares_with_cash() {
+    let conn = new_books();
+
+    db_operations::store_new_user(&conn, "Ann".to_string(), 500.0).unwrap();
+    db_operations::store_new_user(&conn, "Ben".to_string(), 1500.0).unwrap();
+
+    let members = db_operations::members(&conn).unwrap();
+    let summary = db_operations::summary(&conn).unwrap();
+
+    assert_eq!(members.len(), 2);
+    assert_eq!(members[0].name, "Ann");
+    assert_close(members[0].percent, 25.0);
+    assert_close(members[1].percent, 75.0);
+    assert_close(summary.total_shares, 2000.0);
+    assert_close(summary.total_cash, 2000.0);
+    assert_eq!(
+        ledger_kinds(&conn),
+        [LedgerKind::Shares, LedgerKind::Shares]
+    );
+}
+
+#[test]
+fn percent_is_zero_while_nobody_has_shares() {
+    let conn = new_books();
+
+    db_operations::store_new_user(&conn, "Ann".to_string(), 0.0).unwrap();
+    db_operations::store_new_user(&conn, "Ben".to_string(), 0.0).unwrap();
+
+    for member in db_operations::members(&conn).unwrap() {
+        assert_eq!(member.percent, 0.0);
+    }
+}
+
+#[test]
+fn store_new_user_needs_a_name() {
+    let conn = new_books();
+
+    assert!(matches!(
+        db_operations::store_new_user(&conn, "  ".to_string(), 10.0),
+        Err(Error::Validation(_))
+    ));
+    assert!(!db_operations::has_members(&conn).unwrap());
+}
+
+#[test]
+fn shares_cannot_be_negative() {
+    let conn = books_with_a_loan();
+
+    assert!(matches!(
+        db_operations::store_new_user(&conn, "Bob".to_string(), -50.0),
+        Err(Error::Validation(_))
+    ));
+    assert!(matches!(
+        db_operations::edit_user(&conn, 1, "Ann".to_string(), -1.0),
+        Err(Error::Validation(_))
+    ));
+
+    let members = db_operations::members(&conn).unwrap();
+
+    assert_eq!(members.len(), 1);
+    assert_close(members[0].shares, 500.0);
+    assert_close(members[0].percent, 100.0);
+}
+
+#[test]
+fn edit_user_logs_only_the_change_in_shares() {
+    let conn = new_books();
+
+    db_operations::store_new_user(&conn, "Ann".to_string(), 500.0).unwrap();
+    db_operations::edit_user(&conn, 1, "Anne".to_string(), 500.0).unwrap();
+    db_operations::edit_user(&conn, 1, "Anne".to_string(), 700.0).unwrap();
+
+    let ledger = db_operations::fetch_ledger(&conn).unwrap();
+
+    assert_eq!(db_operations::members(&conn).unwrap()[0].name, "Anne");
+    assert_eq!(ledger.len(), 2);
+    assert_close(ledger[1].amount, 200.0);
+    assert_close(db_operations::summary(&conn).unwrap().total_cash, 700.0);
+}
+
+#[test]
+fn edit_user_rejects_unknown_members_and_empty_names() {
+    let conn = books_with_a_loan();
+
+    assert!(matches!(
+        db_operations::edit_user(&conn, 7, "Zed".to_string(), 1.0),
+        Err(Error::NotFound(_))
+    ));
+    assert!(matches!(
+        db_operations::edit_user(&conn, 0, "Profits".to_string(), 1.0),
+        Err(Error::NotFound(_))
+    ));
+    assert!(matches!(
+        db_operations::edit_user(&conn, 1, String::new(), 1.0),
+        Err(Error::Validation(_))
+    ));
+}
+
+#[test]
+fn delete_user_pays_out_shares_and_writes_off_debt() {
+    let conn = books_with_a_loan();
+
+    db_operations::store_new_user(&conn, "Ben".to_string(), 300.0).unwrap();
+    db_operations::delete_user(&conn, 1).unwrap();
+
+    let members = db_operations::members(&conn).unwrap();
+    let summary = db_operations::summary(&conn).unwrap();
+    let ledger = db_operations::fetch_ledger(&conn).unwrap();
+
+    assert_eq!(members.len(), 1);
+    assert_eq!(members[0].name, "Ben");
+    assert_close(members[0].percent, 100.0);
+    assert_close(summary.total_shares, 300.0);
+    assert_close(summary.total_debt, 0.0);
+    // The loan is taken out of Ann's payout, so cash only goes
+    // down by the shares she had left after borrowing
+    assert_close(summary.total_cash, 300.0);
+    assert_eq!(
+        ledger_kinds(&conn)[3..],
+        [LedgerKind::Shares, LedgerKind::WriteOff]
+    );
+    // The records keep the name but no longer point at the row
+    assert!(ledger
+        .iter()
+        .filter(|record| record.member_name == "Ann")
+        .all(|record| record.member_id.is_none()));
+}
+
+#[test]
+fn delete_user_of_an_unknown_member_changes_nothing() {
+    let conn = books_with_a_loan();
+
+    assert!(matches!(
+        db_operations::delete_user(&conn, 5),
+        Err(Error::NotFound(_))
+    ));
+    assert_eq!(db_operations::fetch_ledger(&conn).unwrap().len(), 2);
+}
+
+#[test]
+fn borrow_debt_pays_out_the_loan_and_charges_interest() {
+    let conn = books_with_a_loan();
+    let summary = db_operations::summary(&conn).unwrap();
+    let ann = &db_operations::members(&conn).unwrap()[0];
+
+    assert_close(ann.loan, 200.0);
+    assert_close(ann.interest, 20.0);
+    assert_close(ann.debt(), 220.0);
+    assert_close(summary.total_loans, 200.0);
+    assert_close(summary.total_debt, 220.0);
+    assert_close(summary.total_cash, 300.0);
+    assert_close(summary.profit, 0.0);
+}
+
+#[test]
+fn borrow_debt_rejects_negative_amounts_and_unknown_members() {
+    let conn = books_with_a_loan();
+
+    for (id, loan, interest) in [(1, -1.0, 0.0), (1, 10.0, -1.0)] {
+        assert!(matches!(
+            db_operations::borrow_debt(&conn, id, loan, interest),
+            Err(Error::Validation(_))
+        ));
+    }
+
+    assert!(matches!(
+        db_operations::borrow_debt(&conn, 2, 10.0, 1.0),
+        Err(Error::NotFound(_))
+    ));
+    assert_close(db_operations::summary(&conn).unwrap().total_debt, 220.0);
+}
+
+#[test]
+fn repay_pays_the_loan_before_the_interest() {
+    let conn = books_with_a_loan();
+
+    assert_eq!(
+        db_operations::repay(&conn, 1, 150.0).unwrap(),
+        Repayment {
+            loan: 150.0,
+            interest: 0.0
+        }
+    );
+    assert_eq!(
+        db_operations::repay(&conn, 1, 60.0).unwrap(),
+        Repayment {
+            loan: 50.0,
+            interest: 10.0
+        }
+    );
+
+    let ann = &db_operations::members(&conn).unwrap()[0];
+    let summary = db_operations::summary(&conn).unwrap();
+
+    assert_close(ann.loan, 0.0);
+    assert_close(ann.interest, 10.0);
+    // Only the interest is profit, the loan was the group's money already
+    assert_close(summary.profit, 10.0);
+    assert_close(summary.total_cash, 510.0);
+}
+
+#[test]
+fn repay_splits_by_the_allocation_rule_the_group_chose() {
+    let conn = books_with_a_loan();
+
+    assert_eq!(
+        db_operations::allocation_rule(&conn).unwrap(),
+        AllocationRule::PrincipalFirst
+    );
+
+    db_operations::store_allocation_rule(&conn, AllocationRule::InterestFirst).unwrap();
+    assert_eq!(
+        db_operations::repay(&conn, 1, 30.0).unwrap(),
+        Repayment {
+            loan: 10.0,
+            interest: 20.0
+        }
+    );
+
+    // What is left is 190 of loan and no interest, so a proportional
+    // repayment now goes to the loan alone
+    db_operations::store_allocation_rule(&conn, AllocationRule::Proportional).unwrap();
+    assert_eq!(
+        db_operations::allocation_rule(&conn).unwrap(),
+        AllocationRule::Proportional
+    );
+    assert_eq!(
+        db_operations::repay(&conn, 1, 90.0).unwrap(),
+        Repayment {
+            loan: 90.0,
+            interest: 0.0
+        }
+    );
+
+    let ann = &db_operations::members(&conn).unwrap()[0];
+
+    assert_close(ann.loan, 100.0);
+    assert_close(ann.interest, 0.0);
+    assert_close(db_operations::summary(&conn).unwrap().profit, 20.0);
+}
+
+#[test]
+fn repayments_are_allocated_proportionally_to_what_is_owed() {
+    let repayment = Repayment::allocate(AllocationRule::Proportional, 200.0, 20.0, 110.0);
+
+    assert_close(repayment.loan, 100.0);
+    assert_close(repayment.interest, 10.0);
+    // Paying off the whole debt clears both parts exactly
+    assert_eq!(
+        Repayment::allocate(AllocationRule::Proportional, 200.0, 20.0, 220.0),
+        Repayment {
+            loan: 200.0,
+            interest: 20.0
+        }
+    );
+    assert_eq!(
+        AllocationRule::from_setting("not a rule"),
+        AllocationRule::PrincipalFirst
+    );
+}
+
+#[test]
+fn repay_cannot_be_more_than_the_debt_or_negative() {
+    let conn = books_with_a_loan();
+
+    for amount in [220.01, -5.0] {
+        assert!(matches!(
+            db_operations::repay(&conn, 1, amount),
+            Err(Error::Validation(_))
+        ));
+    }
+
+    assert!(matches!(
+        db_operations::repay(&conn, 3, 1.0),
+        Err(Error::NotFound(_))
+    ));
+
+    db_operations::repay(&conn, 1, 220.0).unwrap();
+
+    assert_close(db_operations::members(&conn).unwrap()[0].debt(), 0.0);
+}
+
+#[test]
+fn repay_debt_moves_interest_to_the_profits() {
+    let conn = books_with_a_loan();
+
+    db_operations::repay_debt(&conn, 1, 100.0, 15.0).unwrap();
+
+    let ann = &db_operations::members(&conn).unwrap()[0];
+    let ledger = db_operations::fetch_ledger(&conn).unwrap();
+    let last = ledger.last().unwrap();
+
+    assert_close(ann.loan, 100.0);
+    assert_close(ann.interest, 5.0);
+    assert_close(db_operations::summary(&conn).unwrap().profit, 15.0);
+    assert_eq!(last.kind, LedgerKind::Repay);
+    assert_close(last.amount, 100.0);
+    assert_close(last.interest, 15.0);
+}
+
+#[test]
+fn repay_debt_rejects_negative_amounts_and_more_than_is_owed() {
+    let conn = books_with_a_loan();
+
+    for (loan, interest) in [(-1.0, 0.0), (0.0, -1.0), (200.01, 0.0), (0.0, 20.01)] {
+        assert!(matches!(
+            db_operations::repay_debt(&conn, 1, loan, interest),
+            Err(Error::Validation(_))
+        ));
+    }
+    assert!(matches!(
+        db_operations::repay_debt(&conn, 3, 1.0, 0.0),
+        Err(Error::NotFound(_))
+    ));
+    assert_close(db_operations::members(&conn).unwrap()[0].debt(), 220.0);
+    assert_close(db_operations::summary(&conn).unwrap().profit, 0.0);
+}
+
+#[test]
+fn expenses_come_out_of_profit_and_income_goes_into_it() {
+    let conn = books_with_a_loan();
+
+    db_operations::store_entry(
+        &conn,
+        &category(&conn, "Stationery", EntryKind::Expense),
+        "Pens".to_string(),
+        15.0,
+    )
+    .unwrap();
+    db_operations::store_entry(
+        &conn,
+        &category(&conn, "Fines", EntryKind::Income),
+        "Late to meeting".to_string(),
+        40.0,
+    )
+    .unwrap();
+
+    let summary = db_operations::summary(&conn).unwrap();
+    let entries = db_operations::fetch_entries(&conn).unwrap();
+
+    assert_close(summary.total_expenses, 15.0);
+    assert_close(summary.total_income, 40.0);
+    assert_close(summary.profit, 25.0);
+    assert_close(summary.total_cash, 325.0);
+    // Newest first
+    assert_eq!(entries[0].description, "Late to meeting");
+    assert_eq!(entries[1].kind, EntryKind::Expense);
+}
+
+#[test]
+fn store_entry_needs_an_amount_above_zero() {
+    let conn = new_books();
+    let stationery = category(&conn, "Stationery", EntryKind::Expense);
+
+    for amount in [0.0, -3.0] {
+        assert!(matches!(
+            db_operations::store_entry(&conn, &stationery, "Pens".to_string(), amount),
+            Err(Error::Validation(_))
+        ));
+    }
+
+    assert!(db_operations::fetch_entries(&conn).unwrap().is_empty());
+}
+
+#[test]
+fn categories_are_sorted_by_name() {
+    let conn = new_books();
+    let names: Vec<String> = db_operations::fetch_categories(&conn)
+        .unwrap()
+        .into_iter()
+        .map(|category| category.name)
+        .collect();
+    let mut sorted = names.clone();
+
+    sorted.sort();
+
+    assert_eq!(names.len(), 7);
+    assert_eq!(names, sorted);
+}
+
+#[test]
+fn record_reconciliation_posts_the_adjustment_to_profit() {
+    let conn = books_with_a_loan();
+
+    db_operations::record_reconciliation(&conn, 300.0, 250.0, 40.0, -10.0, "Lost".into()).unwrap();
+    db_operations::record_reconciliation(&conn, 290.0, 250.0, 40.0, 0.0, String::new()).unwrap();
+
+    let history = db_operations::fetch_reconciliations(&conn).unwrap();
+    let summary = db_operations::summary(&conn).unwrap();
+
+    assert_eq!(history.len(), 2);
+    assert_close(history[0].variance(), 0.0);
+    assert_close(history[1].variance(), -10.0);
+    assert_eq!(history[1].reason, "Lost");
+    assert_close(summary.profit, -10.0);
+    assert_close(summary.total_cash, 290.0);
+    // Only the reconciliation with an adjustment is in the ledger
+    assert_eq!(ledger_kinds(&conn).last(), Some(&LedgerKind::Adjustment));
+    assert_eq!(db_operations::fetch_ledger(&conn).unwrap().len(), 3);
+}
+
+#[test]
+fn record_reconciliation_rejects_negative_counts() {
+    let conn = new_books();
+
+    assert!(matches!(
+        db_operations::record_reconciliation(&conn, 0.0, -1.0, 0.0, 0.0, String::new()),
+        Err(Error::Validation(_))
+    ));
+    assert!(db_operations::fetch_reconciliations(&conn)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn totals_history_ends_at_the_current_totals() {
+    let conn = books_with_a_loan();
+
+    db_operations::repay(&conn, 1, 220.0).unwrap();
+
+    let history = db_operations::totals_history(&conn).unwrap();
+    let first = history.first().unwrap();
+    let last = history.last().unwrap();
+
+    // One point before the first record and one after each
+    assert_eq!(history.len(), 4);
+    assert_close(first.shares, 0.0);
+    assert_close(first.cash, 0.0);
+    assert_close(last.shares, 500.0);
+    assert_close(last.cash, 520.0);
+    assert_close(last.profit, 20.0);
+}
+
+#[test]
+fn settings_are_stored_and_replaced() {
+    let conn = new_books();
+
+    assert_eq!(db_operations::get_setting(&conn, "theme").unwrap(), None);
+
+    db_operations::store_setting(&conn, "theme", "dark").unwrap();
+    db_operations::store_setting(&conn, "theme", "light").unwrap();
+
+    assert_eq!(
+        db_operations::get_setting(&conn, "theme").unwrap(),
+        Some("light".to_string())
+    );
+}
+
+fn names(conn: &Connection, sort: MemberSort) -> Vec<String> {
+    let mut members = db_operations::members(conn).unwrap();
+
+    sort.sort(&mut members);
+    members.into_iter().map(|member| member.name).collect()
+}
+
+#[test]
+fn members_are_sorted_by_any_column_either_way() {
+    let conn = new_books();
+
+    db_operations::store_new_user(&conn, "carol".to_string(), 300.0).unwrap();
+    db_operations::store_new_user(&conn, "Ann".to_string(), 100.0).unwrap();
+    db_operations::store_new_user(&conn, "Ben".to_string(), 200.0).unwrap();
+    db_operations::borrow_debt(&conn, 2, 50.0, 5.0).unwrap();
+
+    let sort = MemberSort::default();
+
+    assert_eq!(names(&conn, sort), ["Ann", "Ben", "carol"]);
+
+    let sort = sort.toggled(SortColumn::Name);
+
+    assert_eq!(names(&conn, sort), ["carol", "Ben", "Ann"]);
+
+    // Amounts start from the largest
+    let sort = sort.toggled(SortColumn::Shares);
+
+    assert!(sort.descending);
+    assert_eq!(names(&conn, sort), ["carol", "Ben", "Ann"]);
+    assert_eq!(
+        names(&conn, sort.toggled(SortColumn::Shares)),
+        ["Ann", "Ben", "carol"]
+    );
+    assert_eq!(
+        names(&conn, sort.toggled(SortColumn::Percent)),
+        ["carol", "Ben", "Ann"]
+    );
+
+    // Members without debt keep the order they were added in
+    assert_eq!(
+        names(&conn, sort.toggled(SortColumn::Debt)),
+        ["Ann", "carol", "Ben"]
+    );
+}
+
+#[test]
+fn the_sort_order_of_each_table_is_kept() {
+    let conn = new_books();
+    let sort = MemberSort {
+        column: SortColumn::Debt,
+        descending: true,
+    };
+
+    assert_eq!(
+        db_operations::member_sort(&conn, "home").unwrap(),
+        MemberSort::default()
+    );
+
+    db_operations::store_member_sort(&conn, "home", sort).unwrap();
+
+    assert_eq!(db_operations::member_sort(&conn, "home").unwrap(), sort);
+    assert_eq!(
+        db_operations::member_sort(&conn, "debts").unwrap(),
+        MemberSort::default()
+    );
+
+    // An order that cannot be read falls back to the default
+    db_operations::store_setting(&conn, "sort.users", "height up").unwrap();
+
+    assert_eq!(
+        db_operations::member_sort(&conn, "users").unwrap(),
+        MemberSort::default()
+    );
+}
+
+fn matching(conn: &Connection, search: &str, filter: MemberFilter) -> Vec<String> {
+    let query = MemberQuery {
+        search: search.to_string(),
+        filter,
+    };
+
+    db_operations::members(conn)
+        .unwrap()
+        .into_iter()
+        .filter(|member| query.matches(member))
+        .map(|member| member.name)
+        .collect()
+}
+
+#[test]
+fn members_are_found_by_part_of_their_name() {
+    let conn = new_books();
+
+    for name in ["Ann Banda", "Ben Phiri", "Joanna Banda"] {
+        db_operations::store_new_user(&conn, name.to_string(), 100.0).unwrap();
+    }
+
+    assert_eq!(
+        matching(&conn, " banda", MemberFilter::All),
+        ["Ann Banda", "Joanna Banda"]
+    );
+    assert_eq!(
+        matching(&conn, "ANN", MemberFilter::All),
+        ["Ann Banda", "Joanna Banda"]
+    );
+    assert_eq!(matching(&conn, "", MemberFilter::All).len(), 3);
+    assert!(matching(&conn, "Chisomo", MemberFilter::All).is_empty());
+}
+
+#[test]
+fn quick_filters_pick_out_debtors_and_idle_members() {
+    let conn = new_books();
+
+    db_operations::store_new_user(&conn, "Ann".to_string(), 500.0).unwrap();
+    db_operations::store_new_user(&conn, "Ben".to_string(), 0.0).unwrap();
+    db_operations::store_new_user(&conn, "Cat".to_string(), 300.0).unwrap();
+    db_operations::borrow_debt(&conn, 1, 200.0, 20.0).unwrap();
+    db_operations::borrow_debt(&conn, 3, 100.0, 10.0).unwrap();
+
+    assert_eq!(matching(&conn, "", MemberFilter::HasDebt), ["Ann", "Cat"]);
+    assert_eq!(matching(&conn, "", MemberFilter::ZeroShares), ["Ben"]);
+    assert!(matching(&conn, "", MemberFilter::Overdue).is_empty());
+    assert!(matching(&conn, "", MemberFilter::Inactive).is_empty());
+
+    // Cat borrowed two months ago and Ben joined half a year ago
+    conn.execute_batch(
+        "
+        UPDATE ledger SET date = datetime('now', 'localtime', '-60 days') WHERE memberId = 3;
+        UPDATE ledger SET date = datetime('now', 'localtime', '-180 days') WHERE memberId = 2;",
+    )
+    .unwrap();
+
+    assert_eq!(matching(&conn, "", MemberFilter::Overdue), ["Cat"]);
+    assert_eq!(matching(&conn, "", MemberFilter::Inactive), ["Ben"]);
+
+    // A repayment brings Cat up to date again
+    db_operations::repay(&conn, 3, 10.0).unwrap();
+
+    assert!(matching(&conn, "", MemberFilter::Overdue).is_empty());
+    assert_eq!(matching(&conn, "c", MemberFilter::HasDebt), ["Cat"]);
+}
+
+#[test]
+fn member_details_follow_each_loan_until_it_is_repaid() {
+    let conn = books_with_a_loan();
+
+    db_operations::borrow_debt(&conn, 1, 100.0, 10.0).unwrap();
+    db_operations::repay(&conn, 1, 250.0).unwrap();
+
+    let details = db_operations::member_details(&conn, 1).unwrap();
+    let loans: Vec<_> = details
+        .loans
+        .iter()
+        .map(|loan| (loan.loan, loan.interest, loan.repaid))
+        .collect();
+
+    assert_eq!(details.member.name, "Ann");
+    assert_eq!(details.contributions.len(), 1);
+    assert_eq!(details.contributions[0].amount, 500.0);
+    assert_eq!(loans, [(200.0, 20.0, 220.0), (100.0, 10.0, 30.0)]);
+    assert_close(details.loans[1].remaining(), 80.0);
+    assert_eq!(details.repayments.len(), 1);
+    assert_close(
+        details.repayments[0].amount + details.repayments[0].interest,
+        250.0,
+    );
+    assert_eq!(
+        details.profile.joined.as_deref().map(str::len),
+        Some("2000-01-01".len())
+    );
+}
+
+#[test]
+fn undone_loans_and_repayments_leave_the_loan_schedule() {
+    let conn = books_with_a_loan();
+
+    db_operations::borrow_debt(&conn, 1, 100.0, 10.0).unwrap();
+    let loan = db_operations::last_ledger_id(&conn).unwrap().unwrap();
+    db_operations::repay(&conn, 1, 250.0).unwrap();
+    let repayment = db_operations::last_ledger_id(&conn).unwrap().unwrap();
+
+    db_operations::reverse_transaction(&conn, repayment).unwrap();
+    db_operations::reverse_transaction(&conn, loan).unwrap();
+
+    let details = db_operations::member_details(&conn, 1).unwrap();
+
+    assert_eq!(details.loans.len(), 1);
+    assert_eq!(details.loans[0].repaid, 0.0);
+    assert_close(details.loans[0].remaining(), 220.0);
+}
+
+#[test]
+fn a_lone_member_would_take_home_all_the_cash_once_repaid() {
+    let conn = books_with_a_loan();
+
+    db_operations::repay(&conn, 1, 100.0).unwrap();
+
+    let details = db_operations::member_details(&conn, 1).unwrap();
+    let summary = db_operations::summary(&conn).unwrap();
+
+    // Ann's outstanding interest is paid to herself
+    assert_close(details.share_out, summary.total_cash);
+}
+
+#[test]
+fn member_profiles_are_saved_trimmed_and_phone_numbers_checked() {
+    let conn = books_with_a_loan();
+
+    db_operations::store_member_profile(&conn, 1, " +265 (0) 99-123 ", " Pays on Fridays\n")
+        .unwrap();
+
+    let profile = db_operations::member_details(&conn, 1).unwrap().profile;
+
+    assert_eq!(profile.phone, "+265 (0) 99-123");
+    assert_eq!(profile.notes, "Pays on Fridays");
+    assert!(matches!(
+        db_operations::store_member_profile(&conn, 1, "call me", ""),
+        Err(Error::Validation(_))
+    ));
+    assert!(matches!(
+        db_operations::store_member_profile(&conn, 9, "", ""),
+        Err(Error::NotFound(_))
+    ));
+    assert!(matches!(
+        db_operations::member_details(&conn, 9),
+        Err(Error::NotFound(_))
+    ));
+}
+
+#[test]
+fn the_currency_is_kept_with_the_books_and_checked_on_import() {
+    let conn = new_books();
+    let rand = Currency {
+        symbol: "R".to_string(),
+        position: SymbolPosition::After,
+        decimals: 0,
+        grouping: None,
+        decimal_separator: ',',
+    };
+
+    assert_eq!(db_operations::currency(&conn).unwrap(), Currency::default());
+
+    db_operations::store_currency(&conn, &rand).unwrap();
+
+    assert_eq!(db_operations::currency(&conn).unwrap(), rand);
+    assert!(matches!(
+        db_operations::store_currency(
+            &conn,
+            &Currency {
+                decimal_separator: '5',
+                ..rand.clone()
+            }
+        ),
+        Err(Error::Validation(_))
+    ));
+    assert_eq!(db_operations::currency(&conn).unwrap(), rand);
+
+    let mut document = export::export_document(&conn).unwrap();
+
+    assert!(export::validate_document(&document).is_ok());
+
+    document
+        .settings
+        .insert(Currency::DECIMALS_SETTING.to_string(), "many".to_string());
+
+    assert!(export::validate_document(&document).is_err());
+}
+
+#[test]
+fn exporting_importing_and_exporting_again_gives_the_same_document() {
+    let conn = books_with_a_loan();
+
+    db_operations::store_new_user(&conn, "Ben".to_string(), 300.0).unwrap();
+    db_operations::store_member_profile(&conn, 1, "0999 123 456", "Treasurer").unwrap();
+    db_operations::repay(&conn, 1, 50.0).unwrap();
+    db_operations::store_entry(
+        &conn,
+        &category(&conn, "Stationery", EntryKind::Expense),
+        "Pens".to_string(),
+        15.0,
+    )
+    .unwrap();
+    db_operations::record_reconciliation(&conn, 300.0, 250.0, 40.0, -10.0, "Lost".into()).unwrap();
+    db_operations::store_currency(&conn, &Currency::default()).unwrap();
+
+    let last = db_operations::last_ledger_id(&conn).unwrap().unwrap();
+    db_operations::reverse_transaction(&conn, last).unwrap();
+
+    let exported = export::export_document(&conn).unwrap();
+    let json = serde_json::to_string(&exported).unwrap();
+    let copy = new_books();
+
+    export::import_document(&copy, &serde_json::from_str(&json).unwrap()).unwrap();
+
+    assert_eq!(export::export_document(&copy).unwrap(), exported);
+}
+
+#[test]
+fn documents_of_older_versions_can_still_be_imported() {
+    let conn = books_with_a_loan();
+    let mut json = serde_json::to_value(export::export_document(&conn).unwrap()).unwrap();
+
+    // Version 1 had no undo links or member profiles
+    json["version"] = 1.into();
+    for member in json["members"].as_array_mut().unwrap() {
+        let member = member.as_object_mut().unwrap();
+
+        member.remove("phone");
+        member.remove("joined");
+        member.remove("notes");
+    }
+    for transaction in json["transactions"].as_array_mut().unwrap() {
+        transaction.as_object_mut().unwrap().remove("reverses");
+    }
+
+    let copy = new_books();
+
+    export::import_document(&copy, &serde_json::from_value(json).unwrap()).unwrap();
+
+    let members = db_operations::members(&copy).unwrap();
+
+    assert_eq!(members.len(), 1);
+    assert_eq!(members[0].name, "Ann");
+    assert_close(members[0].loan, 200.0);
+
+    let mut json = serde_json::to_value(export::export_document(&conn).unwrap()).unwrap();
+
+    json["version"] = (export::FORMAT_VERSION + 1).into();
+    assert!(matches!(
+        export::import_document(&copy, &serde_json::from_value(json).unwrap()),
+        Err(Error::UnsupportedFormat(_, _))
+    ));
+}
@@ -0,0 +1,207 @@
+//! Rules that have to hold for any amounts, checked against
+//! randomly generated members, loans and repayments
+
+use family_banking_core::{
+    db_operations, format_decimal,
+    model::{AllocationRule, Currency, Repayment, SymbolPosition},
+};
+use proptest::prelude::*;
+
+mod common;
+
+use common::{assert_close, new_books};
+
+#[derive(Debug, Clone)]
+enum Operation {
+    Lend {
+        member: usize,
+        loan: f64,
+        interest: f64,
+    },
+    /// Repays this fraction of the member's debt
+    Repay { member: usize, fraction: f64 },
+}
+
+/// Whole cents, so the expected totals can be added up exactly
+fn money(max: u32) -> impl Strategy<Value = f64> {
+    (0..=max * 100).prop_map(|cents| cents as f64 / 100.0)
+}
+
+fn operation(members: usize) -> impl Strategy<Value = Operation> {
+    prop_oneof![
+        (0..members, money(5_000), money(500)).prop_map(|(member, loan, interest)| {
+            Operation::Lend {
+                member,
+                loan,
+                interest,
+            }
+        }),
+        (0..members, 0.0..=1.0)
+            .prop_map(|(member, fraction)| Operation::Repay { member, fraction }),
+    ]
+}
+
+fn books_and_operations() -> impl Strategy<Value = (Vec<f64>, Vec<Operation>)> {
+    prop::collection::vec(money(10_000), 1..5).prop_flat_map(|shares| {
+        let members = shares.len();
+
+        (
+            Just(shares),
+            prop::collection::vec(operation(members), 0..20),
+        )
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn cash_is_conserved_across_loans_and_repayments(
+        (shares, operations) in books_and_operations()
+    ) {
+        let conn = new_books();
+
+        for (i, shares) in shares.iter().enumerate() {
+            db_operations::store_new_user(&conn, format!("Member {}", i), *shares).unwrap();
+        }
+
+        let start = db_operations::summary(&conn).unwrap();
+        let (mut lent, mut charged, mut repaid, mut interest_repaid) = (0.0, 0.0, 0.0, 0.0);
+
+        for operation in &operations {
+            match *operation {
+                Operation::Lend { member, loan, interest } => {
+                    db_operations::borrow_debt(&conn, member as i32 + 1, loan, interest).unwrap();
+                    lent += loan;
+                    charged += interest;
+                }
+                Operation::Repay { member, fraction } => {
+                    let debt = db_operations::members(&conn).unwrap()[member].debt();
+                    let repayment =
+                        db_operations::repay(&conn, member as i32 + 1, debt * fraction).unwrap();
+
+                    repaid += repayment.loan + repayment.interest;
+                    interest_repaid += repayment.interest;
+                }
+            }
+        }
+
+        let end = db_operations::summary(&conn).unwrap();
+
+        assert_close(start.total_cash, start.total_shares);
+        assert_close(end.total_cash, start.total_cash - lent + repaid);
+        assert_close(end.total_debt, lent + charged - repaid);
+        assert_close(end.profit, interest_repaid);
+        assert_close(end.total_shares, start.total_shares);
+        assert_close(end.total_cash + end.total_loans, end.total_shares + end.profit);
+
+        for member in db_operations::members(&conn).unwrap() {
+            prop_assert!(member.loan > -common::TOLERANCE);
+            prop_assert!(member.interest > -common::TOLERANCE);
+        }
+
+        // Working back through the ledger arrives at empty books
+        let history = db_operations::totals_history(&conn).unwrap();
+        let first = history.first().unwrap();
+
+        assert_close(first.cash, 0.0);
+        assert_close(first.shares, 0.0);
+        assert_close(first.profit, 0.0);
+    }
+
+    #[test]
+    fn percentages_add_up_to_a_hundred(shares in prop::collection::vec(money(10_000), 1..8)) {
+        let conn = new_books();
+
+        for (i, shares) in shares.iter().enumerate() {
+            db_operations::store_new_user(&conn, format!("Member {}", i), *shares).unwrap();
+        }
+
+        let members = db_operations::members(&conn).unwrap();
+        let total: f64 = members.iter().map(|member| member.percent).sum();
+
+        for member in &members {
+            prop_assert!(member.percent.is_finite());
+            prop_assert!((0.0..=100.0 + common::TOLERANCE).contains(&member.percent));
+        }
+
+        if shares.iter().any(|shares| *shares > 0.0) {
+            assert_close(total, 100.0);
+        } else {
+            prop_assert_eq!(total, 0.0);
+        }
+    }
+
+    #[test]
+    fn repayments_are_split_without_losing_money(loan in money(10_000), amount in money(20_000)) {
+        let repayment = Repayment::split(loan, amount);
+
+        prop_assert!((repayment.loan + repayment.interest - amount).abs() < common::TOLERANCE);
+        prop_assert!(repayment.loan <= loan);
+        prop_assert!(repayment.loan >= 0.0 && repayment.interest >= 0.0);
+        // Interest is only paid once the loan is paid off
+        if repayment.interest > 0.0 {
+            prop_assert_eq!(repayment.loan, loan);
+        }
+    }
+
+    #[test]
+    fn every_allocation_rule_pays_no_more_than_is_owed(
+        loan in money(10_000),
+        interest in money(1_000),
+        paid in 0.0..=1.0f64,
+    ) {
+        let amount = (loan + interest) * paid;
+
+        for rule in AllocationRule::ALL {
+            let repayment = Repayment::allocate(rule, loan, interest, amount);
+
+            prop_assert!((repayment.loan + repayment.interest - amount).abs() < common::TOLERANCE);
+            prop_assert!(repayment.loan >= -common::TOLERANCE && repayment.interest >= -common::TOLERANCE);
+            prop_assert!(repayment.loan <= loan + common::TOLERANCE);
+            prop_assert!(repayment.interest <= interest + common::TOLERANCE);
+        }
+    }
+
+    #[test]
+    fn format_decimal_rounds_to_the_nearest_cent(number in 0.0..1e12f64) {
+        let formatted = format_decimal(number);
+        let (whole, cents) = formatted.split_once('.').unwrap();
+        let parsed: f64 = formatted.replace(',', "").parse().unwrap();
+
+        // Allowing for the precision of an f64 that large
+        prop_assert!((number - parsed).abs() <= 0.005 + number * 1e-15);
+        prop_assert_eq!(cents.len(), 2);
+        // Every group after the first has exactly three digits
+        for group in whole.split(',').skip(1) {
+            prop_assert_eq!(group.len(), 3);
+        }
+        prop_assert!(whole.split(',').next().unwrap().len() <= 3);
+
+        let positive = number.max(0.01);
+
+        prop_assert_eq!(format_decimal(-positive), format!("-{}", format_decimal(positive)));
+    }
+
+    #[test]
+    fn amounts_read_back_what_the_currency_wrote(
+        number in -1e9..1e9f64,
+        decimals in 0..=Currency::MAX_DECIMALS,
+        euro in any::<bool>(),
+    ) {
+        let currency = if euro {
+            Currency {
+                symbol: "€".to_string(),
+                position: SymbolPosition::After,
+                decimals,
+                grouping: Some(' '),
+                decimal_separator: ',',
+            }
+        } else {
+            Currency { decimals, ..Currency::default() }
+        };
+        let parsed = currency.parse(&currency.format(number)).unwrap();
+
+        prop_assert!((number - parsed).abs() <= 0.5 / 10f64.powi(decimals.into()) + 1e-6);
+    }
+}
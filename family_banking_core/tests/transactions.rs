@@ -0,0 +1,259 @@
+//! Each operation that makes several changes is made to fail part way
+//! through, by a trigger that aborts one of its later statements, and
+//! the books are then checked to be exactly as they were before it
+
+use family_banking_core::{
+    backup::{self, BackupSettings},
+    db_operations,
+};
+use rusqlite::{types::Value, Connection};
+
+mod common;
+
+use common::{books_with_a_loan, new_books};
+
+const TABLES: [&str; 7] = [
+    "admin",
+    "member",
+    "ledger",
+    "category",
+    "entry",
+    "reconciliation",
+    "setting",
+];
+
+/// Makes every `event` on `table` abort while `condition` holds
+fn fail_on(conn: &Connection, event: &str, table: &str, condition: &str) {
+    conn.execute_batch(&format!(
+        "
+        CREATE TRIGGER injected_failure
+        BEFORE {} ON {}
+        WHEN {}
+        BEGIN
+            SELECT RAISE(ABORT, 'injected failure');
+        END;",
+        event, table, condition
+    ))
+    .unwrap();
+}
+
+/// Every row of every table, to compare the books before and after
+fn dump(conn: &Connection) -> Vec<String> {
+    let mut rows = Vec::new();
+
+    for table in TABLES {
+        let mut stmt = conn.prepare(&format!("SELECT * FROM {}", table)).unwrap();
+        let columns = stmt.column_count();
+        let mut table_rows = stmt.query([]).unwrap();
+
+        while let Some(row) = table_rows.next().unwrap() {
+            let values: Vec<String> = (0..columns)
+                .map(|i| format!("{:?}", row.get::<_, Value>(i).unwrap()))
+                .collect();
+
+            rows.push(format!("{}: {}", table, values.join(", ")));
+        }
+    }
+
+    rows
+}
+
+fn assert_rolled_back<T: std::fmt::Debug>(
+    conn: &Connection,
+    before: &[String],
+    result: family_banking_core::Result<T>,
+) {
+    assert!(result.is_err(), "the injected failure was not reported");
+    assert_eq!(dump(conn), before);
+    assert!(conn.is_autocommit(), "the transaction was left open");
+}
+
+#[test]
+fn initialize_db_tables_creates_nothing_if_a_table_cannot_be_created() {
+    let conn = Connection::open_in_memory().unwrap();
+
+    conn.execute_batch("CREATE TABLE member (memberId INTEGER PRIMARY KEY);")
+        .unwrap();
+
+    assert!(db_operations::initialize_db_tables(&conn).is_err());
+    assert!(!db_operations::table_exists(&conn, "admin").unwrap());
+}
+
+#[test]
+fn update_db_schema_keeps_a_failed_migration_out() {
+    let conn = Connection::open_in_memory().unwrap();
+
+    db_operations::initialize_db_tables(&conn).unwrap();
+    // The second migration creates the category table, then this one
+    conn.execute_batch("CREATE TABLE entry (entryId INTEGER PRIMARY KEY);")
+        .unwrap();
+
+    assert!(db_operations::update_db_schema(&conn).is_err());
+    assert!(db_operations::table_exists(&conn, "reconciliation").unwrap());
+    assert!(!db_operations::table_exists(&conn, "category").unwrap());
+    assert_eq!(
+        db_operations::pending_migrations(&conn).unwrap(),
+        db_operations::latest_schema_version() - 1
+    );
+}
+
+#[test]
+fn store_new_user_adds_no_member_if_the_ledger_fails() {
+    let conn = new_books();
+    let before = dump(&conn);
+
+    fail_on(&conn, "INSERT", "ledger", "1");
+
+    let result = db_operations::store_new_user(&conn, "Ann".to_string(), 500.0);
+
+    assert_rolled_back(&conn, &before, result);
+}
+
+#[test]
+fn edit_user_keeps_the_old_shares_if_the_ledger_fails() {
+    let conn = books_with_a_loan();
+    let before = dump(&conn);
+
+    fail_on(&conn, "INSERT", "ledger", "1");
+
+    let result = db_operations::edit_user(&conn, 1, "Anne".to_string(), 800.0);
+
+    assert_rolled_back(&conn, &before, result);
+}
+
+#[test]
+fn delete_user_keeps_the_ledger_if_the_member_cannot_be_deleted() {
+    let conn = books_with_a_loan();
+    let before = dump(&conn);
+
+    fail_on(&conn, "DELETE", "member", "1");
+
+    let result = db_operations::delete_user(&conn, 1);
+
+    assert_rolled_back(&conn, &before, result);
+}
+
+#[test]
+fn borrow_debt_lends_nothing_if_the_ledger_fails() {
+    let conn = books_with_a_loan();
+    let before = dump(&conn);
+
+    fail_on(&conn, "INSERT", "ledger", "1");
+
+    let result = db_operations::borrow_debt(&conn, 1, 100.0, 10.0);
+
+    assert_rolled_back(&conn, &before, result);
+}
+
+#[test]
+fn repay_debt_leaves_the_borrower_alone_if_the_profits_update_fails() {
+    let conn = books_with_a_loan();
+    let before = dump(&conn);
+
+    fail_on(&conn, "UPDATE", "member", "OLD.memberId = 0");
+
+    let result = db_operations::repay_debt(&conn, 1, 100.0, 10.0);
+
+    assert_rolled_back(&conn, &before, result);
+}
+
+#[test]
+fn repay_changes_nothing_if_the_ledger_fails() {
+    let conn = books_with_a_loan();
+    let before = dump(&conn);
+
+    fail_on(&conn, "INSERT", "ledger", "1");
+
+    let result = db_operations::repay(&conn, 1, 210.0);
+
+    assert_rolled_back(&conn, &before, result);
+}
+
+#[test]
+fn record_reconciliation_is_not_kept_if_the_adjustment_fails() {
+    let conn = books_with_a_loan();
+    let before = dump(&conn);
+
+    fail_on(&conn, "UPDATE", "member", "OLD.memberId = 0");
+
+    let result =
+        db_operations::record_reconciliation(&conn, 300.0, 250.0, 40.0, -10.0, "Miscounted".into());
+
+    assert_rolled_back(&conn, &before, result);
+}
+
+#[test]
+fn store_entry_is_not_kept_if_the_ledger_fails() {
+    let conn = books_with_a_loan();
+    let category = db_operations::fetch_categories(&conn).unwrap().remove(0);
+    let before = dump(&conn);
+
+    fail_on(&conn, "INSERT", "ledger", "1");
+
+    let result = db_operations::store_entry(&conn, &category, "Pens".to_string(), 15.0);
+
+    assert_rolled_back(&conn, &before, result);
+}
+
+#[test]
+fn reverse_transaction_puts_nothing_back_if_the_ledger_fails() {
+    let conn = books_with_a_loan();
+
+    db_operations::repay(&conn, 1, 210.0).unwrap();
+
+    let repayment = db_operations::last_ledger_id(&conn).unwrap().unwrap();
+    let before = dump(&conn);
+
+    fail_on(&conn, "INSERT", "ledger", "1");
+
+    let result = db_operations::reverse_transaction(&conn, repayment);
+
+    assert_rolled_back(&conn, &before, result);
+}
+
+#[test]
+fn store_backup_settings_saves_none_if_one_fails() {
+    let conn = new_books();
+    let before = dump(&conn);
+
+    fail_on(&conn, "INSERT", "setting", "NEW.key = 'snapshot.retention'");
+
+    let result = backup::store_backup_settings(
+        &conn,
+        &BackupSettings {
+            retention: 3,
+            snapshot_retention: 2,
+            ..BackupSettings::default()
+        },
+    );
+
+    assert_rolled_back(&conn, &before, result);
+}
+
+#[test]
+fn nested_operations_are_rolled_back_together() {
+    let conn = books_with_a_loan();
+    let before = dump(&conn);
+
+    fail_on(&conn, "UPDATE", "member", "OLD.memberId = 0");
+
+    let result = db_operations::in_transaction(&conn, |conn| {
+        db_operations::borrow_debt(conn, 1, 100.0, 10.0)?;
+        db_operations::repay_debt(conn, 1, 50.0, 5.0)
+    });
+
+    assert_rolled_back(&conn, &before, result);
+}
+
+#[test]
+fn a_successful_operation_is_committed() {
+    let conn = books_with_a_loan();
+
+    db_operations::repay(&conn, 1, 210.0).unwrap();
+
+    let ann = &db_operations::members(&conn).unwrap()[0];
+
+    assert_eq!((ann.loan, ann.interest), (0.0, 10.0));
+    assert_eq!(db_operations::summary(&conn).unwrap().profit, 10.0);
+    assert!(conn.is_autocommit());
+}
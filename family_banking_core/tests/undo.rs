@@ -0,0 +1,182 @@
+//! Undoing posts a transaction that cancels out an earlier one, and
+//! redoing cancels out that one in turn, so the ledger keeps a record
+//! of every change however often it is undone
+
+use family_banking_core::{db_operations, ledger::LedgerKind, Error};
+
+mod common;
+
+use common::{assert_close, books_with_a_loan, new_books};
+
+fn last_record(conn: &rusqlite::Connection) -> i64 {
+    db_operations::last_ledger_id(conn).unwrap().unwrap()
+}
+
+#[test]
+fn undoing_a_loan_takes_it_back_and_keeps_both_records() {
+    let conn = books_with_a_loan();
+    let loan = last_record(&conn);
+
+    let reversal = db_operations::reverse_transaction(&conn, loan).unwrap();
+
+    let ann = &db_operations::members(&conn).unwrap()[0];
+    let summary = db_operations::summary(&conn).unwrap();
+    let ledger = db_operations::fetch_ledger(&conn).unwrap();
+    let record = ledger.last().unwrap();
+
+    assert_eq!((ann.loan, ann.interest), (0.0, 0.0));
+    assert_eq!(summary.total_cash, 500.0);
+    assert_eq!(ledger.len(), 3);
+    assert_eq!(record.id, reversal);
+    assert_eq!(record.kind, LedgerKind::Lend);
+    assert_eq!(record.reverses, Some(loan));
+    assert_eq!((record.amount, record.interest), (-200.0, -20.0));
+}
+
+#[test]
+fn undoing_a_repayment_puts_the_debt_back_and_redoing_repays_it_again() {
+    let conn = books_with_a_loan();
+
+    db_operations::repay(&conn, 1, 210.0).unwrap();
+
+    let reversal = db_operations::reverse_transaction(&conn, last_record(&conn)).unwrap();
+    let ann = &db_operations::members(&conn).unwrap()[0];
+    let summary = db_operations::summary(&conn).unwrap();
+
+    assert_eq!((ann.loan, ann.interest), (200.0, 20.0));
+    assert_eq!(summary.profit, 0.0);
+    assert_eq!(summary.total_cash, 300.0);
+
+    db_operations::reverse_transaction(&conn, reversal).unwrap();
+
+    let ann = &db_operations::members(&conn).unwrap()[0];
+    let summary = db_operations::summary(&conn).unwrap();
+
+    assert_eq!((ann.loan, ann.interest), (0.0, 10.0));
+    assert_eq!(summary.profit, 10.0);
+    assert_eq!(summary.total_cash, 510.0);
+}
+
+#[test]
+fn undoing_a_change_of_shares_restores_the_old_shares() {
+    let conn = books_with_a_loan();
+
+    db_operations::edit_user(&conn, 1, "Ann".to_string(), 800.0).unwrap();
+    db_operations::reverse_transaction(&conn, last_record(&conn)).unwrap();
+
+    assert_eq!(db_operations::members(&conn).unwrap()[0].shares, 500.0);
+}
+
+#[test]
+fn undoing_an_entry_adds_the_opposite_entry_to_the_cash_book() {
+    let conn = new_books();
+    let category = db_operations::fetch_categories(&conn)
+        .unwrap()
+        .into_iter()
+        .find(|category| category.name == "Stationery")
+        .unwrap();
+
+    db_operations::store_entry(&conn, &category, "Pens".to_string(), 15.0).unwrap();
+    db_operations::reverse_transaction(&conn, last_record(&conn)).unwrap();
+
+    let entries = db_operations::fetch_entries(&conn).unwrap();
+    let summary = db_operations::summary(&conn).unwrap();
+
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].description, "Pens");
+    assert_eq!(entries[0].amount, -15.0);
+    assert_eq!(summary.total_expenses, 0.0);
+    assert_eq!(summary.profit, 0.0);
+}
+
+#[test]
+fn undoing_an_adjustment_takes_it_out_of_the_profits() {
+    let conn = books_with_a_loan();
+
+    db_operations::record_reconciliation(&conn, 300.0, 250.0, 40.0, -10.0, "Miscounted".into())
+        .unwrap();
+    db_operations::reverse_transaction(&conn, last_record(&conn)).unwrap();
+
+    assert_eq!(db_operations::summary(&conn).unwrap().profit, 0.0);
+}
+
+#[test]
+fn a_record_can_only_be_undone_once() {
+    let conn = books_with_a_loan();
+    let loan = last_record(&conn);
+
+    db_operations::reverse_transaction(&conn, loan).unwrap();
+
+    assert!(matches!(
+        db_operations::reverse_transaction(&conn, loan),
+        Err(Error::Validation(_))
+    ));
+    assert_eq!(db_operations::members(&conn).unwrap()[0].loan, 0.0);
+}
+
+#[test]
+fn a_loan_cannot_be_undone_while_a_repayment_depends_on_it() {
+    let conn = books_with_a_loan();
+    let loan = last_record(&conn);
+
+    db_operations::repay(&conn, 1, 150.0).unwrap();
+
+    assert!(matches!(
+        db_operations::reverse_transaction(&conn, loan),
+        Err(Error::Validation(_))
+    ));
+
+    db_operations::reverse_transaction(&conn, last_record(&conn)).unwrap();
+    db_operations::reverse_transaction(&conn, loan).unwrap();
+
+    let ann = &db_operations::members(&conn).unwrap()[0];
+
+    assert_eq!((ann.loan, ann.interest), (0.0, 0.0));
+}
+
+#[test]
+fn the_records_of_a_deleted_member_cannot_be_undone() {
+    let conn = books_with_a_loan();
+    let loan = last_record(&conn);
+
+    db_operations::delete_user(&conn, 1).unwrap();
+
+    let write_off = last_record(&conn);
+
+    for id in [loan, write_off] {
+        assert!(matches!(
+            db_operations::reverse_transaction(&conn, id),
+            Err(Error::Validation(_))
+        ));
+    }
+}
+
+#[test]
+fn an_unknown_record_is_not_found() {
+    let conn = new_books();
+
+    assert!(matches!(
+        db_operations::reverse_transaction(&conn, 42),
+        Err(Error::NotFound(_))
+    ));
+}
+
+#[test]
+fn the_history_still_starts_from_empty_books() {
+    let conn = books_with_a_loan();
+
+    db_operations::repay(&conn, 1, 220.0).unwrap();
+    let repayment = last_record(&conn);
+    let reversal = db_operations::reverse_transaction(&conn, repayment).unwrap();
+    db_operations::reverse_transaction(&conn, reversal).unwrap();
+
+    let history = db_operations::totals_history(&conn).unwrap();
+    let first = history.first().unwrap();
+    let last = history.last().unwrap();
+
+    assert_close(first.cash, 0.0);
+    assert_close(first.shares, 0.0);
+    assert_close(first.profit, 0.0);
+    assert_close(last.cash, 520.0);
+    assert_close(last.profit, 20.0);
+}
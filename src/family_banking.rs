@@ -1,31 +1,82 @@
-use std::str::FromStr;
-
-use iced::{Application, Color, Column, Element, Row};
+use family_banking_core::{
+    backup,
+    config::{self, Args, Group},
+    db_operations, export,
+    model::{
+        AllocationRule, Category, Currency, EntryKind, MemberFilter, MemberQuery, SortColumn,
+        SymbolPosition,
+    },
+    Error,
+};
+use iced::{Application, Color, Column, Container, Element, Row};
+use std::time::Instant;
 
 use crate::{
-    db_operations,
+    i18n::{self, tr, tr_with, Language},
+    keyboard,
     login_view::{render_login_view, render_new_password_view, LoginView},
     main_view::{
         self,
         debts_tab::{AddDebt, RepayDebt},
-        render_tab_buttons,
+        expenses_tab::AddEntry,
+        render_tab_buttons, render_undo_buttons,
         users_tab::ConfirmDeletion,
         users_tab::EditUserDetails,
         EditingPane, MainView, Tab, TabData,
     },
-    WINDOW_HEIGHT, WINDOW_WIDTH,
+    notifications::{self, Link, Notification},
+    recovery_view::{render_recovery_view, RecoveryView},
+    style::{self, Theme},
+    tab_data,
 };
 
 pub struct FamilyBanking {
     pub db_connection: rusqlite::Connection,
+    /// The group whose database is open
+    pub group: Group,
+    /// Every known group, for the group picker
+    pub groups: Vec<Group>,
     pub status: Status,
     pub admin_password: Option<String>,
+    /// Set once the window has been asked to close and the exit
+    /// snapshot has been taken, or left out after it failed
+    pub exiting: bool,
+    /// Set when the exit snapshot could not be taken, so that
+    /// closing the window again exits without one
+    pub exit_snapshot_failed: bool,
+    /// Ledger records of the changes made since the books were
+    /// opened, newest last. Undoing reverses them one at a time
+    pub undo_stack: Vec<i64>,
+    /// Records posted by undoing, which redoing reverses again
+    pub redo_stack: Vec<i64>,
+    /// The search and filter shared by the tables of members
+    pub member_query: MemberQuery,
+    pub theme: Theme,
+    /// How amounts are written and read, as saved with the books
+    pub currency: Currency,
+    /// The language the window is shown in, as saved with the books
+    pub language: Language,
+    /// What has just been done, newest last
+    pub notifications: Vec<Notification>,
 }
 
+// There is only ever one status, so its size does not matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 pub enum Status {
     LoggedIn(MainView),
     NotLoggedIn(LoginView),
+    /// The books could not be read, so they cannot be logged in to
+    Unreadable(RecoveryView),
+}
+
+/// A change to a member that their ledger record does not hold
+/// all of, so that reversing the record would not undo it
+enum UnrecordedChange {
+    /// A new member, whose record only holds the shares they bought
+    Adding,
+    /// A member being edited, with their name before the edit
+    Editing(i32, String),
 }
 
 impl Default for Status {
@@ -53,553 +104,1611 @@ pub enum Message {
     EditPaneRepaymentInputChanged(String),
     EditPaneInterestInputChanged(String),
     CloseEditPane,
+    CountedCashInputChanged(String),
+    BankBalanceInputChanged(String),
+    PostAdjustmentToggled(bool),
+    ReconciliationReasonInputChanged(String),
+    RecordReconciliationButtonPressed,
+    AddExpenseButtonPressed,
+    AddIncomeButtonPressed,
+    EditPaneCategorySelected(Category),
+    EditPaneDescriptionInputChanged(String),
+    EditPaneAmountInputChanged(String),
+    ToggleChartsButtonPressed,
+    SortColumnPressed(SortColumn),
+    MemberSearchChanged(String),
+    MemberFilterSelected(MemberFilter),
+    ExportPathInputChanged(String),
+    ExportButtonPressed,
+    ImportPathInputChanged(String),
+    ImportButtonPressed,
+    BackupFolderInputChanged(String),
+    BackupRetentionInputChanged(String),
+    SnapshotRetentionInputChanged(String),
+    SaveBackupSettingsButtonPressed,
+    BackupNowButtonPressed,
+    RestoreBackupButtonPressed(usize),
+    RestoreSnapshotButtonPressed(usize),
+    ConfirmRestoreButtonPressed,
+    CancelRestoreButtonPressed,
+    WindowCloseRequested,
+    GroupSelected(Group),
+    NewGroupNameInputChanged(String),
+    CreateGroupButtonPressed,
+    SwitchGroupButtonPressed,
+    UndoButtonPressed,
+    RedoButtonPressed,
+    MemberPhoneInputChanged(String),
+    MemberNotesInputChanged(String),
+    SaveMemberProfilePressed,
+    ThemeSelected(Theme),
+    LanguageSelected(Language),
+    AllocationRuleSelected(AllocationRule),
+    CurrencySymbolInputChanged(String),
+    CurrencyPositionSelected(SymbolPosition),
+    CurrencyDecimalsInputChanged(String),
+    CurrencyGroupingInputChanged(String),
+    CurrencyDecimalSeparatorInputChanged(String),
+    SaveCurrencyButtonPressed,
+    FocusNextInput,
+    FocusPreviousInput,
+    SearchShortcutPressed,
+    /// Enter pressed while no input had it to submit
+    EnterPressed,
+    NotificationTick(Instant),
+    DismissNotification(usize),
+    NotificationLinkPressed(usize),
+    RetryOpeningBooksPressed,
 }
 
 pub struct Flags {
     pub admin_password: Option<String>,
+    pub group: Group,
+    pub groups: Vec<Group>,
     pub db_connection: rusqlite::Connection,
+    /// Why the books could not be read, if they could not. The
+    /// app then starts on the recovery screen instead of the login
+    pub load_error: Option<Error>,
+    /// Why the group list could not be saved, if it could not.
+    /// The group can still be used, so this is only noted
+    pub group_list_error: Option<Error>,
 }
 
-impl Default for Flags {
-    fn default() -> Self {
+impl Flags {
+    /// Opens the database chosen by `args`, the environment or the
+    /// config file, adding it to the group list if it is new
+    pub fn open(args: &Args) -> family_banking_core::Result<Self> {
+        let (db_path, db_connection) = config::open_database(args)?;
+        let mut config = config::load_config()?;
+        let known_groups = config.groups.len();
+        let group = config::find_or_add_group(&mut config, &db_path);
+
+        let group_list_error = if config.groups.len() != known_groups {
+            config::save_config(&config).err()
+        } else {
+            None
+        };
+
+        Ok(Self {
+            group_list_error,
+            ..Self::from_connection(group, config.groups, db_connection)
+        })
+    }
+
+    /// Flags for a group whose database has already been opened,
+    /// brought up to date if it was made by an older version
+    pub fn from_connection(
+        group: Group,
+        groups: Vec<Group>,
+        db_connection: rusqlite::Connection,
+    ) -> Self {
+        let (admin_password, load_error) = match open_books(&db_connection) {
+            Ok(admin_password) => (admin_password, None),
+            Err(err) => (None, Some(err)),
+        };
+
         Self {
-            admin_password: None,
-            db_connection: rusqlite::Connection::open("./db.sqlite3").unwrap(),
+            admin_password,
+            group,
+            groups,
+            db_connection,
+            load_error,
+            group_list_error: None,
         }
     }
 }
 
-impl Application for FamilyBanking {
-    type Message = Message;
+/// Reads the admin password of the books open on `conn` and brings
+/// them up to date, which fails if they are damaged or in use elsewhere
+fn open_books(conn: &rusqlite::Connection) -> family_banking_core::Result<Option<String>> {
+    let admin_password = db_operations::get_password(conn)?;
 
-    type Executor = iced::executor::Default;
+    backup::update_db_schema(conn)?;
+    Ok(admin_password)
+}
 
-    type Flags = Flags;
+/// The theme chosen for the books open on `conn`, or the
+/// default theme if none has been chosen or it cannot be read
+fn saved_theme(conn: &rusqlite::Connection) -> Theme {
+    match db_operations::get_setting(conn, style::THEME_SETTING) {
+        Ok(Some(setting)) => Theme::from_setting(&setting),
+        _ => Theme::default(),
+    }
+}
 
-    fn new(flags: Self::Flags) -> (Self, iced::Command<Message>) {
-        (
-            FamilyBanking {
-                db_connection: flags.db_connection,
-                admin_password: flags.admin_password,
-                status: Status::default(),
+/// The language saved with the books open on `conn`, or
+/// English if none has been chosen or it cannot be read
+fn saved_language(conn: &rusqlite::Connection) -> Language {
+    match db_operations::get_setting(conn, i18n::LANGUAGE_SETTING) {
+        Ok(Some(setting)) => Language::from_setting(&setting),
+        _ => Language::default(),
+    }
+}
+
+/// The name of member `id`, for saying what was done for them
+fn member_name(conn: &rusqlite::Connection, id: i32) -> String {
+    db_operations::member_details(conn, id)
+        .map(|details| details.member.name)
+        .unwrap_or_default()
+}
+
+/// The newest ledger record of the books open on `conn`, which is
+/// that of a change just made, for linking its notification to
+fn newest_record(conn: &rusqlite::Connection) -> Option<i64> {
+    db_operations::last_ledger_id(conn).unwrap_or(None)
+}
+
+/// The currency saved with the books open on `conn`, or the
+/// default currency if it cannot be read
+fn saved_currency(conn: &rusqlite::Connection) -> Currency {
+    db_operations::currency(conn).unwrap_or_default()
+}
+
+impl FamilyBanking {
+    pub fn from_flags(flags: Flags) -> Self {
+        let language = saved_language(&flags.db_connection);
+
+        // Set now, so messages made before the first view are translated
+        i18n::set_language(language);
+
+        let status = match flags.load_error {
+            Some(err) => Status::Unreadable(RecoveryView::new(err, &flags.db_connection)),
+            None => Status::default(),
+        };
+
+        let mut app = FamilyBanking {
+            group: flags.group,
+            groups: flags.groups,
+            admin_password: flags.admin_password,
+            status,
+            exiting: false,
+            exit_snapshot_failed: false,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            member_query: MemberQuery::default(),
+            theme: saved_theme(&flags.db_connection),
+            currency: saved_currency(&flags.db_connection),
+            language,
+            notifications: Vec::new(),
+            db_connection: flags.db_connection,
+        };
+
+        if let Some(err) = flags.group_list_error {
+            app.notify(
+                tr_with("notice.group_list_not_saved", &[&i18n::error(&err)]),
+                None,
+            );
+        }
+
+        app
+    }
+
+    /// Closes the open group and opens `group`, which
+    /// then has to be logged in to with its own password
+    fn switch_group(&mut self, group: Group) -> Result<(), String> {
+        let db_connection = config::open_group(&group).map_err(|err| i18n::error(&err))?;
+
+        self.admin_password = open_books(&db_connection).map_err(|err| i18n::error(&err))?;
+        self.db_connection = db_connection;
+        self.load_saved_settings();
+        self.group = group;
+        self.status = Status::default();
+        self.member_query = MemberQuery::default();
+        self.notifications.clear();
+        self.forget_changes();
+
+        Ok(())
+    }
+
+    /// Reads the theme, currency and language again, after the
+    /// books they are saved with have been opened or replaced
+    fn load_saved_settings(&mut self) {
+        self.theme = saved_theme(&self.db_connection);
+        self.currency = saved_currency(&self.db_connection);
+        self.language = saved_language(&self.db_connection);
+        i18n::set_language(self.language);
+    }
+
+    /// What confirming the edit pane with `message` would do to a
+    /// member that their ledger record does not hold, if anything
+    fn unrecorded_change(&self, message: &Message) -> Option<UnrecordedChange> {
+        match (message, &self.status) {
+            (Message::EditPaneConfirmButtonClicked, Status::LoggedIn(main_view)) => {
+                match main_view.tab_data.edit_pane() {
+                    Some(EditingPane::AddingUser(_)) => Some(UnrecordedChange::Adding),
+                    Some(EditingPane::EditingUser(user_id, _)) => Some(UnrecordedChange::Editing(
+                        *user_id,
+                        member_name(&self.db_connection, *user_id),
+                    )),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Puts the change made since `last_record` on the undo stack. A
+    /// change that cannot be undone, such as adding, renaming or deleting
+    /// a member, clears the history, as the changes before it may depend
+    /// on it and undoing would otherwise reverse one of those instead
+    fn remember_changes(&mut self, last_record: Option<i64>, unrecorded: Option<UnrecordedChange>) {
+        let made_unrecorded_change = match unrecorded {
+            Some(UnrecordedChange::Adding) => {
+                db_operations::last_ledger_id(&self.db_connection).unwrap_or(None) != last_record
+            }
+            Some(UnrecordedChange::Editing(user_id, name)) => {
+                member_name(&self.db_connection, user_id) != name
+            }
+            None => false,
+        };
+
+        if made_unrecorded_change {
+            return self.forget_changes();
+        }
+
+        let records = match db_operations::fetch_ledger(&self.db_connection) {
+            Ok(records) => records,
+            Err(_) => return self.forget_changes(),
+        };
+        let new_records: Vec<_> = records
+            .iter()
+            .filter(|record| Some(record.id) > last_record)
+            .collect();
+
+        match new_records.as_slice() {
+            [] => {}
+            // Deleted members are no longer linked to their records
+            [record] if record.member_id.is_some() => {
+                self.undo_stack.push(record.id);
+                self.redo_stack.clear();
+            }
+            _ => self.forget_changes(),
+        }
+    }
+
+    /// Clears the undo history, once the books
+    /// have been replaced or another group opened
+    fn forget_changes(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+
+    /// Reverses the newest change on the undo stack, or on the redo
+    /// stack if `redo` is set, and moves the record that reversed it
+    /// onto the other stack
+    fn reverse_last_change(&mut self, redo: bool) {
+        let current_tab = match &self.status {
+            Status::LoggedIn(main_view) => main_view.current_tab,
+            _ => return,
+        };
+        let (from, to) = if redo {
+            (&mut self.redo_stack, &mut self.undo_stack)
+        } else {
+            (&mut self.undo_stack, &mut self.redo_stack)
+        };
+        let id = match from.last() {
+            Some(id) => *id,
+            None => return,
+        };
+
+        match db_operations::reverse_transaction(&self.db_connection, id) {
+            Ok(reversal_id) => {
+                from.pop();
+                to.push(reversal_id);
+                self.show_tab(current_tab);
+            }
+            Err(err) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    main_view.undo_error = i18n::error(&err)
+                }
+            }
+        }
+    }
+
+    /// Opens `tab`, loading what it shows from the books
+    fn show_tab(&mut self, tab: Tab) {
+        let tab_data = match tab {
+            Tab::Home => TabData::Home(tab_data::home_tab_data(&self.db_connection)),
+            Tab::Users => TabData::Users(tab_data::users_tab_data(&self.db_connection)),
+            Tab::Debts => TabData::Debts(tab_data::debts_tab_data(&self.db_connection)),
+            Tab::Expenses => TabData::Expenses(tab_data::expenses_tab_data(&self.db_connection)),
+            Tab::Reconciliation => {
+                TabData::Reconciliation(tab_data::reconciliation_tab_data(&self.db_connection))
+            }
+            Tab::Settings => TabData::Settings(tab_data::settings_tab_data(&self.db_connection)),
+            Tab::Member(id) => TabData::Member(tab_data::member_tab_data(&self.db_connection, id)),
+        };
+        self.status = Status::LoggedIn(MainView {
+            current_tab: tab,
+            tab_data,
+            ..MainView::default()
+        })
+    }
+
+    /// Shows `text` until it expires, with a link to `link`
+    /// unless that is on the tab already open
+    fn notify(&mut self, text: String, link: Option<Link>) {
+        let current_tab = match &self.status {
+            Status::LoggedIn(main_view) => Some(main_view.current_tab),
+            Status::NotLoggedIn(_) | Status::Unreadable(_) => None,
+        };
+
+        notifications::push(
+            &mut self.notifications,
+            Notification::new(text, link.filter(|link| Some(link.tab) != current_tab)),
+        );
+    }
+
+    /// The text inputs on screen, in the order Tab moves through them
+    fn text_inputs_mut(&mut self) -> Vec<&mut iced::text_input::State> {
+        match &mut self.status {
+            Status::NotLoggedIn(login_view_data) => {
+                login_view_data.text_inputs_mut(self.admin_password.is_none())
+            }
+            Status::LoggedIn(main_view) => main_view.tab_data.text_inputs_mut(),
+            Status::Unreadable(_) => Vec::new(),
+        }
+    }
+
+    /// What Enter does when no input has taken it: logging in, or
+    /// confirming the open edit pane. Deleting a member is left to
+    /// its button, so that it is never done by a stray key press
+    fn enter_message(&mut self) -> Option<Message> {
+        match &mut self.status {
+            Status::NotLoggedIn(_) => Some(match self.admin_password {
+                None => Message::NewPasswordButtonPressed,
+                Some(_) => Message::LoginButtonPressed,
+            }),
+            Status::LoggedIn(main_view) => match main_view.tab_data.edit_pane_mut() {
+                None | Some(EditingPane::Closed) | Some(EditingPane::ConfirmingDeletion(_)) => None,
+                Some(_) => Some(Message::EditPaneConfirmButtonClicked),
             },
-            iced::Command::none(),
-        )
+            Status::Unreadable(_) => None,
+        }
     }
 
-    fn title(&self) -> String {
-        String::from("Family Banking")
+    /// Applies `message` to the state of the app. This is everything
+    /// `update` does, without needing a window, so that tests can
+    /// play a sequence of messages through it
+    pub fn handle(&mut self, message: Message) {
+        let changes_books = matches!(
+            message,
+            Message::EditPaneConfirmButtonClicked | Message::RecordReconciliationButtonPressed
+        );
+        let last_record = db_operations::last_ledger_id(&self.db_connection).unwrap_or(None);
+        let unrecorded = self.unrecorded_change(&message);
+
+        self.apply(message);
+
+        if changes_books {
+            self.remember_changes(last_record, unrecorded);
+        }
     }
 
-    fn update(
-        &mut self,
-        message: Message,
-        clipboard: &mut iced::Clipboard,
-    ) -> iced::Command<Message> {
+    fn apply(&mut self, message: Message) {
         match message {
-            Message::TabButtonPressed(tab) => match &mut self.status {
-                Status::LoggedIn(_) => {
-                    let tab_data = match tab {
-                        Tab::Home => {
-                            TabData::Home(db_operations::home_tab_data(&self.db_connection))
-                        }
-                        Tab::Users => {
-                            TabData::Users(db_operations::users_tab_data(&self.db_connection))
-                        }
-                        Tab::Debts => {
-                            TabData::Debts(db_operations::debts_tab_data(&self.db_connection))
-                        }
-                    };
-                    self.status = Status::LoggedIn(MainView {
-                        current_tab: tab,
-                        tab_data: tab_data,
-                        ..MainView::default()
-                    })
+            Message::TabButtonPressed(tab) => {
+                if let Status::LoggedIn(_) = &self.status {
+                    self.show_tab(tab)
                 }
-                _ => {}.clone(),
-            },
+            }
             Message::PasswordInput1Changed(text) => {
-                match &mut self.status {
-                    Status::NotLoggedIn(view) => {
-                        self.status = Status::NotLoggedIn(LoginView {
-                            password_input_1_value: text,
-                            ..view.clone()
-                        })
-                    }
-                    _ => {}
+                if let Status::NotLoggedIn(view) = &mut self.status {
+                    self.status = Status::NotLoggedIn(LoginView {
+                        password_input_1_value: text,
+                        ..view.clone()
+                    })
                 };
             }
             Message::PasswordInput2Changed(text) => {
-                match &mut self.status {
-                    Status::NotLoggedIn(view) => {
-                        self.status = Status::NotLoggedIn(LoginView {
-                            password_input_2_value: text,
-                            ..view.clone()
-                        })
-                    }
-                    _ => {}
+                if let Status::NotLoggedIn(view) = &mut self.status {
+                    self.status = Status::NotLoggedIn(LoginView {
+                        password_input_2_value: text,
+                        ..view.clone()
+                    })
                 };
             }
-            Message::LoginButtonPressed => match &self.admin_password {
-                Some(hash) => match &mut self.status {
-                    Status::NotLoggedIn(view) => {
-                        let matches = match argon2::verify_encoded(
-                            &hash,
-                            view.password_input_1_value.as_bytes(),
-                        ) {
-                            Ok(boolean) => boolean,
-                            Err(_) => false,
-                        };
+            Message::LoginButtonPressed => {
+                if let Some(hash) = &self.admin_password {
+                    if let Status::NotLoggedIn(view) = &mut self.status {
+                        let matches =
+                            db_operations::verify_password(hash, &view.password_input_1_value);
 
                         if matches {
                             self.status = Status::LoggedIn(MainView {
-                                tab_data: TabData::Home(db_operations::home_tab_data(
+                                tab_data: TabData::Home(tab_data::home_tab_data(
                                     &self.db_connection,
                                 )),
                                 ..MainView::default()
                             });
                         } else {
                             self.status = Status::NotLoggedIn(LoginView {
-                                login_error_message: String::from("Incorrect password"),
+                                login_error_message: tr("login.incorrect_password"),
                                 ..LoginView::default()
                             });
                         }
                     }
-                    _ => {}
-                },
-                None => {}
-            },
-            Message::NewPasswordButtonPressed => match &mut self.status {
-                Status::NotLoggedIn(login_view_data) => {
+                }
+            }
+            Message::NewPasswordButtonPressed => {
+                if let Status::NotLoggedIn(login_view_data) = &mut self.status {
                     if login_view_data.password_input_1_value.len() < 6
                         || login_view_data.password_input_2_value.len() < 6
                     {
-                        login_view_data.login_error_message =
-                            String::from("Password needs to be at least 6 characters long");
+                        login_view_data.login_error_message = tr("login.password_too_short");
                     } else if login_view_data.password_input_1_value
                         != login_view_data.password_input_2_value
                     {
-                        login_view_data.login_error_message = String::from("Passwords do not match")
+                        login_view_data.login_error_message = tr("login.passwords_differ")
                     } else {
-                        db_operations::store_password(
+                        match db_operations::store_password(
                             &self.db_connection,
                             login_view_data.password_input_1_value.clone(),
-                        );
+                        )
+                        .and_then(|_| db_operations::get_password(&self.db_connection))
+                        {
+                            Ok(admin_password) => {
+                                self.admin_password = admin_password;
+                                self.status = Status::LoggedIn(MainView {
+                                    tab_data: TabData::Home(tab_data::home_tab_data(
+                                        &self.db_connection,
+                                    )),
+                                    ..MainView::default()
+                                })
+                            }
+                            Err(err) => login_view_data.login_error_message = i18n::error(&err),
+                        }
+                    }
+                }
+            }
+            Message::EditUserButtonPressed(user_id) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    let member = db_operations::members(&self.db_connection)
+                        .ok()
+                        .and_then(|members| members.into_iter().find(|u| u.id == user_id));
 
-                        self.status = Status::LoggedIn(MainView {
-                            tab_data: TabData::Home(db_operations::home_tab_data(
-                                &self.db_connection,
-                            )),
-                            ..MainView::default()
+                    if let (Some(edit_pane), Some(user)) =
+                        (main_view.tab_data.edit_pane_mut(), member)
+                    {
+                        *edit_pane = EditingPane::EditingUser(
+                            user_id,
+                            EditUserDetails {
+                                name_value: user.name.clone(),
+                                shares_value: user.shares.to_string(),
+                                ..EditUserDetails::default()
+                            },
+                        )
+                    }
+                }
+            }
+            Message::DeleteUserButtonPressed(user_id) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let Some(edit_pane) = main_view.tab_data.edit_pane_mut() {
+                        *edit_pane = EditingPane::ConfirmingDeletion(ConfirmDeletion {
+                            user_id,
+                            ..Default::default()
                         })
                     }
                 }
-                _ => {}
-            },
-            Message::EditUserButtonPressed(user_id) => match &mut self.status {
-                Status::LoggedIn(main_view) => match &mut main_view.tab_data {
-                    TabData::Users(utd_result) => match utd_result {
-                        Ok(users_tab_data) => {
-                            let user = users_tab_data
-                                .user_details
-                                .clone()
-                                .iter()
-                                .find(|u| u.id == user_id)
-                                .unwrap()
-                                .clone();
-
-                            users_tab_data.edit_pane = EditingPane::EditingUser(
-                                user_id,
-                                EditUserDetails {
-                                    name_value: user.name.clone(),
-                                    shares_value: user.contribution.to_string(),
-                                    ..EditUserDetails::default()
-                                },
-                            )
+            }
+            Message::AddUserButtonPressed => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let Some(edit_pane) = main_view.tab_data.edit_pane_mut() {
+                        *edit_pane = EditingPane::AddingUser(EditUserDetails::default())
+                    }
+                }
+            }
+            Message::EditPaneUserNameInputChanged(value) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    match main_view.tab_data.edit_pane_mut() {
+                        Some(EditingPane::AddingUser(edit_user_details)) => {
+                            edit_user_details.name_value = value
                         }
-                        _ => {}
-                    },
-                    _ => {}
-                },
-                _ => {}
-            },
-            Message::DeleteUserButtonPressed(user_id) => match &mut self.status {
-                Status::LoggedIn(main_view) => match &mut main_view.tab_data {
-                    TabData::Users(utd_result) => match utd_result {
-                        Ok(users_tab_data) => {
-                            users_tab_data.edit_pane =
-                                EditingPane::ConfirmingDeletion(ConfirmDeletion {
-                                    user_id,
-                                    ..Default::default()
-                                })
+                        Some(EditingPane::EditingUser(_user_id, edit_user_details)) => {
+                            edit_user_details.name_value = value
                         }
                         _ => {}
-                    },
-                    _ => {}
-                },
-                _ => {}
-            },
-            Message::AddUserButtonPressed => match &mut self.status {
-                Status::LoggedIn(main_view) => match &mut main_view.tab_data {
-                    TabData::Users(utd_result) => match utd_result {
-                        Ok(users_tab_data) => {
-                            users_tab_data.edit_pane =
-                                EditingPane::AddingUser(EditUserDetails::default())
+                    }
+                }
+            }
+            Message::EditPaneUserShareInputChanged(value) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    match main_view.tab_data.edit_pane_mut() {
+                        Some(EditingPane::AddingUser(edit_user_details)) => {
+                            edit_user_details.shares_value = value
+                        }
+                        Some(EditingPane::EditingUser(_user_id, edit_user_details)) => {
+                            edit_user_details.shares_value = value
                         }
                         _ => {}
-                    },
-                    _ => {}
-                },
-                _ => {}
-            },
-            Message::EditPaneUserNameInputChanged(value) => match &mut self.status {
-                Status::LoggedIn(main_view) => match &mut main_view.tab_data {
-                    TabData::Users(utd_result) => match utd_result {
-                        Ok(users_tab_data) => match &mut users_tab_data.edit_pane {
-                            EditingPane::AddingUser(edit_user_details) => {
-                                edit_user_details.name_value = value
-                            }
-                            EditingPane::EditingUser(_user_id, edit_user_details) => {
-                                edit_user_details.name_value = value
+                    }
+                }
+            }
+            // Each change reloads the tab it was made from, except
+            // deleting a member, whose page is then gone
+            Message::EditPaneConfirmButtonClicked => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    let current_tab = main_view.current_tab;
+
+                    match main_view.tab_data.edit_pane_mut() {
+                        Some(EditingPane::AddingUser(edit_user_details)) => {
+                            if let Some(shares) =
+                                self.currency.parse(&edit_user_details.shares_value)
+                            {
+                                match db_operations::store_new_user(
+                                    &self.db_connection,
+                                    edit_user_details.name_value.clone(),
+                                    shares,
+                                ) {
+                                    Ok(_) => {
+                                        let text = tr_with(
+                                            "notice.user_added",
+                                            &[
+                                                &edit_user_details.name_value,
+                                                &self.currency.format(shares),
+                                            ],
+                                        );
+
+                                        self.show_tab(current_tab);
+                                        self.notify(text, None)
+                                    }
+                                    Err(err) => edit_user_details.error_message = i18n::error(&err),
+                                }
+                            } else {
+                                edit_user_details.error_message = tr("common.invalid_number")
                             }
-                            _ => {}
-                        },
-                        _ => {}
-                    },
-                    _ => {}
-                },
-                _ => {}
-            },
-            Message::EditPaneUserShareInputChanged(value) => match &mut self.status {
-                Status::LoggedIn(main_view) => match &mut main_view.tab_data {
-                    TabData::Users(utd_result) => match utd_result {
-                        Ok(users_tab_data) => match &mut users_tab_data.edit_pane {
-                            EditingPane::AddingUser(edit_user_details) => {
-                                edit_user_details.shares_value = value
+                        }
+                        Some(EditingPane::EditingUser(user_id, edit_user_details)) => {
+                            if let Some(shares) =
+                                self.currency.parse(&edit_user_details.shares_value)
+                            {
+                                let last_record = newest_record(&self.db_connection);
+
+                                match db_operations::edit_user(
+                                    &self.db_connection,
+                                    *user_id,
+                                    edit_user_details.name_value.clone(),
+                                    shares,
+                                ) {
+                                    Ok(_) => {
+                                        let text = tr_with(
+                                            "notice.user_edited",
+                                            &[&edit_user_details.name_value],
+                                        );
+                                        // A rename alone is not recorded
+                                        let link = Link {
+                                            tab: Tab::Member(*user_id),
+                                            record: newest_record(&self.db_connection)
+                                                .filter(|id| Some(*id) != last_record),
+                                        };
+
+                                        self.show_tab(current_tab);
+                                        self.notify(text, Some(link))
+                                    }
+                                    Err(err) => edit_user_details.error_message = i18n::error(&err),
+                                }
+                            } else {
+                                edit_user_details.error_message = tr("common.invalid_number")
                             }
-                            EditingPane::EditingUser(_user_id, edit_user_details) => {
-                                edit_user_details.shares_value = value
+                        }
+                        Some(EditingPane::ConfirmingDeletion(confirm_deletion)) => {
+                            let name = member_name(&self.db_connection, confirm_deletion.user_id);
+
+                            match backup::create_snapshot(&self.db_connection, "delete")
+                                .map_err(|err| i18n::error(&err))
+                                .and_then(|_| {
+                                    db_operations::delete_user(
+                                        &self.db_connection,
+                                        confirm_deletion.user_id,
+                                    )
+                                    .map_err(|err| i18n::error(&err))
+                                }) {
+                                Ok(_) => {
+                                    self.show_tab(match current_tab {
+                                        Tab::Member(_) => Tab::Users,
+                                        tab => tab,
+                                    });
+                                    self.notify(tr_with("notice.user_deleted", &[&name]), None)
+                                }
+                                Err(err) => confirm_deletion.error_message = err,
                             }
-                            _ => {}
-                        },
-                        _ => {}
-                    },
-                    _ => {}
-                },
-                _ => {}
-            },
-            Message::EditPaneConfirmButtonClicked => match &mut self.status {
-                Status::LoggedIn(main_view) => match &mut main_view.tab_data {
-                    TabData::Users(utd_result) => match utd_result {
-                        Ok(users_tab_data) => match &mut users_tab_data.edit_pane {
-                            EditingPane::AddingUser(edit_user_details) => {
-                                if edit_user_details.name_value == "".to_string() {
-                                    edit_user_details.error_message =
-                                        "Enter valid username".to_string()
-                                } else if let Ok(shares) = edit_user_details.shares_value.parse()
-                                    as Result<f64, <f64 as FromStr>::Err>
+                        }
+                        Some(EditingPane::AddingDebt(add_debt)) => {
+                            if let Some(loan) = self.currency.parse(&add_debt.debt_value) {
+                                if let Some(interest) =
+                                    self.currency.parse(&add_debt.interest_value)
                                 {
-                                    match db_operations::store_new_user(
+                                    match db_operations::borrow_debt(
                                         &self.db_connection,
-                                        edit_user_details.name_value.clone(),
-                                        shares,
+                                        add_debt.user_id,
+                                        loan,
+                                        interest,
                                     ) {
                                         Ok(_) => {
-                                            self.status = Status::LoggedIn(MainView {
-                                                current_tab: Tab::Users,
-                                                tab_data: TabData::Users(
-                                                    db_operations::users_tab_data(
+                                            let text = tr_with(
+                                                "notice.lent",
+                                                &[
+                                                    &self.currency.format(loan),
+                                                    &member_name(
                                                         &self.db_connection,
+                                                        add_debt.user_id,
                                                     ),
-                                                ),
-                                                ..MainView::default()
-                                            })
+                                                    &self.currency.format(interest),
+                                                ],
+                                            );
+                                            let link = Link {
+                                                tab: Tab::Member(add_debt.user_id),
+                                                record: newest_record(&self.db_connection),
+                                            };
+
+                                            self.show_tab(current_tab);
+                                            self.notify(text, Some(link))
                                         }
-                                        Err(err) => {
-                                            edit_user_details.error_message = err.to_string()
+                                        Err(err) => add_debt.error_message = i18n::error(&err),
+                                    }
+                                } else {
+                                    add_debt.error_message = tr("debts.invalid_interest")
+                                }
+                            } else {
+                                add_debt.error_message = tr("debts.invalid_loan")
+                            }
+                        }
+                        Some(EditingPane::RepayingDebt(repay_debt)) => {
+                            if let Some(repayment) =
+                                self.currency.parse(&repay_debt.repayment_value)
+                            {
+                                match db_operations::repay(
+                                    &self.db_connection,
+                                    repay_debt.user_id,
+                                    repayment,
+                                ) {
+                                    Ok(split) => {
+                                        let text = tr_with(
+                                            "notice.repaid",
+                                            &[
+                                                &self.currency.format(repayment),
+                                                &member_name(
+                                                    &self.db_connection,
+                                                    repay_debt.user_id,
+                                                ),
+                                                &self.currency.format(split.loan),
+                                                &self.currency.format(split.interest),
+                                            ],
+                                        );
+                                        let link = Link {
+                                            tab: Tab::Member(repay_debt.user_id),
+                                            record: newest_record(&self.db_connection),
+                                        };
+
+                                        self.show_tab(current_tab);
+                                        self.notify(text, Some(link))
+                                    }
+                                    Err(err) => repay_debt.error_message = i18n::error(&err),
+                                }
+                            } else {
+                                repay_debt.error_message = tr("common.invalid_number")
+                            }
+                        }
+                        Some(EditingPane::AddingEntry(add_entry)) => {
+                            if let Some(amount) = self.currency.parse(&add_entry.amount_value) {
+                                if let Some(category) = &add_entry.category {
+                                    match db_operations::store_entry(
+                                        &self.db_connection,
+                                        category,
+                                        add_entry.description_value.trim().to_string(),
+                                        amount,
+                                    ) {
+                                        Ok(_) => {
+                                            let text = tr_with(
+                                                match category.kind {
+                                                    EntryKind::Expense => "notice.expense",
+                                                    EntryKind::Income => "notice.income",
+                                                },
+                                                &[&self.currency.format(amount), &category.name],
+                                            );
+                                            let link = Link {
+                                                tab: Tab::Expenses,
+                                                record: newest_record(&self.db_connection),
+                                            };
+
+                                            self.show_tab(current_tab);
+                                            self.notify(text, Some(link))
                                         }
+                                        Err(err) => add_entry.error_message = i18n::error(&err),
                                     }
                                 } else {
-                                    edit_user_details.error_message =
-                                        "Enter valid number".to_string()
+                                    add_entry.error_message = tr("expenses.no_category")
                                 }
+                            } else {
+                                add_entry.error_message = tr("common.invalid_number")
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Message::AddDebtButtonPressed(user_id) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let Some(edit_pane) = main_view.tab_data.edit_pane_mut() {
+                        *edit_pane = EditingPane::AddingDebt(AddDebt {
+                            user_id,
+                            ..Default::default()
+                        })
+                    }
+                }
+            }
+            Message::RepayDebtButtonPressed(user_id) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let Some(edit_pane) = main_view.tab_data.edit_pane_mut() {
+                        *edit_pane = EditingPane::RepayingDebt(RepayDebt {
+                            user_id,
+                            rule: db_operations::allocation_rule(&self.db_connection)
+                                .unwrap_or_default(),
+                            ..Default::default()
+                        })
+                    }
+                }
+            }
+            Message::EditPaneDebtInputChanged(value) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let Some(EditingPane::AddingDebt(add_debt)) =
+                        main_view.tab_data.edit_pane_mut()
+                    {
+                        add_debt.debt_value = value
+                    }
+                }
+            }
+            Message::EditPaneInterestInputChanged(value) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let Some(EditingPane::AddingDebt(add_debt)) =
+                        main_view.tab_data.edit_pane_mut()
+                    {
+                        add_debt.interest_value = value
+                    }
+                }
+            }
+            Message::EditPaneRepaymentInputChanged(value) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let Some(EditingPane::RepayingDebt(repay_debt)) =
+                        main_view.tab_data.edit_pane_mut()
+                    {
+                        repay_debt.repayment_value = value
+                    }
+                }
+            }
+            Message::CloseEditPane => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let Some(edit_pane) = main_view.tab_data.edit_pane_mut() {
+                        *edit_pane = EditingPane::Closed
+                    }
+                }
+            }
+            Message::MemberPhoneInputChanged(value) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Member(Ok(member_tab_data)) = &mut main_view.tab_data {
+                        member_tab_data.phone_value = value
+                    }
+                }
+            }
+            Message::MemberNotesInputChanged(value) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Member(Ok(member_tab_data)) = &mut main_view.tab_data {
+                        member_tab_data.notes_value = value
+                    }
+                }
+            }
+            Message::SaveMemberProfilePressed => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Member(Ok(member_tab_data)) = &mut main_view.tab_data {
+                        match db_operations::store_member_profile(
+                            &self.db_connection,
+                            member_tab_data.details.member.id,
+                            &member_tab_data.phone_value,
+                            &member_tab_data.notes_value,
+                        ) {
+                            Ok(_) => {
+                                let profile = &mut member_tab_data.details.profile;
+
+                                profile.phone = member_tab_data.phone_value.trim().to_string();
+                                profile.notes = member_tab_data.notes_value.trim().to_string();
+                                member_tab_data.error_message.clear();
                             }
-                            EditingPane::EditingUser(user_id, edit_user_details) => {
-                                if edit_user_details.name_value == "".to_string() {
-                                    edit_user_details.error_message =
-                                        "Enter valid username".to_string()
-                                } else if let Ok(shares) = edit_user_details.shares_value.parse()
-                                    as Result<f64, <f64 as FromStr>::Err>
+                            Err(err) => member_tab_data.error_message = i18n::error(&err),
+                        }
+                    }
+                }
+            }
+            Message::CountedCashInputChanged(value) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Reconciliation(Ok(reconciliation_tab_data)) =
+                        &mut main_view.tab_data
+                    {
+                        reconciliation_tab_data.counted_cash_value = value
+                    }
+                }
+            }
+            Message::BankBalanceInputChanged(value) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Reconciliation(Ok(reconciliation_tab_data)) =
+                        &mut main_view.tab_data
+                    {
+                        reconciliation_tab_data.bank_balance_value = value
+                    }
+                }
+            }
+            Message::PostAdjustmentToggled(checked) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Reconciliation(Ok(reconciliation_tab_data)) =
+                        &mut main_view.tab_data
+                    {
+                        reconciliation_tab_data.post_adjustment = checked
+                    }
+                }
+            }
+            Message::ReconciliationReasonInputChanged(value) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Reconciliation(Ok(reconciliation_tab_data)) =
+                        &mut main_view.tab_data
+                    {
+                        reconciliation_tab_data.reason_value = value
+                    }
+                }
+            }
+            Message::RecordReconciliationButtonPressed => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Reconciliation(Ok(reconciliation_tab_data)) =
+                        &mut main_view.tab_data
+                    {
+                        if let Some(counted_cash) = self
+                            .currency
+                            .parse(&reconciliation_tab_data.counted_cash_value)
+                        {
+                            if let Some(bank_balance) = self
+                                .currency
+                                .parse(&reconciliation_tab_data.bank_balance_value)
+                            {
+                                let variance = counted_cash + bank_balance
+                                    - reconciliation_tab_data.computed_cash;
+
+                                if reconciliation_tab_data.post_adjustment
+                                    && reconciliation_tab_data.reason_value.trim().is_empty()
                                 {
-                                    match db_operations::edit_user(
+                                    reconciliation_tab_data.error_message =
+                                        tr("reconcile.no_reason")
+                                } else {
+                                    match db_operations::record_reconciliation(
                                         &self.db_connection,
-                                        *user_id,
-                                        edit_user_details.name_value.clone(),
-                                        shares,
+                                        reconciliation_tab_data.computed_cash,
+                                        counted_cash,
+                                        bank_balance,
+                                        if reconciliation_tab_data.post_adjustment {
+                                            variance
+                                        } else {
+                                            0.0
+                                        },
+                                        reconciliation_tab_data.reason_value.trim().to_string(),
                                     ) {
                                         Ok(_) => {
                                             self.status = Status::LoggedIn(MainView {
-                                                current_tab: Tab::Users,
-                                                tab_data: TabData::Users(
-                                                    db_operations::users_tab_data(
+                                                current_tab: Tab::Reconciliation,
+                                                tab_data: TabData::Reconciliation(
+                                                    tab_data::reconciliation_tab_data(
                                                         &self.db_connection,
                                                     ),
                                                 ),
                                                 ..MainView::default()
-                                            })
+                                            });
+                                            self.notify(
+                                                tr_with(
+                                                    "notice.reconciled",
+                                                    &[&self.currency.format(variance)],
+                                                ),
+                                                None,
+                                            )
                                         }
                                         Err(err) => {
-                                            edit_user_details.error_message = err.to_string()
+                                            reconciliation_tab_data.error_message =
+                                                i18n::error(&err)
                                         }
                                     }
-                                } else {
-                                    edit_user_details.error_message =
-                                        "Enter valid number".to_string()
                                 }
+                            } else {
+                                reconciliation_tab_data.error_message =
+                                    tr("reconcile.invalid_bank_balance")
+                            }
+                        } else {
+                            reconciliation_tab_data.error_message = tr("reconcile.invalid_cash")
+                        }
+                    }
+                }
+            }
+            Message::AddExpenseButtonPressed => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Expenses(Ok(expenses_tab_data)) = &mut main_view.tab_data {
+                        expenses_tab_data.edit_pane = EditingPane::AddingEntry(AddEntry::new(
+                            EntryKind::Expense,
+                            &expenses_tab_data.categories,
+                        ))
+                    }
+                }
+            }
+            Message::AddIncomeButtonPressed => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Expenses(Ok(expenses_tab_data)) = &mut main_view.tab_data {
+                        expenses_tab_data.edit_pane = EditingPane::AddingEntry(AddEntry::new(
+                            EntryKind::Income,
+                            &expenses_tab_data.categories,
+                        ))
+                    }
+                }
+            }
+            Message::EditPaneCategorySelected(category) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Expenses(Ok(expenses_tab_data)) = &mut main_view.tab_data {
+                        if let EditingPane::AddingEntry(add_entry) =
+                            &mut expenses_tab_data.edit_pane
+                        {
+                            add_entry.category = Some(category)
+                        }
+                    }
+                }
+            }
+            Message::EditPaneDescriptionInputChanged(value) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Expenses(Ok(expenses_tab_data)) = &mut main_view.tab_data {
+                        if let EditingPane::AddingEntry(add_entry) =
+                            &mut expenses_tab_data.edit_pane
+                        {
+                            add_entry.description_value = value
+                        }
+                    }
+                }
+            }
+            Message::EditPaneAmountInputChanged(value) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Expenses(Ok(expenses_tab_data)) = &mut main_view.tab_data {
+                        if let EditingPane::AddingEntry(add_entry) =
+                            &mut expenses_tab_data.edit_pane
+                        {
+                            add_entry.amount_value = value
+                        }
+                    }
+                }
+            }
+            Message::ToggleChartsButtonPressed => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Home(Ok(home_tab_data)) = &mut main_view.tab_data {
+                        home_tab_data.charts_visible = !home_tab_data.charts_visible
+                    }
+                }
+            }
+            Message::SortColumnPressed(column) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    let (name, table, members) = match &mut main_view.tab_data {
+                        TabData::Home(Ok(home_tab_data)) => (
+                            tab_data::HOME_TABLE,
+                            &mut home_tab_data.table,
+                            &mut home_tab_data.user_details,
+                        ),
+                        TabData::Users(Ok(users_tab_data)) => (
+                            tab_data::USERS_TABLE,
+                            &mut users_tab_data.table,
+                            &mut users_tab_data.user_details,
+                        ),
+                        TabData::Debts(Ok(debts_tab_data)) => (
+                            tab_data::DEBTS_TABLE,
+                            &mut debts_tab_data.table,
+                            &mut debts_tab_data.user_details,
+                        ),
+                        _ => return,
+                    };
+
+                    table.sort = table.sort.toggled(column);
+                    table.sort.sort(members);
+
+                    // The table is still sorted if the order cannot be saved
+                    if let Err(err) =
+                        db_operations::store_member_sort(&self.db_connection, name, table.sort)
+                    {
+                        self.notify(
+                            tr_with("notice.sort_not_saved", &[&i18n::error(&err)]),
+                            None,
+                        );
+                    }
+                }
+            }
+            Message::MemberSearchChanged(value) => self.member_query.search = value,
+            Message::MemberFilterSelected(filter) => self.member_query.filter = filter,
+            Message::ExportPathInputChanged(value) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Settings(Ok(settings_tab_data)) = &mut main_view.tab_data {
+                        settings_tab_data.export_path_value = value
+                    }
+                }
+            }
+            Message::ImportPathInputChanged(value) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Settings(Ok(settings_tab_data)) = &mut main_view.tab_data {
+                        settings_tab_data.import_path_value = value
+                    }
+                }
+            }
+            Message::ExportButtonPressed => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Settings(Ok(settings_tab_data)) = &mut main_view.tab_data {
+                        match export::export_to_file(
+                            &self.db_connection,
+                            std::path::Path::new(&settings_tab_data.export_path_value),
+                        ) {
+                            Ok(_) => {
+                                settings_tab_data.error_message = String::new();
+                                settings_tab_data.status_message = tr_with(
+                                    "settings.exported",
+                                    &[&settings_tab_data.export_path_value],
+                                )
                             }
-                            EditingPane::ConfirmingDeletion(confirm_deletion) => {
-                                match db_operations::delete_user(
+                            Err(err) => {
+                                settings_tab_data.status_message = String::new();
+                                settings_tab_data.error_message = i18n::error(&err)
+                            }
+                        }
+                    }
+                }
+            }
+            Message::ImportButtonPressed => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Settings(Ok(settings_tab_data)) = &mut main_view.tab_data {
+                        match backup::create_snapshot(&self.db_connection, "import")
+                            .map_err(|err| i18n::error(&err))
+                            .and_then(|_| {
+                                export::import_from_file(
+                                    &self.db_connection,
+                                    std::path::Path::new(&settings_tab_data.import_path_value),
+                                )
+                                .map_err(|err| i18n::error(&err))
+                            }) {
+                            Ok(_) => {
+                                let status_message = tr_with(
+                                    "settings.imported",
+                                    &[&settings_tab_data.import_path_value],
+                                );
+
+                                self.forget_changes();
+                                self.load_saved_settings();
+
+                                // Rebuilt so the new snapshot shows in the list
+                                self.status = Status::LoggedIn(MainView {
+                                    current_tab: Tab::Settings,
+                                    tab_data: TabData::Settings(
+                                        tab_data::settings_tab_data(&self.db_connection).map(
+                                            |mut settings_tab_data| {
+                                                settings_tab_data.status_message = status_message;
+                                                settings_tab_data
+                                            },
+                                        ),
+                                    ),
+                                    ..MainView::default()
+                                })
+                            }
+                            Err(err) => {
+                                settings_tab_data.status_message = String::new();
+                                settings_tab_data.error_message = err
+                            }
+                        }
+                    }
+                }
+            }
+            Message::BackupFolderInputChanged(value) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Settings(Ok(settings_tab_data)) = &mut main_view.tab_data {
+                        settings_tab_data.backup_folder_value = value
+                    }
+                }
+            }
+            Message::BackupRetentionInputChanged(value) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Settings(Ok(settings_tab_data)) = &mut main_view.tab_data {
+                        settings_tab_data.backup_retention_value = value
+                    }
+                }
+            }
+            Message::SnapshotRetentionInputChanged(value) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Settings(Ok(settings_tab_data)) = &mut main_view.tab_data {
+                        settings_tab_data.snapshot_retention_value = value
+                    }
+                }
+            }
+            Message::SaveBackupSettingsButtonPressed => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Settings(Ok(settings_tab_data)) = &mut main_view.tab_data {
+                        if settings_tab_data.backup_folder_value.trim().is_empty() {
+                            settings_tab_data.error_message = tr("settings.no_backup_folder")
+                        } else if let (Ok(retention), Ok(snapshot_retention)) = (
+                            settings_tab_data.backup_retention_value.parse() as Result<usize, _>,
+                            settings_tab_data.snapshot_retention_value.parse() as Result<usize, _>,
+                        ) {
+                            if retention == 0 {
+                                settings_tab_data.error_message = tr("settings.no_backups_kept")
+                            } else if snapshot_retention == 0 {
+                                settings_tab_data.error_message = tr("settings.no_snapshots_kept")
+                            } else {
+                                match backup::store_backup_settings(
                                     &self.db_connection,
-                                    confirm_deletion.user_id,
+                                    &backup::BackupSettings {
+                                        folder: settings_tab_data.backup_folder_value.trim().into(),
+                                        retention,
+                                        snapshot_retention,
+                                    },
                                 ) {
                                     Ok(_) => {
                                         self.status = Status::LoggedIn(MainView {
-                                            current_tab: Tab::Users,
-                                            tab_data: TabData::Users(
-                                                db_operations::users_tab_data(&self.db_connection),
+                                            current_tab: Tab::Settings,
+                                            tab_data: TabData::Settings(
+                                                tab_data::settings_tab_data(&self.db_connection)
+                                                    .map(|mut settings_tab_data| {
+                                                        settings_tab_data.status_message =
+                                                            tr("settings.backups_saved");
+                                                        settings_tab_data
+                                                    }),
                                             ),
                                             ..MainView::default()
                                         })
                                     }
-                                    Err(err) => confirm_deletion.error_message = err.to_string(),
+                                    Err(err) => settings_tab_data.error_message = i18n::error(&err),
                                 }
                             }
-                            _ => {}
-                        },
-                        _ => {}
-                    },
-
-                    TabData::Debts(dtd_result) => match dtd_result {
-                        Ok(debts_tab_data) => match &mut debts_tab_data.edit_pane {
-                            EditingPane::AddingDebt(add_debt) => {
-                                if let Ok(loan) = add_debt.debt_value.parse()
-                                    as Result<f64, <f64 as FromStr>::Err>
-                                {
-                                    if let Ok(interest) = add_debt.interest_value.parse()
-                                        as Result<f64, <f64 as FromStr>::Err>
-                                    {
-                                        if loan < 0.0 {
-                                            add_debt.error_message =
-                                                "Loan ammount cannot be negative".to_string()
-                                        } else if interest < 0.0 {
-                                            add_debt.error_message =
-                                                "Interest cannot be negative".to_string()
-                                        } else {
-                                            match db_operations::borrow_debt(
-                                                &self.db_connection,
-                                                add_debt.user_id,
-                                                loan,
-                                                interest,
-                                            ) {
-                                                Ok(_) => {
-                                                    self.status = Status::LoggedIn(MainView {
-                                                        current_tab: Tab::Debts,
-                                                        tab_data: TabData::Debts(
-                                                            db_operations::debts_tab_data(
-                                                                &self.db_connection,
-                                                            ),
-                                                        ),
-                                                        ..MainView::default()
-                                                    })
-                                                }
-                                                Err(err) => {
-                                                    add_debt.error_message = err.to_string()
-                                                }
-                                            }
-                                        }
-                                    } else {
-                                        add_debt.error_message = "Invalid interest".to_string()
-                                    }
-                                } else {
-                                    add_debt.error_message = "Invalid loan ammount".to_string()
-                                }
+                        } else {
+                            settings_tab_data.error_message = tr("settings.invalid_retention")
+                        }
+                    }
+                }
+            }
+            Message::BackupNowButtonPressed => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Settings(Ok(settings_tab_data)) = &mut main_view.tab_data {
+                        match backup::create_backup(&self.db_connection) {
+                            Ok(path) => {
+                                self.status = Status::LoggedIn(MainView {
+                                    current_tab: Tab::Settings,
+                                    tab_data: TabData::Settings(
+                                        tab_data::settings_tab_data(&self.db_connection).map(
+                                            |mut settings_tab_data| {
+                                                settings_tab_data.status_message = tr_with(
+                                                    "settings.backed_up",
+                                                    &[&path.display()],
+                                                );
+                                                settings_tab_data
+                                            },
+                                        ),
+                                    ),
+                                    ..MainView::default()
+                                })
                             }
-                            EditingPane::RepayingDebt(repay_debt) => {
-                                if let Ok(repayment) = repay_debt.repayment_value.parse()
-                                    as Result<f64, <f64 as FromStr>::Err>
-                                {
-                                    if repayment < 0.0 {
-                                        repay_debt.error_message =
-                                            "Repayment ammount cannot be negative".to_string()
-                                    } else {
-                                        let user = debts_tab_data
-                                            .user_details
-                                            .iter()
-                                            .find(|u| u.id == repay_debt.user_id)
-                                            .unwrap();
-
-                                        if user.loan + user.interest >= repayment {
-                                            let (loan_repayment, interest_repayment) =
-                                                if user.loan > repayment {
-                                                    (repayment, 0.0)
-                                                } else {
-                                                    (user.loan, repayment - user.loan)
-                                                };
-
-                                            match db_operations::repay_debt(
-                                                &self.db_connection,
-                                                repay_debt.user_id,
-                                                loan_repayment,
-                                                interest_repayment,
-                                            ) {
-                                                Ok(_) => {
-                                                    self.status = Status::LoggedIn(MainView {
-                                                        current_tab: Tab::Debts,
-                                                        tab_data: TabData::Debts(
-                                                            db_operations::debts_tab_data(
-                                                                &self.db_connection,
-                                                            ),
-                                                        ),
-                                                        ..MainView::default()
-                                                    })
-                                                }
-                                                Err(err) => {
-                                                    repay_debt.error_message = err.to_string()
-                                                }
-                                            }
-                                        } else {
-                                            repay_debt.error_message =
-                                                "Repayment is higher than debt".to_string()
-                                        }
-                                    }
-                                } else {
-                                    repay_debt.error_message = "Enter valid number".to_string()
-                                }
+                            Err(err) => {
+                                settings_tab_data.status_message = String::new();
+                                settings_tab_data.error_message = i18n::error(&err)
                             }
-                            _ => {}
-                        },
-                        _ => {}
-                    },
-                    _ => {}
-                },
-                _ => {}
-            },
-            Message::AddDebtButtonPressed(user_id) => match &mut self.status {
-                Status::LoggedIn(main_view) => match &mut main_view.tab_data {
-                    TabData::Debts(dtd_result) => match dtd_result {
-                        Ok(debts_tab_data) => {
-                            debts_tab_data.edit_pane = EditingPane::AddingDebt(AddDebt {
-                                user_id,
-                                ..Default::default()
-                            })
-                        }
-                        _ => {}
-                    },
-                    _ => {}
-                },
-                _ => {}
-            },
-            Message::RepayDebtButtonPressed(user_id) => match &mut self.status {
-                Status::LoggedIn(main_view) => match &mut main_view.tab_data {
-                    TabData::Debts(dtd_result) => match dtd_result {
-                        Ok(debts_tab_data) => {
-                            debts_tab_data.edit_pane = EditingPane::RepayingDebt(RepayDebt {
-                                user_id,
-                                ..Default::default()
-                            })
                         }
-                        _ => {}
-                    },
-                    _ => {}
-                },
+                    }
+                }
+            }
+            Message::RestoreBackupButtonPressed(index) => match &mut self.status {
+                Status::LoggedIn(main_view) => {
+                    if let TabData::Settings(Ok(settings_tab_data)) = &mut main_view.tab_data {
+                        settings_tab_data.pending_restore =
+                            settings_tab_data.backups.get(index).cloned()
+                    }
+                }
+                Status::Unreadable(recovery_view) => {
+                    recovery_view.pending_restore = recovery_view.backups.get(index).cloned()
+                }
                 _ => {}
             },
-            Message::EditPaneDebtInputChanged(value) => match &mut self.status {
-                Status::LoggedIn(main_view) => match &mut main_view.tab_data {
-                    TabData::Debts(dtd_result) => match dtd_result {
-                        Ok(debts_tab_data) => match &mut debts_tab_data.edit_pane {
-                            EditingPane::AddingDebt(add_debt) => add_debt.debt_value = value,
-                            _ => {}
-                        },
-                        _ => {}
-                    },
-                    _ => {}
-                },
+            Message::RestoreSnapshotButtonPressed(index) => match &mut self.status {
+                Status::LoggedIn(main_view) => {
+                    if let TabData::Settings(Ok(settings_tab_data)) = &mut main_view.tab_data {
+                        settings_tab_data.pending_restore =
+                            settings_tab_data.snapshots.get(index).cloned()
+                    }
+                }
+                Status::Unreadable(recovery_view) => {
+                    recovery_view.pending_restore = recovery_view.snapshots.get(index).cloned()
+                }
                 _ => {}
             },
-            Message::EditPaneInterestInputChanged(value) => match &mut self.status {
-                Status::LoggedIn(main_view) => match &mut main_view.tab_data {
-                    TabData::Debts(dtd_result) => match dtd_result {
-                        Ok(debts_tab_data) => match &mut debts_tab_data.edit_pane {
-                            EditingPane::AddingDebt(add_debt) => add_debt.interest_value = value,
-                            _ => {}
-                        },
-                        _ => {}
-                    },
-                    _ => {}
-                },
+            Message::CancelRestoreButtonPressed => match &mut self.status {
+                Status::LoggedIn(main_view) => {
+                    if let TabData::Settings(Ok(settings_tab_data)) = &mut main_view.tab_data {
+                        settings_tab_data.pending_restore = None
+                    }
+                }
+                Status::Unreadable(recovery_view) => recovery_view.pending_restore = None,
                 _ => {}
             },
-            Message::EditPaneRepaymentInputChanged(value) => match &mut self.status {
-                Status::LoggedIn(main_view) => match &mut main_view.tab_data {
-                    TabData::Debts(dtd_result) => match dtd_result {
-                        Ok(debts_tab_data) => match &mut debts_tab_data.edit_pane {
-                            EditingPane::RepayingDebt(repay_debt) => {
-                                repay_debt.repayment_value = value
+            Message::ConfirmRestoreButtonPressed => match &mut self.status {
+                Status::LoggedIn(main_view) => {
+                    if let TabData::Settings(Ok(settings_tab_data)) = &mut main_view.tab_data {
+                        if let Some(backup_file) = settings_tab_data.pending_restore.take() {
+                            let name = backup_file.name;
+
+                            match backup::restore_backup(&mut self.db_connection, &backup_file.path)
+                                .map_err(|err| i18n::error(&err))
+                                .and_then(|_| {
+                                    // The restored file has its own admin password
+                                    db_operations::get_password(&self.db_connection)
+                                        .map_err(|err| i18n::error(&err))
+                                }) {
+                                Ok(admin_password) => {
+                                    self.admin_password = admin_password;
+                                    self.forget_changes();
+                                    self.load_saved_settings();
+                                    self.status = Status::LoggedIn(MainView {
+                                        current_tab: Tab::Settings,
+                                        tab_data: TabData::Settings(
+                                            tab_data::settings_tab_data(&self.db_connection).map(
+                                                |mut settings_tab_data| {
+                                                    settings_tab_data.status_message =
+                                                        tr_with("settings.restored", &[&name]);
+                                                    settings_tab_data
+                                                },
+                                            ),
+                                        ),
+                                        ..MainView::default()
+                                    })
+                                }
+                                Err(err) => {
+                                    settings_tab_data.status_message = String::new();
+                                    settings_tab_data.error_message = err
+                                }
                             }
-                            _ => {}
-                        },
-                        _ => {}
-                    },
-                    _ => {}
-                },
+                        }
+                    }
+                }
+                Status::Unreadable(recovery_view) => {
+                    if let Some(backup_file) = recovery_view.pending_restore.take() {
+                        match backup::restore_backup(&mut self.db_connection, &backup_file.path)
+                            .and_then(|_| db_operations::get_password(&self.db_connection))
+                        {
+                            Ok(admin_password) => {
+                                self.admin_password = admin_password;
+                                self.forget_changes();
+                                self.load_saved_settings();
+                                // The restored books are logged in to with their own password
+                                self.status = Status::default();
+                                self.notify(
+                                    tr_with("settings.restored", &[&backup_file.name]),
+                                    None,
+                                )
+                            }
+                            Err(err) => recovery_view.error_message = i18n::error(&err),
+                        }
+                    }
+                }
                 _ => {}
             },
-            Message::CloseEditPane => match &mut self.status {
-                Status::LoggedIn(main_view) => match &mut main_view.tab_data {
-                    TabData::Users(utd_result) => match utd_result {
-                        Ok(utd) => utd.edit_pane = EditingPane::Closed,
-                        _ => {}
-                    },
-                    TabData::Debts(dtd_result) => match dtd_result {
-                        Ok(dtd) => dtd.edit_pane = EditingPane::Closed,
-                        _ => {}
-                    },
-                    _ => {}
-                },
+            Message::RetryOpeningBooksPressed => {
+                if let Status::Unreadable(recovery_view) = &mut self.status {
+                    match open_books(&self.db_connection) {
+                        Ok(admin_password) => {
+                            self.admin_password = admin_password;
+                            self.load_saved_settings();
+                            self.status = Status::default()
+                        }
+                        Err(err) => recovery_view.error = err,
+                    }
+                }
+            }
+            Message::GroupSelected(group) => {
+                if group != self.group {
+                    if let Err(err) = self.switch_group(group) {
+                        if let Status::NotLoggedIn(login_view_data) = &mut self.status {
+                            login_view_data.login_error_message = err
+                        }
+                    }
+                }
+            }
+            Message::NewGroupNameInputChanged(value) => {
+                if let Status::NotLoggedIn(login_view_data) = &mut self.status {
+                    login_view_data.new_group_name_value = value
+                }
+            }
+            Message::CreateGroupButtonPressed => {
+                if let Status::NotLoggedIn(login_view_data) = &mut self.status {
+                    match config::create_group(&login_view_data.new_group_name_value) {
+                        Ok(groups) => {
+                            self.groups = groups;
+
+                            if let Some(group) = self.groups.last().cloned() {
+                                if let Err(err) = self.switch_group(group) {
+                                    if let Status::NotLoggedIn(login_view_data) = &mut self.status {
+                                        login_view_data.login_error_message = err
+                                    }
+                                }
+                            }
+                        }
+                        Err(err) => login_view_data.login_error_message = i18n::error(&err),
+                    }
+                }
+            }
+            Message::SwitchGroupButtonPressed => {
+                self.notifications.clear();
+                self.status = Status::default()
+            }
+            Message::ThemeSelected(theme) => {
+                self.theme = theme;
+
+                if let Err(err) = db_operations::store_setting(
+                    &self.db_connection,
+                    style::THEME_SETTING,
+                    theme.as_setting(),
+                ) {
+                    if let Status::LoggedIn(main_view) = &mut self.status {
+                        if let TabData::Settings(Ok(settings_tab_data)) = &mut main_view.tab_data {
+                            settings_tab_data.error_message =
+                                tr_with("settings.theme_not_saved", &[&i18n::error(&err)])
+                        }
+                    }
+                }
+            }
+            Message::LanguageSelected(language) => {
+                self.language = language;
+                i18n::set_language(language);
+
+                if let Err(err) = db_operations::store_setting(
+                    &self.db_connection,
+                    i18n::LANGUAGE_SETTING,
+                    language.as_setting(),
+                ) {
+                    if let Status::LoggedIn(main_view) = &mut self.status {
+                        if let TabData::Settings(Ok(settings_tab_data)) = &mut main_view.tab_data {
+                            settings_tab_data.error_message =
+                                tr_with("settings.language_not_saved", &[&i18n::error(&err)])
+                        }
+                    }
+                }
+            }
+            Message::AllocationRuleSelected(rule) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Settings(Ok(settings_tab_data)) = &mut main_view.tab_data {
+                        match db_operations::store_allocation_rule(&self.db_connection, rule) {
+                            Ok(_) => settings_tab_data.allocation_rule = rule,
+                            Err(err) => {
+                                settings_tab_data.error_message =
+                                    tr_with("settings.allocation_not_saved", &[&i18n::error(&err)])
+                            }
+                        }
+                    }
+                }
+            }
+            Message::CurrencySymbolInputChanged(value) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Settings(Ok(settings_tab_data)) = &mut main_view.tab_data {
+                        settings_tab_data.currency.symbol_value = value
+                    }
+                }
+            }
+            Message::CurrencyPositionSelected(position) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Settings(Ok(settings_tab_data)) = &mut main_view.tab_data {
+                        settings_tab_data.currency.position = position
+                    }
+                }
+            }
+            Message::CurrencyDecimalsInputChanged(value) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Settings(Ok(settings_tab_data)) = &mut main_view.tab_data {
+                        settings_tab_data.currency.decimals_value = value
+                    }
+                }
+            }
+            Message::CurrencyGroupingInputChanged(value) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Settings(Ok(settings_tab_data)) = &mut main_view.tab_data {
+                        settings_tab_data.currency.grouping_value = value
+                    }
+                }
+            }
+            Message::CurrencyDecimalSeparatorInputChanged(value) => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Settings(Ok(settings_tab_data)) = &mut main_view.tab_data {
+                        settings_tab_data.currency.decimal_separator_value = value
+                    }
+                }
+            }
+            Message::SaveCurrencyButtonPressed => {
+                if let Status::LoggedIn(main_view) = &mut self.status {
+                    if let TabData::Settings(Ok(settings_tab_data)) = &mut main_view.tab_data {
+                        match settings_tab_data
+                            .currency
+                            .entered_currency()
+                            .and_then(|currency| {
+                                db_operations::store_currency(&self.db_connection, &currency)
+                                    .map(|_| currency)
+                                    .map_err(|err| i18n::error(&err))
+                            }) {
+                            Ok(currency) => {
+                                self.currency = currency;
+                                settings_tab_data.error_message = String::new();
+                                settings_tab_data.status_message = tr("settings.currency_saved")
+                            }
+                            Err(err) => {
+                                settings_tab_data.status_message = String::new();
+                                settings_tab_data.error_message = err
+                            }
+                        }
+                    }
+                }
+            }
+            Message::UndoButtonPressed => self.reverse_last_change(false),
+            Message::RedoButtonPressed => self.reverse_last_change(true),
+            Message::FocusNextInput => keyboard::move_focus(self.text_inputs_mut(), false),
+            Message::FocusPreviousInput => keyboard::move_focus(self.text_inputs_mut(), true),
+            Message::SearchShortcutPressed => match &mut self.status {
+                // The search comes first on every tab with a table
+                Status::LoggedIn(main_view) if main_view.tab_data.member_table().is_some() => {
+                    keyboard::focus(main_view.tab_data.text_inputs_mut(), 0)
+                }
                 _ => {}
             },
-        };
+            Message::EnterPressed => {
+                if let Some(message) = self.enter_message() {
+                    self.handle(message)
+                }
+            }
+            Message::NotificationTick(now) => self
+                .notifications
+                .retain(|notification| !notification.has_expired(now)),
+            Message::DismissNotification(i) => {
+                if i < self.notifications.len() {
+                    self.notifications.remove(i);
+                }
+            }
+            Message::NotificationLinkPressed(i) => {
+                if let Some(link) = self
+                    .notifications
+                    .get(i)
+                    .and_then(|notification| notification.link)
+                {
+                    self.notifications.remove(i);
+
+                    if let Status::LoggedIn(_) = &self.status {
+                        self.show_tab(link.tab)
+                    }
+                    if let Status::LoggedIn(main_view) = &mut self.status {
+                        main_view.tab_data.highlight_record(link.record)
+                    }
+                }
+            }
+            Message::WindowCloseRequested => {
+                // Nothing worth keeping until a password has been set
+                if self.admin_password.is_some() && !self.exit_snapshot_failed {
+                    if let Err(err) = backup::create_snapshot(&self.db_connection, "exit") {
+                        // The window stays open so the failure is seen
+                        self.exit_snapshot_failed = true;
+                        self.notify(
+                            tr_with("notice.exit_snapshot_failed", &[&i18n::error(&err)]),
+                            None,
+                        );
+                        return;
+                    }
+                }
+
+                self.exiting = true
+            }
+        }
+    }
+}
+
+impl Application for FamilyBanking {
+    type Message = Message;
+
+    type Executor = iced::executor::Default;
+
+    type Flags = Flags;
+
+    fn new(flags: Self::Flags) -> (Self, iced::Command<Message>) {
+        (FamilyBanking::from_flags(flags), iced::Command::none())
+    }
+
+    fn title(&self) -> String {
+        format!("Family Banking - {}", self.group.name)
+    }
+
+    fn update(
+        &mut self,
+        message: Message,
+        _clipboard: &mut iced::Clipboard,
+    ) -> iced::Command<Message> {
+        self.handle(message);
         iced::Command::none()
     }
 
-    fn view<'a>(&mut self) -> Element<Message> {
-        Column::new()
-            .width(iced::Length::Units(WINDOW_WIDTH))
-            .height(iced::Length::Units(WINDOW_HEIGHT))
+    fn view(&mut self) -> Element<'_, Message> {
+        style::set_theme(self.theme);
+        i18n::set_language(self.language);
+
+        let content = Column::new()
+            .width(iced::Length::Fill)
+            .height(iced::Length::Fill)
             .align_items(iced::Align::Center)
             .push(match &mut self.status {
-                Status::NotLoggedIn(login_view_data) => match &self.admin_password {
-                    None => render_new_password_view(login_view_data),
-                    Some(_) => render_login_view(login_view_data),
-                },
+                // Shown with the notifications, such as that of a backup
+                // restored from the recovery screen
+                Status::NotLoggedIn(login_view_data) => Column::new()
+                    .width(iced::Length::Fill)
+                    .align_items(iced::Align::Center)
+                    .push(notifications::render_notifications(&mut self.notifications))
+                    .push(match &self.admin_password {
+                        None => {
+                            render_new_password_view(login_view_data, &self.groups, &self.group)
+                        }
+                        Some(_) => render_login_view(login_view_data, &self.groups, &self.group),
+                    }),
+                Status::Unreadable(recovery_view) => render_recovery_view(recovery_view),
                 Status::LoggedIn(main_view_data) => Column::new().width(iced::Length::Fill).push(
                     Row::new()
                         .width(iced::Length::Fill)
-                        .push(render_tab_buttons(
-                            &mut main_view_data.home_button,
-                            &mut main_view_data.users_button,
-                            &mut main_view_data.debts_button,
-                            main_view_data.current_tab,
-                        ))
-                        .push(main_view::render_main_view(&mut main_view_data.tab_data)),
+                        .push(
+                            Column::new()
+                                .push(render_tab_buttons(
+                                    &mut main_view_data.tab_buttons,
+                                    main_view_data.current_tab,
+                                ))
+                                .push(render_undo_buttons(
+                                    &mut main_view_data.undo_button,
+                                    &mut main_view_data.redo_button,
+                                    !self.undo_stack.is_empty(),
+                                    !self.redo_stack.is_empty(),
+                                    &main_view_data.undo_error,
+                                )),
+                        )
+                        .push(
+                            Column::new()
+                                .width(iced::Length::Fill)
+                                .push(notifications::render_notifications(&mut self.notifications))
+                                .push(main_view::render_main_view(
+                                    &mut main_view_data.tab_data,
+                                    main_view_data.current_tab,
+                                    &mut main_view_data.retry_button,
+                                    &mut main_view_data.restore_backup_button,
+                                    &self.member_query,
+                                    &self.currency,
+                                    self.theme,
+                                )),
+                        ),
                 ),
-            })
+            });
+
+        Container::new(content)
+            .width(iced::Length::Fill)
+            .height(iced::Length::Fill)
+            .style(style::Window)
             .into()
     }
 
     fn background_color(&self) -> Color {
-        Color::WHITE
+        self.theme.palette().background
     }
 
     fn scale_factor(&self) -> f64 {
@@ -607,7 +1716,18 @@ impl Application for FamilyBanking {
     }
 
     fn subscription(&self) -> iced::Subscription<Self::Message> {
-        iced::Subscription::none()
+        let events = iced_native::subscription::events_with(|event, status| match event {
+            iced_native::Event::Window(iced_native::window::Event::CloseRequested) => {
+                Some(Message::WindowCloseRequested)
+            }
+            event => keyboard::shortcut(event, status),
+        });
+
+        if self.notifications.is_empty() {
+            events
+        } else {
+            iced::Subscription::batch(vec![events, notifications::ticks()])
+        }
     }
 
     fn mode(&self) -> iced::window::Mode {
@@ -615,6 +1735,6 @@ impl Application for FamilyBanking {
     }
 
     fn should_exit(&self) -> bool {
-        false
+        self.exiting
     }
 }
@@ -0,0 +1,156 @@
+//! The text of the window in the language chosen in the settings.
+//! Each language has a catalog of `key = text` lines, bundled with
+//! the app. A key missing from a catalog is shown in English
+
+use std::{cell::Cell, collections::HashMap, fmt};
+
+use family_banking_core::{error::Rule, Error};
+
+/// The key the chosen language is saved under in the settings
+pub const LANGUAGE_SETTING: &str = "language";
+
+const ENGLISH_CATALOG: &str = include_str!("../locales/en.catalog");
+const CHICHEWA_CATALOG: &str = include_str!("../locales/ny.catalog");
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Language {
+    #[default]
+    English,
+    Chichewa,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Chichewa];
+
+    /// The code the language is saved as
+    pub fn as_setting(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Chichewa => "ny",
+        }
+    }
+
+    /// The language saved as `setting`, or English if it is not known
+    pub fn from_setting(setting: &str) -> Language {
+        Language::ALL
+            .iter()
+            .copied()
+            .find(|language| language.as_setting() == setting)
+            .unwrap_or_default()
+    }
+
+    fn catalog(self) -> &'static str {
+        match self {
+            Language::English => ENGLISH_CATALOG,
+            Language::Chichewa => CHICHEWA_CATALOG,
+        }
+    }
+}
+
+/// Each language is named in itself, so it can be found
+/// by someone who cannot read the one in use
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Language::English => "English",
+            Language::Chichewa => "Chichewa",
+        })
+    }
+}
+
+/// The keys and texts of a catalog. Blank lines and
+/// lines starting with `#` are left out
+fn parse_catalog(catalog: &'static str) -> HashMap<&'static str, &'static str> {
+    catalog
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, text)| (key.trim(), text.trim()))
+        .collect()
+}
+
+thread_local! {
+    static ACTIVE_LANGUAGE: Cell<Language> = Cell::new(Language::default());
+    static CATALOGS: HashMap<Language, HashMap<&'static str, &'static str>> = Language::ALL
+        .iter()
+        .map(|language| (*language, parse_catalog(language.catalog())))
+        .collect();
+}
+
+/// Makes `language` the one `tr` looks text up in
+pub fn set_language(language: Language) {
+    ACTIVE_LANGUAGE.with(|active| active.set(language));
+}
+
+pub fn language() -> Language {
+    ACTIVE_LANGUAGE.with(|active| active.get())
+}
+
+/// The text of `key` in the language in use, in English if it has
+/// not been translated, or the key itself if it is in no catalog
+pub fn tr(key: &str) -> String {
+    CATALOGS.with(|catalogs| {
+        [language(), Language::English]
+            .iter()
+            .find_map(|language| catalogs[language].get(key))
+            .map_or_else(|| key.to_string(), |text| text.to_string())
+    })
+}
+
+/// The text of `key` with each `{}` in it replaced by the next of `args`
+pub fn tr_with(key: &str, args: &[&dyn fmt::Display]) -> String {
+    let text = tr(key);
+    let mut parts = text.split("{}");
+    let mut result = parts.next().unwrap_or_default().to_string();
+
+    for (i, part) in parts.enumerate() {
+        if let Some(arg) = args.get(i) {
+            result.push_str(&arg.to_string());
+        }
+        result.push_str(part);
+    }
+
+    result
+}
+
+/// The keys of the English catalog that `language` has no text for
+pub fn untranslated(language: Language) -> Vec<&'static str> {
+    CATALOGS.with(|catalogs| {
+        let mut keys: Vec<&'static str> = catalogs[&Language::English]
+            .keys()
+            .filter(|key| !catalogs[&language].contains_key(*key))
+            .copied()
+            .collect();
+
+        keys.sort_unstable();
+        keys
+    })
+}
+
+/// `err` as it is shown in the window
+pub fn error(err: &Error) -> String {
+    match err {
+        Error::Validation(rule) | Error::Usage(rule) => rule_message(rule),
+        Error::Io(err) => tr_with("error.io", &[err]),
+        Error::Schema(reason) => tr_with("error.schema", &[reason]),
+        Error::NotFound(what) => tr_with("error.not_found", &[what]),
+        Error::Locked => tr("error.locked"),
+        Error::Database(err) => tr_with("error.database", &[err]),
+        Error::Json(err) => tr_with("error.json", &[err]),
+        Error::UnsupportedFormat(format, version) => {
+            tr_with("error.unsupported_format", &[format, version])
+        }
+        Error::InvalidDocument(reason) => tr_with("error.invalid_document", &[reason]),
+        Error::InvalidBackup(reason) => tr_with("error.invalid_backup", &[reason]),
+    }
+}
+
+/// The message of `rule` from the catalog, found by its code
+fn rule_message(rule: &Rule) -> String {
+    let arguments = rule.arguments();
+    let arguments: Vec<&dyn fmt::Display> =
+        arguments.iter().map(|a| a as &dyn fmt::Display).collect();
+
+    tr_with(&format!("error.{}", rule.code()), &arguments)
+}
@@ -0,0 +1,80 @@
+//! Keys that do what would otherwise need the mouse: Tab and
+//! Shift+Tab move between inputs, Enter confirms, Escape closes
+//! the edit pane, Ctrl+F searches and Ctrl+1 to Ctrl+6 switch tabs
+
+use iced::text_input;
+use iced_native::{
+    event,
+    keyboard::{self, KeyCode},
+    Event,
+};
+
+use crate::{family_banking::Message, main_view::Tab};
+
+/// The tabs of the sidebar, in the order their number keys pick them
+const TAB_KEYS: [(KeyCode, Tab); 6] = [
+    (KeyCode::Key1, Tab::Home),
+    (KeyCode::Key2, Tab::Users),
+    (KeyCode::Key3, Tab::Debts),
+    (KeyCode::Key4, Tab::Expenses),
+    (KeyCode::Key5, Tab::Reconciliation),
+    (KeyCode::Key6, Tab::Settings),
+];
+
+/// The message for a key pressed anywhere in the window. A focused
+/// input captures every key, so Enter is only taken from here when
+/// no input has used it to submit itself
+pub fn shortcut(event: Event, status: event::Status) -> Option<Message> {
+    match event {
+        Event::Keyboard(keyboard::Event::KeyPressed {
+            key_code,
+            modifiers,
+        }) => match key_code {
+            KeyCode::Tab if modifiers.shift => Some(Message::FocusPreviousInput),
+            KeyCode::Tab => Some(Message::FocusNextInput),
+            KeyCode::Escape => Some(Message::CloseEditPane),
+            KeyCode::Enter | KeyCode::NumpadEnter if status == event::Status::Ignored => {
+                Some(Message::EnterPressed)
+            }
+            KeyCode::F if modifiers.is_command_pressed() => Some(Message::SearchShortcutPressed),
+            _ if modifiers.is_command_pressed() => TAB_KEYS
+                .iter()
+                .find(|(key, _)| *key == key_code)
+                .map(|(_, tab)| Message::TabButtonPressed(*tab)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Focuses `input` alone of `inputs`, with the cursor after its text
+pub fn focus(inputs: Vec<&mut text_input::State>, input: usize) {
+    for (i, state) in inputs.into_iter().enumerate() {
+        if i == input {
+            state.focus();
+            state.move_cursor_to_end();
+        } else {
+            state.unfocus();
+        }
+    }
+}
+
+/// Moves the focus to the input after the focused one, or the one
+/// before it if `backwards`, going round from the last to the first.
+/// If none is focused, the first or the last is
+pub fn move_focus(inputs: Vec<&mut text_input::State>, backwards: bool) {
+    let count = inputs.len();
+
+    if count == 0 {
+        return;
+    }
+
+    let next = match inputs.iter().position(|input| input.is_focused()) {
+        Some(i) if backwards => (i + count - 1) % count,
+        Some(i) => (i + 1) % count,
+        None if backwards => count - 1,
+        None => 0,
+    };
+
+    focus(inputs, next);
+}
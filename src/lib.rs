@@ -0,0 +1,24 @@
+pub mod family_banking;
+pub mod i18n;
+pub mod keyboard;
+pub mod login_view;
+pub mod main_view;
+pub mod notifications;
+pub mod recovery_view;
+pub mod style;
+pub mod tab_data;
+
+use family_banking_core::format_decimal;
+
+/// The size the window opens at
+pub const WINDOW_WIDTH: u16 = 1100;
+pub const WINDOW_HEIGHT: u16 = 600;
+/// The smallest the window can be made. Below this
+/// a tab and its edit pane no longer fit side by side
+pub const MIN_WINDOW_WIDTH: u16 = 900;
+pub const MIN_WINDOW_HEIGHT: u16 = 500;
+const SIDEBAR_WIDTH: u16 = 200;
+/// How the space beside the sidebar is shared
+/// between a tab and its open edit pane
+const TAB_PORTION: u16 = 2;
+const EDIT_PANE_PORTION: u16 = 1;
@@ -1,12 +1,16 @@
-use iced::{button, text_input, Button, Column, Container, Text, TextInput};
+use iced::{
+    button, pick_list, text_input, Button, Column, Container, PickList, Row, Space, Text, TextInput,
+};
+
+use family_banking_core::config::Group;
 
 use crate::{
     family_banking::Message,
-    style::{self, ACCENT_COLOR, OPEN_SANS, OPEN_SANS_BOLD},
-    WINDOW_HEIGHT,
+    i18n::tr,
+    style::{self, OPEN_SANS, OPEN_SANS_BOLD},
 };
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct LoginView {
     pub password_input_1_state: text_input::State,
     pub password_input_2_state: text_input::State,
@@ -14,28 +18,142 @@ pub struct LoginView {
     pub password_input_2_value: String,
     pub login_button_state: button::State,
     pub login_error_message: String,
+    pub group_list: pick_list::State<Group>,
+    pub new_group_name_value: String,
+    pub new_group_name_input: text_input::State,
+    pub create_group_button: button::State,
+}
+
+impl LoginView {
+    /// The text inputs in the order they are shown. The second
+    /// password is only asked for when `creating_password`
+    pub fn text_inputs_mut(&mut self, creating_password: bool) -> Vec<&mut text_input::State> {
+        let mut inputs = vec![
+            &mut self.new_group_name_input,
+            &mut self.password_input_1_state,
+        ];
+
+        if creating_password {
+            inputs.push(&mut self.password_input_2_state);
+        }
+
+        inputs
+    }
+}
+
+/// The password starts focused, so it can be typed straight away
+impl Default for LoginView {
+    fn default() -> Self {
+        LoginView {
+            password_input_1_state: text_input::State::focused(),
+            password_input_2_state: text_input::State::new(),
+            password_input_1_value: String::new(),
+            password_input_2_value: String::new(),
+            login_button_state: button::State::new(),
+            login_error_message: String::new(),
+            group_list: pick_list::State::default(),
+            new_group_name_value: String::new(),
+            new_group_name_input: text_input::State::new(),
+            create_group_button: button::State::new(),
+        }
+    }
+}
+
+/// Picks which group to log in to, or creates a new one
+fn render_group_picker<'a>(
+    group_list: &'a mut pick_list::State<Group>,
+    new_group_name_input: &'a mut text_input::State,
+    new_group_name_value: &str,
+    create_group_button: &'a mut button::State,
+    groups: &'a [Group],
+    active_group: &Group,
+) -> Column<'a, Message> {
+    Column::new()
+        .spacing(10)
+        .push(
+            Row::new()
+                .spacing(10)
+                .align_items(iced::Align::Center)
+                .push(
+                    Text::new(tr("login.group"))
+                        .width(iced::Length::Units(80))
+                        .font(OPEN_SANS),
+                )
+                .push(
+                    PickList::new(
+                        group_list,
+                        groups,
+                        Some(active_group.clone()),
+                        Message::GroupSelected,
+                    )
+                    .width(iced::Length::Units(300))
+                    .padding(10)
+                    .font(OPEN_SANS),
+                ),
+        )
+        .push(
+            Row::new()
+                .spacing(10)
+                .align_items(iced::Align::Center)
+                .push(Text::new("").width(iced::Length::Units(80)))
+                .push(
+                    TextInput::new(
+                        new_group_name_input,
+                        &tr("login.new_group_name"),
+                        new_group_name_value,
+                        Message::NewGroupNameInputChanged,
+                    )
+                    .on_submit(Message::CreateGroupButtonPressed)
+                    .width(iced::Length::Units(300))
+                    .font(OPEN_SANS)
+                    .padding(10),
+                )
+                .push(
+                    Button::new(
+                        create_group_button,
+                        Text::new(tr("login.create_group")).font(OPEN_SANS),
+                    )
+                    .style(style::Button::Confirm)
+                    .padding(10)
+                    .on_press(Message::CreateGroupButtonPressed),
+                ),
+        )
 }
 
-pub fn render_login_view<'a>(login_view_data: &'a mut LoginView) -> Column<'a, Message> {
+pub fn render_login_view<'a>(
+    login_view_data: &'a mut LoginView,
+    groups: &'a [Group],
+    active_group: &Group,
+) -> Column<'a, Message> {
     Column::new()
         .width(iced::Length::Units(700))
-        .height(iced::Length::Units(WINDOW_HEIGHT))
-        .padding(150)
+        .height(iced::Length::Fill)
+        .padding(40)
         .align_items(iced::Align::Center)
+        .push(render_group_picker(
+            &mut login_view_data.group_list,
+            &mut login_view_data.new_group_name_input,
+            &login_view_data.new_group_name_value,
+            &mut login_view_data.create_group_button,
+            groups,
+            active_group,
+        ))
+        .push(Space::with_height(iced::Length::Units(40)))
         .push(
-            Text::new("Login")
+            Text::new(tr("login.title"))
                 .font(OPEN_SANS_BOLD)
                 .size(40)
-                .color(ACCENT_COLOR),
+                .color(style::palette().accent),
         )
         .push(
             Container::new(
                 TextInput::new(
                     &mut login_view_data.password_input_1_state,
-                    "Password",
+                    &tr("login.password"),
                     login_view_data.password_input_1_value.as_str(),
                     Message::PasswordInput1Changed,
                 )
+                .on_submit(Message::LoginButtonPressed)
                 .width(iced::Length::Units(300))
                 .font(OPEN_SANS)
                 .padding(10)
@@ -46,12 +164,12 @@ pub fn render_login_view<'a>(login_view_data: &'a mut LoginView) -> Column<'a, M
         .push(
             Text::new(login_view_data.login_error_message.clone())
                 .font(OPEN_SANS)
-                .color(style::RED),
+                .color(style::palette().danger),
         )
         .push(
             Button::new(
                 &mut login_view_data.login_button_state,
-                Text::new("Login").font(OPEN_SANS),
+                Text::new(tr("login.log_in")).font(OPEN_SANS),
             )
             .style(style::Button::Confirm)
             .padding(10)
@@ -59,17 +177,30 @@ pub fn render_login_view<'a>(login_view_data: &'a mut LoginView) -> Column<'a, M
         )
 }
 
-pub fn render_new_password_view<'a>(login_view_data: &'a mut LoginView) -> Column<'a, Message> {
+pub fn render_new_password_view<'a>(
+    login_view_data: &'a mut LoginView,
+    groups: &'a [Group],
+    active_group: &Group,
+) -> Column<'a, Message> {
     Column::new()
         .width(iced::Length::Units(700))
-        .height(iced::Length::Units(WINDOW_HEIGHT))
-        .padding(150)
+        .height(iced::Length::Fill)
+        .padding(40)
         .align_items(iced::Align::Center)
+        .push(render_group_picker(
+            &mut login_view_data.group_list,
+            &mut login_view_data.new_group_name_input,
+            &login_view_data.new_group_name_value,
+            &mut login_view_data.create_group_button,
+            groups,
+            active_group,
+        ))
+        .push(Space::with_height(iced::Length::Units(40)))
         .push(
-            Text::new("Create Password")
+            Text::new(tr("login.create_password"))
                 .font(OPEN_SANS_BOLD)
                 .size(40)
-                .color(ACCENT_COLOR),
+                .color(style::palette().accent),
         )
         .push(
             Column::new()
@@ -77,10 +208,11 @@ pub fn render_new_password_view<'a>(login_view_data: &'a mut LoginView) -> Colum
                 .push(
                     TextInput::new(
                         &mut login_view_data.password_input_1_state,
-                        "New password",
+                        &tr("login.new_password"),
                         login_view_data.password_input_1_value.as_str(),
                         Message::PasswordInput1Changed,
                     )
+                    .on_submit(Message::NewPasswordButtonPressed)
                     .width(iced::Length::Units(300))
                     .font(OPEN_SANS)
                     .padding(10)
@@ -89,10 +221,11 @@ pub fn render_new_password_view<'a>(login_view_data: &'a mut LoginView) -> Colum
                 .push(
                     TextInput::new(
                         &mut login_view_data.password_input_2_state,
-                        "Confirm password",
+                        &tr("login.confirm_password"),
                         login_view_data.password_input_2_value.as_str(),
                         Message::PasswordInput2Changed,
                     )
+                    .on_submit(Message::NewPasswordButtonPressed)
                     .width(iced::Length::Units(300))
                     .font(OPEN_SANS)
                     .padding(10)
@@ -102,12 +235,12 @@ pub fn render_new_password_view<'a>(login_view_data: &'a mut LoginView) -> Colum
         .push(
             Text::new(login_view_data.login_error_message.clone())
                 .font(OPEN_SANS)
-                .color(iced::Color::from_rgb(0.7, 0.0, 0.0)),
+                .color(style::palette().danger),
         )
         .push(
             Button::new(
                 &mut login_view_data.login_button_state,
-                Text::new("Confirm").font(OPEN_SANS),
+                Text::new(tr("common.confirm")).font(OPEN_SANS),
             )
             .style(style::Button::Confirm)
             .padding(10)
@@ -1,59 +1,39 @@
-extern crate argon2;
-
-use family_banking::{FamilyBanking, Flags};
+use family_banking::{
+    family_banking::{FamilyBanking, Flags},
+    MIN_WINDOW_HEIGHT, MIN_WINDOW_WIDTH, WINDOW_HEIGHT, WINDOW_WIDTH,
+};
+use family_banking_core::{config, Error};
 use iced::{window, Application, Settings};
-use rusqlite;
-mod db_operations;
-mod family_banking;
-mod login_view;
-mod main_view;
-mod style;
-
-const HASH_SALT: &[u8; 17] = b"5aP3v*4!1bN<x4i&3";
-
-const WINDOW_WIDTH: u16 = 1100;
-const WINDOW_HEIGHT: u16 = 600;
-const SIDEBAR_WIDTH: u16 = 200;
-const EDIT_PANE_WIDTH: u16 = 300;
 
 fn main() -> Result<(), iced::Error> {
-    let conn = rusqlite::Connection::open("./data.store").unwrap();
-
-    let password = db_operations::get_password(&conn);
+    let flags = match config::parse_args(std::env::args().skip(1)) {
+        Ok(args) if args.help => {
+            println!("{}", config::USAGE);
+            return Ok(());
+        }
+        Ok(args) => Flags::open(&args),
+        Err(err) => Err(err),
+    }
+    .unwrap_or_else(|err| match err {
+        Error::Usage(_) => {
+            eprintln!("{}\n\n{}", err, config::USAGE);
+            std::process::exit(2)
+        }
+        _ => {
+            eprintln!("{}", err);
+            std::process::exit(1)
+        }
+    });
 
     FamilyBanking::run(Settings {
-        flags: Flags {
-            admin_password: password,
-            db_connection: conn,
-        },
         window: window::Settings {
             size: (WINDOW_WIDTH.into(), WINDOW_HEIGHT.into()),
-            resizable: false,
+            min_size: Some((MIN_WINDOW_WIDTH.into(), MIN_WINDOW_HEIGHT.into())),
+            resizable: true,
             ..window::Settings::default()
         },
-        ..Settings::default()
+        // Closing is handled in the app so a snapshot can be taken first
+        exit_on_close_request: false,
+        ..Settings::with_flags(flags)
     })
 }
-
-/// Converts number to String and shortens it to 2 decimal
-/// places, and adds commas for every thousands
-fn format_decimal(number: f64) -> String {
-    let mut string = number.to_string();
-
-    let position_of_decimal = string.find('.');
-
-    let mut i: i64 = match position_of_decimal {
-        Some(pos) => {
-            string.truncate(pos + 3);
-            pos as i64
-        }
-        None => string.len() as i64,
-    } - 3;
-
-    while i > 0 {
-        string.insert(i as usize, ',');
-        i -= 3;
-    }
-
-    string
-}
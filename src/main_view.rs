@@ -1,35 +1,148 @@
-use iced::{button, Button, Column, Container, Text};
+use family_banking_core::{
+    model::{Currency, MemberQuery},
+    Error, Result,
+};
+use iced::{button, text_input, Button, Column, Container, Row, Space, Text};
 
 use crate::{
     family_banking::Message,
-    style::{self, OPEN_SANS},
-    EDIT_PANE_WIDTH, SIDEBAR_WIDTH, WINDOW_HEIGHT, WINDOW_WIDTH,
+    i18n::{self, tr},
+    style::{self, Theme, OPEN_SANS},
+    EDIT_PANE_PORTION, SIDEBAR_WIDTH,
 };
 
-use self::home_tab::HomeTabData;
+use self::{home_tab::HomeTabData, table::MemberTable};
 
+pub mod charts;
 pub mod debts_tab;
+pub mod expenses_tab;
 pub mod home_tab;
+pub mod member_tab;
+pub mod reconciliation_tab;
+pub mod settings_tab;
+pub mod table;
 pub mod users_tab;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum Tab {
+    #[default]
     Home,
     Users,
     Debts,
+    Expenses,
+    Reconciliation,
+    Settings,
+    /// The page of one member
+    Member(i32),
 }
 
-impl Default for Tab {
-    fn default() -> Tab {
-        Tab::Home
-    }
-}
-
+// Only the open tab has data, so its size does not matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 pub enum TabData {
-    Home(rusqlite::Result<HomeTabData>),
-    Users(rusqlite::Result<users_tab::UsersTabData>),
-    Debts(rusqlite::Result<debts_tab::DebtsTabData>),
+    Home(Result<HomeTabData>),
+    Users(Result<users_tab::UsersTabData>),
+    Debts(Result<debts_tab::DebtsTabData>),
+    Expenses(Result<expenses_tab::ExpensesTabData>),
+    Reconciliation(Result<reconciliation_tab::ReconciliationTabData>),
+    Settings(Result<settings_tab::SettingsTabData>),
+    Member(Result<member_tab::MemberTabData>),
+}
+
+impl TabData {
+    /// The edit pane of the tab, if it has one and it has loaded
+    pub fn edit_pane(&self) -> Option<&EditingPane> {
+        match self {
+            TabData::Users(Ok(tab_data)) => Some(&tab_data.edit_pane),
+            TabData::Debts(Ok(tab_data)) => Some(&tab_data.edit_pane),
+            TabData::Expenses(Ok(tab_data)) => Some(&tab_data.edit_pane),
+            TabData::Member(Ok(tab_data)) => Some(&tab_data.edit_pane),
+            _ => None,
+        }
+    }
+
+    /// The edit pane of the tab, if it has one and it has loaded
+    pub fn edit_pane_mut(&mut self) -> Option<&mut EditingPane> {
+        match self {
+            TabData::Users(Ok(tab_data)) => Some(&mut tab_data.edit_pane),
+            TabData::Debts(Ok(tab_data)) => Some(&mut tab_data.edit_pane),
+            TabData::Expenses(Ok(tab_data)) => Some(&mut tab_data.edit_pane),
+            TabData::Member(Ok(tab_data)) => Some(&mut tab_data.edit_pane),
+            _ => None,
+        }
+    }
+
+    /// Highlights the row of ledger record `record`, on
+    /// the tabs that list records one to a row
+    pub fn highlight_record(&mut self, record: Option<i64>) {
+        match self {
+            TabData::Expenses(Ok(tab_data)) => tab_data.highlighted_record = record,
+            TabData::Member(Ok(tab_data)) => tab_data.highlighted_record = record,
+            _ => {}
+        }
+    }
+
+    /// The member table of the tab, if it has one on show
+    pub fn member_table(&self) -> Option<&MemberTable> {
+        match self {
+            TabData::Home(Ok(tab_data)) if tab_data.shows_table() => Some(&tab_data.table),
+            TabData::Users(Ok(tab_data)) => Some(&tab_data.table),
+            TabData::Debts(Ok(tab_data)) => Some(&tab_data.table),
+            _ => None,
+        }
+    }
+
+    /// The text inputs of the tab in the order they are
+    /// shown, with those of an open edit pane last
+    pub fn text_inputs_mut(&mut self) -> Vec<&mut text_input::State> {
+        let (mut inputs, edit_pane) = match self {
+            TabData::Home(Ok(tab_data)) if tab_data.shows_table() => {
+                (vec![&mut tab_data.table.search_input], None)
+            }
+            TabData::Users(Ok(tab_data)) => (
+                vec![&mut tab_data.table.search_input],
+                Some(&mut tab_data.edit_pane),
+            ),
+            TabData::Debts(Ok(tab_data)) => (
+                vec![&mut tab_data.table.search_input],
+                Some(&mut tab_data.edit_pane),
+            ),
+            TabData::Expenses(Ok(tab_data)) => (Vec::new(), Some(&mut tab_data.edit_pane)),
+            TabData::Reconciliation(Ok(tab_data)) => (
+                vec![
+                    &mut tab_data.counted_cash_input,
+                    &mut tab_data.bank_balance_input,
+                    &mut tab_data.reason_input,
+                ],
+                None,
+            ),
+            TabData::Settings(Ok(tab_data)) => (
+                vec![
+                    &mut tab_data.currency.symbol_input,
+                    &mut tab_data.currency.decimals_input,
+                    &mut tab_data.currency.grouping_input,
+                    &mut tab_data.currency.decimal_separator_input,
+                    &mut tab_data.export_path_input,
+                    &mut tab_data.import_path_input,
+                    &mut tab_data.backup_folder_input,
+                    &mut tab_data.backup_retention_input,
+                    &mut tab_data.snapshot_retention_input,
+                ],
+                None,
+            ),
+            TabData::Member(Ok(tab_data)) => (
+                vec![&mut tab_data.phone_input, &mut tab_data.notes_input],
+                Some(&mut tab_data.edit_pane),
+            ),
+            _ => (Vec::new(), None),
+        };
+
+        if let Some(edit_pane) = edit_pane {
+            inputs.extend(edit_pane.text_inputs_mut());
+        }
+
+        inputs
+    }
 }
 
 impl Default for TabData {
@@ -38,54 +151,134 @@ impl Default for TabData {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub enum EditingPane {
+    #[default]
     Closed,
     AddingUser(users_tab::EditUserDetails),
     EditingUser(i32, users_tab::EditUserDetails),
     ConfirmingDeletion(users_tab::ConfirmDeletion),
     AddingDebt(debts_tab::AddDebt),
     RepayingDebt(debts_tab::RepayDebt),
+    AddingEntry(expenses_tab::AddEntry),
 }
 
-impl Default for EditingPane {
-    fn default() -> Self {
-        Self::Closed
+impl EditingPane {
+    /// The text inputs of the pane in the order they are shown
+    pub fn text_inputs_mut(&mut self) -> Vec<&mut text_input::State> {
+        match self {
+            EditingPane::AddingUser(details) | EditingPane::EditingUser(_, details) => {
+                vec![&mut details.name_input, &mut details.shares_input]
+            }
+            EditingPane::AddingDebt(add_debt) => {
+                vec![&mut add_debt.debt_input, &mut add_debt.interest_input]
+            }
+            EditingPane::RepayingDebt(repay_debt) => vec![&mut repay_debt.repayment_input],
+            EditingPane::AddingEntry(add_entry) => {
+                vec![
+                    &mut add_entry.description_input,
+                    &mut add_entry.amount_input,
+                ]
+            }
+            EditingPane::Closed | EditingPane::ConfirmingDeletion(_) => Vec::new(),
+        }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Default)]
-pub struct UserDetails {
-    pub id: i32,
-    pub name: String,
-    pub contribution: f64,
-    pub percent: f64,
-    pub loan: f64,
-    pub interest: f64,
-}
-
 #[derive(Debug, Default)]
 pub struct MainView {
     pub current_tab: Tab,
     pub tab_data: TabData,
-    pub home_button: button::State,
-    pub users_button: button::State,
-    pub debts_button: button::State,
+    pub tab_buttons: TabButtons,
+    pub retry_button: button::State,
+    pub restore_backup_button: button::State,
+    pub undo_button: button::State,
+    pub redo_button: button::State,
+    /// Why the last undo or redo could not be made
+    pub undo_error: String,
 }
 
-fn render_main_view_error(err: &rusqlite::Error) -> Column<Message> {
-    Column::new().push(
-        Text::new(format!("Error while fetching data: {}", err.to_string()))
-            .size(28)
-            .font(OPEN_SANS)
-            .color(style::RED),
-    )
+/// The buttons of the sidebar, one for each tab and one
+/// to go back to the list of groups
+#[derive(Debug, Default)]
+pub struct TabButtons {
+    pub home: button::State,
+    pub users: button::State,
+    pub debts: button::State,
+    pub expenses: button::State,
+    pub reconciliation: button::State,
+    pub settings: button::State,
+    pub switch_group: button::State,
+}
+
+/// The catalog keys of what went wrong when `err` stopped the books
+/// from being read, and of the advice on what to do about it
+pub fn load_error_keys(err: &Error) -> (&'static str, &'static str) {
+    match err {
+        Error::Locked => ("load_error.locked", "load_error.locked_advice"),
+        Error::Schema(_) => ("load_error.schema", "load_error.schema_advice"),
+        Error::Io(_) => ("load_error.io", "load_error.io_advice"),
+        _ => ("load_error.other", "load_error.other_advice"),
+    }
+}
+
+/// Shown in place of a tab whose data could not be loaded, with
+/// what can be done about it. Backups are restored from the settings
+/// tab, which still opens when the rest of the books cannot be read
+fn render_main_view_error<'a>(
+    err: &Error,
+    current_tab: Tab,
+    retry_button: &'a mut button::State,
+    restore_backup_button: &'a mut button::State,
+) -> Column<'a, Message> {
+    let (heading, advice) = load_error_keys(err);
+
+    Column::new()
+        .padding(40)
+        .spacing(20)
+        .push(
+            Text::new(tr(heading))
+                .size(28)
+                .font(OPEN_SANS)
+                .color(style::palette().danger),
+        )
+        .push(Text::new(i18n::error(err)).size(20).font(OPEN_SANS))
+        .push(
+            Text::new(tr(advice))
+                .size(20)
+                .font(OPEN_SANS)
+                .color(style::palette().heading),
+        )
+        .push(
+            Row::new()
+                .spacing(20)
+                .push(
+                    Button::new(
+                        retry_button,
+                        Text::new(tr("load_error.try_again"))
+                            .size(24)
+                            .font(OPEN_SANS),
+                    )
+                    .style(style::Button::Confirm)
+                    .padding(10)
+                    .on_press(Message::TabButtonPressed(current_tab)),
+                )
+                .push(
+                    Button::new(
+                        restore_backup_button,
+                        Text::new(tr("load_error.restore_backup"))
+                            .size(24)
+                            .font(OPEN_SANS),
+                    )
+                    .style(style::Button::Destructive)
+                    .padding(10)
+                    .on_press(Message::TabButtonPressed(Tab::Settings)),
+                ),
+        )
 }
 
 pub fn render_tab_buttons<'a>(
-    home_button: &'a mut button::State,
-    users_button: &'a mut button::State,
-    debts_button: &'a mut button::State,
+    buttons: &'a mut TabButtons,
     current_tab: Tab,
 ) -> Column<'a, Message> {
     #[inline]
@@ -114,69 +307,152 @@ pub fn render_tab_buttons<'a>(
     Column::new()
         // .padding(15)
         .push(change_tab_button(
-            home_button,
-            "Home",
+            &mut buttons.home,
+            &tr("tab.home"),
             Tab::Home,
             current_tab,
         ))
         .push(change_tab_button(
-            users_button,
-            "Users",
+            &mut buttons.users,
+            &tr("tab.users"),
             Tab::Users,
             current_tab,
         ))
         .push(change_tab_button(
-            debts_button,
-            "Debts",
+            &mut buttons.debts,
+            &tr("tab.debts"),
             Tab::Debts,
             current_tab,
         ))
+        .push(change_tab_button(
+            &mut buttons.expenses,
+            &tr("tab.expenses"),
+            Tab::Expenses,
+            current_tab,
+        ))
+        .push(change_tab_button(
+            &mut buttons.reconciliation,
+            &tr("tab.cash_book"),
+            Tab::Reconciliation,
+            current_tab,
+        ))
+        .push(change_tab_button(
+            &mut buttons.settings,
+            &tr("tab.settings"),
+            Tab::Settings,
+            current_tab,
+        ))
+        .push(Space::with_height(iced::Length::Units(30)))
+        .push(
+            Button::new(
+                &mut buttons.switch_group,
+                Text::new(tr("tab.switch_group"))
+                    .size(22)
+                    .horizontal_alignment(iced::HorizontalAlignment::Center)
+                    .font(OPEN_SANS),
+            )
+            .on_press(Message::SwitchGroupButtonPressed)
+            .width(iced::Length::Units(SIDEBAR_WIDTH))
+            .style(style::SidebarButton::Deselected),
+        )
+}
+
+/// The buttons under the tabs that undo the last change to the
+/// books and redo it. Each is only enabled when there is something
+/// for it to do
+pub fn render_undo_buttons<'a>(
+    undo_button: &'a mut button::State,
+    redo_button: &'a mut button::State,
+    can_undo: bool,
+    can_redo: bool,
+    undo_error: &str,
+) -> Column<'a, Message> {
+    fn history_button<'a>(
+        state: &'a mut button::State,
+        text: &str,
+        message: Option<Message>,
+    ) -> button::Button<'a, Message> {
+        let enabled = message.is_some();
+        let button = Button::new(
+            state,
+            Text::new(text)
+                .size(18)
+                .horizontal_alignment(iced::HorizontalAlignment::Center)
+                .font(OPEN_SANS)
+                .color(if enabled {
+                    style::palette().heading
+                } else {
+                    style::palette().muted
+                }),
+        )
+        .width(iced::Length::Units(SIDEBAR_WIDTH))
+        .style(style::SidebarButton::Deselected);
+
+        match message {
+            Some(message) => button.on_press(message),
+            None => button,
+        }
+    }
+
+    let mut column = Column::new()
+        .push(Space::with_height(iced::Length::Units(30)))
+        .push(history_button(
+            undo_button,
+            &tr("history.undo"),
+            can_undo.then_some(Message::UndoButtonPressed),
+        ))
+        .push(history_button(
+            redo_button,
+            &tr("history.redo"),
+            can_redo.then_some(Message::RedoButtonPressed),
+        ));
+
+    if !undo_error.is_empty() {
+        column = column.push(
+            Text::new(undo_error)
+                .size(16)
+                .width(iced::Length::Units(SIDEBAR_WIDTH))
+                .font(OPEN_SANS)
+                .color(style::palette().danger),
+        );
+    }
+
+    column
 }
 
 fn render_edit_pane(contents: Column<Message>) -> Container<Message> {
     Container::new(contents.align_items(iced::Align::Center))
         .padding(10)
-        .height(iced::Length::Units(WINDOW_HEIGHT))
-        .width(iced::Length::Units(EDIT_PANE_WIDTH))
+        .height(iced::Length::Fill)
+        .width(iced::Length::FillPortion(EDIT_PANE_PORTION))
         .align_x(iced::Align::Center)
         .align_y(iced::Align::Center)
         .style(style::EditPane)
 }
 
-pub fn render_main_view<'a>(tab_data: &'a mut TabData) -> Container<'a, Message> {
+pub fn render_main_view<'a>(
+    tab_data: &'a mut TabData,
+    current_tab: Tab,
+    retry_button: &'a mut button::State,
+    restore_backup_button: &'a mut button::State,
+    member_query: &MemberQuery,
+    currency: &Currency,
+    theme: Theme,
+) -> Container<'a, Message> {
     Container::new(Column::new().push(match tab_data {
         TabData::Home(htd_result) => match htd_result {
-            Ok(HomeTabData {
-                total_cash,
-                total_shares,
-                total_loans,
-                total_debt,
-                profit,
-                user_details,
-            }) => home_tab::render_home_tab(
-                *total_cash,
-                *total_debt,
-                *total_shares,
-                *profit,
-                user_details.clone(),
-            ),
-            Err(err) => render_main_view_error(err),
+            Ok(home_tab_data) => home_tab::render_home_tab(home_tab_data, member_query, currency),
+            Err(err) => {
+                render_main_view_error(err, current_tab, retry_button, restore_backup_button)
+            }
         },
         TabData::Users(users_result) => match users_result {
-            Ok(users_tab::UsersTabData {
-                user_details,
-                edit_pane,
-                add_user_button,
-                edit_button_states,
-                delete_button_states,
-            }) => users_tab::render_users_tab(
-                add_user_button,
-                user_details,
-                edit_button_states,
-                delete_button_states,
-                edit_pane,
-            ),
-            Err(err) => render_main_view_error(err),
+            Ok(users_tab_data) => {
+                users_tab::render_users_tab(users_tab_data, member_query, currency)
+            }
+            Err(err) => {
+                render_main_view_error(err, current_tab, retry_button, restore_backup_button)
+            }
         },
         TabData::Debts(debts_result) => match debts_result {
             Ok(debts_tab::DebtsTabData {
@@ -184,16 +460,48 @@ pub fn render_main_view<'a>(tab_data: &'a mut TabData) -> Container<'a, Message>
                 edit_pane,
                 add_button_states,
                 repay_button_states,
+                table,
             }) => debts_tab::render_debts_tab(
                 user_details,
                 add_button_states,
                 repay_button_states,
                 edit_pane,
+                table,
+                member_query,
+                currency,
             ),
-            Err(err) => render_main_view_error(err),
+            Err(err) => {
+                render_main_view_error(err, current_tab, retry_button, restore_backup_button)
+            }
+        },
+        TabData::Expenses(expenses_result) => match expenses_result {
+            Ok(expenses_tab_data) => expenses_tab::render_expenses_tab(expenses_tab_data, currency),
+            Err(err) => {
+                render_main_view_error(err, current_tab, retry_button, restore_backup_button)
+            }
+        },
+        TabData::Reconciliation(reconciliation_result) => match reconciliation_result {
+            Ok(reconciliation_tab_data) => {
+                reconciliation_tab::render_reconciliation_tab(reconciliation_tab_data, currency)
+            }
+            Err(err) => {
+                render_main_view_error(err, current_tab, retry_button, restore_backup_button)
+            }
+        },
+        TabData::Settings(settings_result) => match settings_result {
+            Ok(settings_tab_data) => settings_tab::render_settings_tab(settings_tab_data, theme),
+            Err(err) => {
+                render_main_view_error(err, current_tab, retry_button, restore_backup_button)
+            }
+        },
+        TabData::Member(member_result) => match member_result {
+            Ok(member_tab_data) => member_tab::render_member_tab(member_tab_data, currency),
+            Err(err) => {
+                render_main_view_error(err, current_tab, retry_button, restore_backup_button)
+            }
         },
     }))
     .style(style::TabContents)
-    .width(iced::Length::Units(WINDOW_WIDTH - SIDEBAR_WIDTH))
-    .height(iced::Length::Units(WINDOW_HEIGHT))
+    .width(iced::Length::Fill)
+    .height(iced::Length::Fill)
 }
@@ -0,0 +1,270 @@
+use iced::{
+    canvas::{self, path::Arc, Canvas, Cursor, Frame, Geometry, Path, Stroke},
+    Color, HorizontalAlignment, Length, Point, Rectangle, Size, VerticalAlignment,
+};
+
+use family_banking_core::{
+    ledger::TotalsPoint,
+    model::{Currency, Member},
+};
+
+use crate::{
+    family_banking::Message,
+    format_decimal,
+    i18n::tr,
+    style::{self, OPEN_SANS},
+};
+
+const LABEL_SIZE: f32 = 16.0;
+
+/// A line of the totals chart: the catalog key of its name,
+/// its colour and how its value is read from a point
+type Series = (&'static str, Color, fn(&TotalsPoint) -> f64);
+
+fn label(content: String, position: Point, color: Color) -> canvas::Text {
+    canvas::Text {
+        content,
+        position,
+        color,
+        size: LABEL_SIZE,
+        font: OPEN_SANS,
+        ..canvas::Text::default()
+    }
+}
+
+/// Shows the text of `message` in the middle of a chart with nothing to draw
+fn no_data(frame: &mut Frame, message: &str) {
+    frame.fill_text(canvas::Text {
+        horizontal_alignment: HorizontalAlignment::Center,
+        vertical_alignment: VerticalAlignment::Center,
+        ..label(tr(message), frame.center(), style::palette().muted)
+    });
+}
+
+/// Pie chart of how the shares are divided between the members
+pub struct OwnershipPie {
+    slices: Vec<(String, f64)>,
+}
+
+impl canvas::Program<Message> for OwnershipPie {
+    fn draw(&self, bounds: Rectangle, _cursor: Cursor) -> Vec<Geometry> {
+        let mut frame = Frame::new(bounds.size());
+        let total: f64 = self.slices.iter().map(|(_, share)| share).sum();
+
+        if total <= 0.0 {
+            no_data(&mut frame, "charts.no_shares");
+            return vec![frame.into_geometry()];
+        }
+
+        let radius = frame.height() / 2.0 - 10.0;
+        let center = Point::new(radius + 10.0, frame.height() / 2.0);
+        let mut start_angle = -std::f32::consts::FRAC_PI_2;
+
+        for (i, (name, share)) in self.slices.iter().enumerate() {
+            let chart = &style::palette().chart;
+            let color = chart[i % chart.len()];
+            let end_angle = start_angle + (share / total) as f32 * 2.0 * std::f32::consts::PI;
+
+            frame.fill(
+                &Path::new(|builder| {
+                    builder.move_to(center);
+                    builder.arc(Arc {
+                        center,
+                        radius,
+                        start_angle,
+                        end_angle,
+                    });
+                    builder.close();
+                }),
+                color,
+            );
+
+            let legend_y = 10.0 + i as f32 * (LABEL_SIZE + 4.0);
+
+            if legend_y + LABEL_SIZE < frame.height() {
+                let legend_x = center.x + radius + 20.0;
+
+                frame.fill_rectangle(
+                    Point::new(legend_x, legend_y + 3.0),
+                    Size::new(10.0, 10.0),
+                    color,
+                );
+                frame.fill_text(label(
+                    format!("{} ({}%)", name, format_decimal(share / total * 100.0)),
+                    Point::new(legend_x + 15.0, legend_y),
+                    style::palette().heading,
+                ));
+            }
+
+            start_angle = end_angle;
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+pub fn ownership_pie(user_details: &[Member]) -> Canvas<Message, OwnershipPie> {
+    Canvas::new(OwnershipPie {
+        slices: user_details
+            .iter()
+            .filter(|user| user.shares > 0.0)
+            .map(|user| (user.name.clone(), user.shares))
+            .collect(),
+    })
+    .width(Length::Fill)
+    .height(Length::Units(180))
+}
+
+/// Bar chart of the outstanding debt of each member
+pub struct DebtBars {
+    bars: Vec<(String, f64)>,
+    currency: Currency,
+}
+
+impl canvas::Program<Message> for DebtBars {
+    fn draw(&self, bounds: Rectangle, _cursor: Cursor) -> Vec<Geometry> {
+        let mut frame = Frame::new(bounds.size());
+        let max_debt = self
+            .bars
+            .iter()
+            .fold(0.0, |acc: f64, (_, debt)| acc.max(*debt));
+
+        if max_debt <= 0.0 {
+            no_data(&mut frame, "charts.no_debts");
+            return vec![frame.into_geometry()];
+        }
+
+        let chart_height = frame.height() - 2.0 * (LABEL_SIZE + 4.0);
+        let slot_width = frame.width() / self.bars.len() as f32;
+
+        for (i, (name, debt)) in self.bars.iter().enumerate() {
+            let bar_height = (debt / max_debt) as f32 * chart_height;
+            let x = i as f32 * slot_width;
+            let top = LABEL_SIZE + 4.0 + chart_height - bar_height;
+
+            frame.fill_rectangle(
+                Point::new(x + slot_width * 0.15, top),
+                Size::new(slot_width * 0.7, bar_height),
+                style::palette().danger,
+            );
+            frame.fill_text(canvas::Text {
+                horizontal_alignment: HorizontalAlignment::Center,
+                vertical_alignment: VerticalAlignment::Bottom,
+                ..label(
+                    self.currency.format_number(*debt),
+                    Point::new(x + slot_width / 2.0, top),
+                    style::palette().heading,
+                )
+            });
+            frame.fill_text(canvas::Text {
+                horizontal_alignment: HorizontalAlignment::Center,
+                ..label(
+                    name.clone(),
+                    Point::new(x + slot_width / 2.0, frame.height() - LABEL_SIZE - 2.0),
+                    style::palette().heading,
+                )
+            });
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+pub fn debt_bars(user_details: &[Member], currency: &Currency) -> Canvas<Message, DebtBars> {
+    Canvas::new(DebtBars {
+        bars: user_details
+            .iter()
+            .map(|user| (user.name.clone(), user.loan + user.interest))
+            .collect(),
+        currency: currency.clone(),
+    })
+    .width(Length::Fill)
+    .height(Length::Units(180))
+}
+
+/// Line chart of the total shares, available cash and profit over time
+pub struct TotalsLines {
+    points: Vec<TotalsPoint>,
+}
+
+impl canvas::Program<Message> for TotalsLines {
+    fn draw(&self, bounds: Rectangle, _cursor: Cursor) -> Vec<Geometry> {
+        let mut frame = Frame::new(bounds.size());
+
+        if self.points.len() < 2 {
+            no_data(&mut frame, "charts.no_history");
+            return vec![frame.into_geometry()];
+        }
+
+        let (min, max) = self
+            .points
+            .iter()
+            .fold((0.0, 0.0), |acc: (f64, f64), point| {
+                (
+                    acc.0.min(point.shares).min(point.cash).min(point.profit),
+                    acc.1.max(point.shares).max(point.cash).max(point.profit),
+                )
+            });
+        let range = if max > min { max - min } else { 1.0 };
+
+        let top = LABEL_SIZE + 8.0;
+        let chart_height = frame.height() - 2.0 * top;
+        let step = frame.width() / (self.points.len() - 1) as f32;
+        let to_point = |i: usize, value: f64| {
+            Point::new(
+                i as f32 * step,
+                top + chart_height - ((value - min) / range) as f32 * chart_height,
+            )
+        };
+
+        let series: [Series; 3] = [
+            ("charts.shares", style::palette().accent, |point| {
+                point.shares
+            }),
+            ("charts.cash", style::palette().chart[1], |point| point.cash),
+            ("charts.profit", style::palette().chart[2], |point| {
+                point.profit
+            }),
+        ];
+
+        for (n, (name, color, value)) in series.iter().enumerate() {
+            frame.stroke(
+                &Path::new(|builder| {
+                    for (i, point) in self.points.iter().enumerate() {
+                        if i == 0 {
+                            builder.move_to(to_point(i, value(point)));
+                        } else {
+                            builder.line_to(to_point(i, value(point)));
+                        }
+                    }
+                }),
+                Stroke::default().with_color(*color).with_width(2.0),
+            );
+            frame.fill_text(label(tr(name), Point::new(n as f32 * 80.0, 0.0), *color));
+        }
+
+        frame.fill_text(label(
+            self.points[0].date.clone(),
+            Point::new(0.0, frame.height() - LABEL_SIZE),
+            style::palette().muted,
+        ));
+        frame.fill_text(canvas::Text {
+            horizontal_alignment: HorizontalAlignment::Right,
+            ..label(
+                self.points[self.points.len() - 1].date.clone(),
+                Point::new(frame.width(), frame.height() - LABEL_SIZE),
+                style::palette().muted,
+            )
+        });
+
+        vec![frame.into_geometry()]
+    }
+}
+
+pub fn totals_lines(points: &[TotalsPoint]) -> Canvas<Message, TotalsLines> {
+    Canvas::new(TotalsLines {
+        points: points.to_vec(),
+    })
+    .width(Length::Fill)
+    .height(Length::Units(180))
+}
@@ -1,24 +1,34 @@
 use crate::{
     family_banking::Message,
-    format_decimal, style,
-    style::{ACCENT_COLOR, OPEN_SANS, OPEN_SANS_BOLD},
-    EDIT_PANE_WIDTH, SIDEBAR_WIDTH, WINDOW_WIDTH,
+    i18n::{tr, tr_with},
+    style,
+    style::{OPEN_SANS, OPEN_SANS_BOLD},
+    TAB_PORTION,
 };
 
 use iced::{button, text_input, Button, Column, Container, Row, Text, TextInput};
 
-use super::{render_edit_pane, EditingPane, UserDetails};
+use family_banking_core::model::{
+    AllocationRule, Currency, Member, MemberQuery, MemberSort, Repayment, SortColumn,
+};
+
+use super::{
+    render_edit_pane,
+    table::{actions_cell, amount_header, render_member_table, table_cell, MemberTable},
+    EditingPane,
+};
 
 #[derive(Debug, Clone)]
 pub struct DebtsTabData {
-    pub user_details: Vec<UserDetails>,
+    pub user_details: Vec<Member>,
     pub edit_pane: EditingPane,
     pub add_button_states: Vec<button::State>,
     pub repay_button_states: Vec<button::State>,
+    pub table: MemberTable,
 }
 
 impl DebtsTabData {
-    pub fn new(user_details: Vec<UserDetails>) -> Self {
+    pub fn new(user_details: Vec<Member>, sort: MemberSort) -> Self {
         let mut add_button_states = Vec::new();
         let mut repay_button_states = Vec::new();
 
@@ -32,6 +42,7 @@ impl DebtsTabData {
             add_button_states,
             repay_button_states,
             edit_pane: EditingPane::default(),
+            table: MemberTable::new(sort),
         }
     }
 }
@@ -50,6 +61,8 @@ pub struct AddDebt {
 #[derive(Debug, Clone, Default)]
 pub struct RepayDebt {
     pub user_id: i32,
+    /// The group's rule, used to preview how the repayment is split
+    pub rule: AllocationRule,
     pub repayment_value: String,
     pub repayment_input: text_input::State,
     pub error_message: String,
@@ -57,241 +70,285 @@ pub struct RepayDebt {
 }
 
 pub fn render_debts_tab<'a>(
-    user_details: &'a mut Vec<UserDetails>,
-    add_button_states: &'a mut Vec<button::State>,
-    repay_button_states: &'a mut Vec<button::State>,
+    user_details: &'a mut [Member],
+    add_button_states: &'a mut [button::State],
+    repay_button_states: &'a mut [button::State],
     edit_pane: &'a mut EditingPane,
+    table: &'a mut MemberTable,
+    query: &MemberQuery,
+    currency: &Currency,
 ) -> Column<'a, Message> {
-    let mut row =
-        Row::new().push(
+    let mut row = Row::new().push(
+        Column::new()
+            .padding(20)
+            .push(
+                Container::new(
+                    Row::new()
+                        .push(
+                            Text::new(format!("{} ", tr("summary.total_debt")))
+                                .size(32)
+                                .font(OPEN_SANS),
+                        )
+                        .push(
+                            Text::new(
+                                currency.format(
+                                    user_details
+                                        .iter()
+                                        .fold(0.0, |acc, user| acc + user.interest + user.loan),
+                                ),
+                            )
+                            .color(style::palette().accent)
+                            .size(32)
+                            .font(OPEN_SANS_BOLD),
+                        ),
+                )
+                .padding(10),
+            )
+            .push(if !user_details.is_empty() {
+                render_debts_list(
+                    user_details,
+                    add_button_states,
+                    repay_button_states,
+                    table,
+                    query,
+                    currency,
+                )
+            } else {
+                Column::new()
+            })
+            .width(iced::Length::FillPortion(TAB_PORTION)),
+    );
+    if let Some(pane) = render_debts_pane(edit_pane, user_details, currency) {
+        row = row.push(pane);
+    }
+    Column::new().push(row)
+}
+
+/// The edit pane for lending to a member or taking a repayment, if one is open
+pub fn render_debts_pane<'a>(
+    edit_pane: &'a mut EditingPane,
+    user_details: &[Member],
+    currency: &Currency,
+) -> Option<Container<'a, Message>> {
+    match edit_pane {
+        EditingPane::AddingDebt(add_debt) => Some(render_edit_pane(
             Column::new()
-                .padding(20)
                 .push(
-                    Container::new(
-                        Row::new()
-                            .push(Text::new("Total Debt: ").size(32).font(OPEN_SANS))
-                            .push(
-                                Text::new(format!(
-                                    "K{}",
-                                    format_decimal(user_details.iter().fold(0.0, |acc, user| {
-                                        acc + user.interest + user.loan
-                                    }))
-                                ))
-                                .color(ACCENT_COLOR)
-                                .size(32)
-                                .font(OPEN_SANS_BOLD),
-                            ),
-                    )
-                    .padding(10),
+                    Text::new(tr_with(
+                        "debts.lend_to",
+                        &[&user_details
+                            .iter()
+                            .find(|u| u.id == add_debt.user_id)
+                            .unwrap()
+                            .name],
+                    ))
+                    .font(OPEN_SANS_BOLD)
+                    .size(32)
+                    .color(style::palette().heading),
                 )
-                .push(if user_details.len() > 0 {
-                    render_debts_list(user_details, add_button_states, repay_button_states)
-                } else {
+                .push(
                     Column::new()
-                })
-                .width(match edit_pane {
-                    EditingPane::Closed => iced::Length::Units(WINDOW_WIDTH - SIDEBAR_WIDTH),
-                    _ => iced::Length::Units(WINDOW_WIDTH - SIDEBAR_WIDTH - EDIT_PANE_WIDTH),
-                }),
-        );
-    match edit_pane {
-        EditingPane::Closed => {}
-        EditingPane::AddingDebt(add_debt) => {
-            row = row.push(render_edit_pane(
+                        .padding(20)
+                        .push(
+                            TextInput::new(
+                                &mut add_debt.debt_input,
+                                &tr("debts.loan_amount"),
+                                &add_debt.debt_value,
+                                Message::EditPaneDebtInputChanged,
+                            )
+                            .on_submit(Message::EditPaneConfirmButtonClicked)
+                            .padding(10)
+                            .size(28)
+                            .font(OPEN_SANS),
+                        )
+                        .push(
+                            TextInput::new(
+                                &mut add_debt.interest_input,
+                                &tr("debts.interest"),
+                                &add_debt.interest_value,
+                                Message::EditPaneInterestInputChanged,
+                            )
+                            .on_submit(Message::EditPaneConfirmButtonClicked)
+                            .padding(10)
+                            .size(28)
+                            .font(OPEN_SANS),
+                        ),
+                )
+                .push(
+                    Text::new(add_debt.error_message.clone())
+                        .size(28)
+                        .font(OPEN_SANS)
+                        .color(style::palette().danger),
+                )
+                .push(
+                    Button::new(
+                        &mut add_debt.confirm_button,
+                        Text::new(tr("common.confirm")).size(28).font(OPEN_SANS),
+                    )
+                    .style(style::Button::Confirm)
+                    .on_press(Message::EditPaneConfirmButtonClicked),
+                ),
+        )),
+        EditingPane::RepayingDebt(repay_debt) => {
+            let member = user_details
+                .iter()
+                .find(|u| u.id == repay_debt.user_id)
+                .unwrap();
+
+            Some(render_edit_pane(
                 Column::new()
                     .push(
-                        Text::new(format!(
-                            "Lend money to {}",
-                            user_details
-                                .iter()
-                                .find(|u| { u.id == add_debt.user_id })
-                                .unwrap()
-                                .name
-                        ))
-                        .font(OPEN_SANS_BOLD)
-                        .size(32)
-                        .color(style::DARK_GREY),
+                        Text::new(tr_with("debts.repay_for", &[&member.name]))
+                            .font(OPEN_SANS_BOLD)
+                            .size(32)
+                            .color(style::palette().heading),
                     )
                     .push(
                         Column::new()
                             .padding(20)
                             .push(
                                 TextInput::new(
-                                    &mut add_debt.debt_input,
-                                    "Loan ammount",
-                                    &add_debt.debt_value,
-                                    Message::EditPaneDebtInputChanged,
-                                )
-                                .padding(10)
-                                .size(28)
-                                .font(OPEN_SANS),
-                            )
-                            .push(
-                                TextInput::new(
-                                    &mut add_debt.interest_input,
-                                    "Interest",
-                                    &add_debt.interest_value,
-                                    Message::EditPaneInterestInputChanged,
+                                    &mut repay_debt.repayment_input,
+                                    &tr("debts.repayment"),
+                                    &repay_debt.repayment_value,
+                                    Message::EditPaneRepaymentInputChanged,
                                 )
+                                .on_submit(Message::EditPaneConfirmButtonClicked)
                                 .padding(10)
                                 .size(28)
                                 .font(OPEN_SANS),
-                            ),
-                    )
-                    .push(
-                        Text::new(add_debt.error_message.clone())
-                            .size(28)
-                            .font(OPEN_SANS)
-                            .color(style::RED),
-                    )
-                    .push(
-                        Button::new(
-                            &mut add_debt.confirm_button,
-                            Text::new("Confirm").size(28).font(OPEN_SANS),
-                        )
-                        .style(style::Button::Confirm)
-                        .on_press(Message::EditPaneConfirmButtonClicked),
-                    ),
-            ))
-        }
-        EditingPane::RepayingDebt(repay_debt) => {
-            row = row.push(render_edit_pane(
-                Column::new()
-                    .push(
-                        Text::new(format!(
-                            "Repay debt for {}",
-                            user_details
-                                .iter()
-                                .find(|u| { u.id == repay_debt.user_id })
-                                .unwrap()
-                                .name
-                        ))
-                        .font(OPEN_SANS_BOLD)
-                        .size(32)
-                        .color(style::DARK_GREY),
-                    )
-                    .push(
-                        Column::new().padding(20).push(
-                            TextInput::new(
-                                &mut repay_debt.repayment_input,
-                                "Repayment",
-                                &repay_debt.repayment_value,
-                                Message::EditPaneRepaymentInputChanged,
                             )
-                            .padding(10)
-                            .size(28)
-                            .font(OPEN_SANS),
-                        ),
+                            .push(repayment_preview(
+                                repay_debt.rule,
+                                member,
+                                currency.parse(&repay_debt.repayment_value),
+                                currency,
+                            )),
                     )
                     .push(
                         Text::new(repay_debt.error_message.clone())
                             .size(28)
                             .font(OPEN_SANS)
-                            .color(style::RED),
+                            .color(style::palette().danger),
                     )
                     .push(
                         Button::new(
                             &mut repay_debt.confirm_button,
-                            Text::new("Confirm").size(28).font(OPEN_SANS),
+                            Text::new(tr("common.confirm")).size(28).font(OPEN_SANS),
                         )
                         .style(style::Button::Confirm)
                         .on_press(Message::EditPaneConfirmButtonClicked),
                     ),
             ))
         }
-        _ => {}
+        _ => None,
     }
-    Column::new().push(row)
 }
 
-pub fn render_debts_list<'a>(
-    user_details: &'a Vec<UserDetails>,
-    add_button_states: &'a mut Vec<button::State>,
-    repay_button_states: &'a mut Vec<button::State>,
+/// The catalog key of the name of `rule`
+pub fn allocation_rule_key(rule: AllocationRule) -> &'static str {
+    match rule {
+        AllocationRule::PrincipalFirst => "allocation.principal_first",
+        AllocationRule::InterestFirst => "allocation.interest_first",
+        AllocationRule::Proportional => "allocation.proportional",
+    }
+}
+
+/// How `amount` would be split between the member's loan and interest,
+/// shown as it is typed. Left empty until the amount could be repaid
+fn repayment_preview<'a>(
+    rule: AllocationRule,
+    member: &Member,
+    amount: Option<f64>,
+    currency: &Currency,
 ) -> Column<'a, Message> {
-    let mut col = Column::new().padding(10).push(
-        Row::new()
-            .push(
-                Container::new(
-                    Text::new("User")
-                        .size(28)
-                        .width(iced::Length::Fill)
-                        .font(OPEN_SANS_BOLD),
-                )
-                .padding(10)
-                .width(iced::Length::Units(200)),
-            )
-            .push(
-                Container::new(
-                    Text::new("Debt (K)")
-                        .size(28)
-                        .width(iced::Length::Fill)
-                        .horizontal_alignment(iced::HorizontalAlignment::Right)
-                        .font(OPEN_SANS_BOLD),
-                )
-                .padding(10)
-                .width(iced::Length::Units(200)),
-            ),
-    );
+    let debt = member.loan + member.interest;
+    let mut preview = Column::new().padding(10).spacing(5);
 
-    let mut add_buttons = Vec::new();
+    if let Some(amount) = amount.filter(|amount| (0.0..=debt).contains(amount)) {
+        let repayment = Repayment::allocate(rule, member.loan, member.interest, amount);
+        let line = |key: &str, amount: f64| {
+            Text::new(tr_with(key, &[&currency.format(amount)]))
+                .size(24)
+                .font(OPEN_SANS)
+        };
 
-    for (i, state) in add_button_states.iter_mut().enumerate() {
-        add_buttons.push(
-            Button::new(state, Text::new("Lend").font(OPEN_SANS))
-                .style(style::Button::IconDestructive)
-                .on_press(Message::AddDebtButtonPressed(user_details[i].id)),
-        );
+        preview = preview
+            .push(
+                Text::new(tr_with(
+                    "debts.preview_rule",
+                    &[&tr(allocation_rule_key(rule))],
+                ))
+                .size(24)
+                .font(OPEN_SANS),
+            )
+            .push(line("debts.preview_loan", repayment.loan))
+            .push(line("debts.preview_interest", repayment.interest))
+            .push(line("debts.preview_remaining", debt - amount))
+            // The interest paid is credited to the group as profit
+            .push(line("debts.preview_profit", repayment.interest).color(style::palette().accent));
     }
 
-    let mut repay_buttons = Vec::new();
+    preview
+}
 
-    for (i, state) in repay_button_states.iter_mut().enumerate() {
-        repay_buttons.push(
-            Button::new(state, Text::new("Repay").font(OPEN_SANS))
-                .style(style::Button::Icon)
-                .on_press(Message::RepayDebtButtonPressed(user_details[i].id)),
-        );
-    }
+pub fn render_debts_list<'a>(
+    user_details: &'a [Member],
+    add_button_states: &'a mut [button::State],
+    repay_button_states: &'a mut [button::State],
+    table: &'a mut MemberTable,
+    query: &MemberQuery,
+    currency: &Currency,
+) -> Column<'a, Message> {
+    let user_header = tr("column.user");
+    let debt_header = amount_header(&tr("column.debt"), currency);
+    let mut rows = Vec::new();
 
-    for (i, user) in user_details.iter().enumerate() {
-        col = col.push(
-            Container::new(
-                Row::new()
-                    .push(
-                        Row::new()
-                            .push(
-                                Container::new(
-                                    Text::new(user.name.clone())
-                                        .width(iced::Length::Fill)
-                                        .size(28)
-                                        .font(OPEN_SANS),
-                                )
-                                .width(iced::Length::Units(200))
-                                .padding(10),
-                            )
-                            .push(
-                                Container::new(
-                                    Text::new(format_decimal(user.interest + user.loan))
-                                        .horizontal_alignment(iced::HorizontalAlignment::Right)
-                                        .width(iced::Length::Fill)
-                                        .size(28)
-                                        .font(OPEN_SANS),
-                                )
-                                .width(iced::Length::Units(200))
-                                .padding(10),
-                            ),
-                    )
-                    .push(add_buttons.remove(0))
-                    .push(repay_buttons.remove(0)),
-            )
-            .style(if i % 2 == 0 {
-                style::TableRow::Lighter
-            } else {
-                style::TableRow::Darker
-            }),
-        );
+    for ((user, add_state), repay_state) in user_details
+        .iter()
+        .zip(add_button_states.iter_mut())
+        .zip(repay_button_states.iter_mut())
+        .filter(|((user, _), _)| query.matches(user))
+    {
+        rows.push((
+            user,
+            Row::new()
+                .push(table_cell(
+                    currency.format_number(user.interest + user.loan),
+                    iced::HorizontalAlignment::Right,
+                ))
+                .push(actions_cell(
+                    Row::new()
+                        .push(
+                            Button::new(add_state, Text::new(tr("debts.lend")).font(OPEN_SANS))
+                                .style(style::Button::IconDestructive)
+                                .on_press(Message::AddDebtButtonPressed(user.id)),
+                        )
+                        .push(
+                            Button::new(repay_state, Text::new(tr("debts.repay")).font(OPEN_SANS))
+                                .style(style::Button::Icon)
+                                .on_press(Message::RepayDebtButtonPressed(user.id)),
+                        ),
+                )),
+        ));
     }
 
-    col
+    render_member_table(
+        table,
+        query,
+        &[
+            (
+                &user_header,
+                SortColumn::Name,
+                iced::HorizontalAlignment::Left,
+            ),
+            (
+                &debt_header,
+                SortColumn::Debt,
+                iced::HorizontalAlignment::Right,
+            ),
+        ],
+        true,
+        rows,
+    )
 }
@@ -0,0 +1,290 @@
+use family_banking_core::model::{Category, Currency, Entry, EntryKind};
+use iced::{
+    button, pick_list, text_input, Button, Column, Container, PickList, Row, Text, TextInput,
+};
+
+use crate::{
+    family_banking::Message,
+    i18n::tr,
+    style::{self, OPEN_SANS, OPEN_SANS_BOLD},
+    TAB_PORTION,
+};
+
+use super::{render_edit_pane, EditingPane};
+
+#[derive(Debug, Clone)]
+pub struct ExpensesTabData {
+    pub entries: Vec<Entry>,
+    pub categories: Vec<Category>,
+    pub total_expenses: f64,
+    pub total_income: f64,
+    pub edit_pane: EditingPane,
+    pub add_expense_button: button::State,
+    pub add_income_button: button::State,
+    /// The ledger record whose entry is highlighted, after
+    /// following the link of a notification to it
+    pub highlighted_record: Option<i64>,
+}
+
+impl ExpensesTabData {
+    pub fn new(entries: Vec<Entry>, categories: Vec<Category>) -> Self {
+        let (total_expenses, total_income) =
+            entries
+                .iter()
+                .fold((0.0, 0.0), |acc, entry| match entry.kind {
+                    EntryKind::Expense => (acc.0 + entry.amount, acc.1),
+                    EntryKind::Income => (acc.0, acc.1 + entry.amount),
+                });
+
+        ExpensesTabData {
+            entries,
+            categories,
+            total_expenses,
+            total_income,
+            edit_pane: EditingPane::default(),
+            add_expense_button: button::State::new(),
+            add_income_button: button::State::new(),
+            highlighted_record: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AddEntry {
+    pub kind: EntryKind,
+    pub categories: Vec<Category>,
+    pub category: Option<Category>,
+    pub category_list: pick_list::State<Category>,
+    pub description_value: String,
+    pub description_input: text_input::State,
+    pub amount_value: String,
+    pub amount_input: text_input::State,
+    pub error_message: String,
+    pub confirm_button: button::State,
+}
+
+impl AddEntry {
+    pub fn new(kind: EntryKind, categories: &[Category]) -> Self {
+        AddEntry {
+            kind,
+            categories: categories
+                .iter()
+                .filter(|category| category.kind == kind)
+                .cloned()
+                .collect(),
+            ..AddEntry::default()
+        }
+    }
+}
+
+pub fn render_expenses_tab<'a>(
+    tab_data: &'a mut ExpensesTabData,
+    currency: &Currency,
+) -> Column<'a, Message> {
+    let mut row = Row::new().push(
+        Column::new()
+            .padding(20)
+            .push(
+                Container::new(
+                    Row::new()
+                        .push(
+                            Text::new(format!("{} ", tr("summary.expenses")))
+                                .size(32)
+                                .font(OPEN_SANS),
+                        )
+                        .push(
+                            Text::new(format!("{}   ", currency.format(tab_data.total_expenses)))
+                                .color(style::palette().accent)
+                                .size(32)
+                                .font(OPEN_SANS_BOLD),
+                        )
+                        .push(
+                            Text::new(format!("{} ", tr("expenses.income")))
+                                .size(32)
+                                .font(OPEN_SANS),
+                        )
+                        .push(
+                            Text::new(currency.format(tab_data.total_income))
+                                .color(style::palette().accent)
+                                .size(32)
+                                .font(OPEN_SANS_BOLD),
+                        ),
+                )
+                .padding(10),
+            )
+            .push(
+                Row::new()
+                    .push(
+                        Container::new(
+                            Button::new(
+                                &mut tab_data.add_expense_button,
+                                Text::new(tr("expenses.add_expense"))
+                                    .size(28)
+                                    .font(OPEN_SANS),
+                            )
+                            .style(style::Button::Destructive)
+                            .on_press(Message::AddExpenseButtonPressed),
+                        )
+                        .padding(10),
+                    )
+                    .push(
+                        Container::new(
+                            Button::new(
+                                &mut tab_data.add_income_button,
+                                Text::new(tr("expenses.add_income"))
+                                    .size(28)
+                                    .font(OPEN_SANS),
+                            )
+                            .style(style::Button::Confirm)
+                            .on_press(Message::AddIncomeButtonPressed),
+                        )
+                        .padding(10),
+                    ),
+            )
+            .push(if tab_data.entries.is_empty() {
+                Column::new().padding(10).push(
+                    Text::new(tr("expenses.none_yet"))
+                        .font(OPEN_SANS)
+                        .size(28)
+                        .color(style::palette().muted),
+                )
+            } else {
+                render_entries_list(&tab_data.entries, tab_data.highlighted_record, currency)
+            })
+            .width(iced::Length::FillPortion(TAB_PORTION)),
+    );
+
+    if let EditingPane::AddingEntry(add_entry) = &mut tab_data.edit_pane {
+        row = row.push(render_edit_pane(
+            Column::new()
+                .push(
+                    Text::new(tr(match add_entry.kind {
+                        EntryKind::Expense => "expenses.add_expense_title",
+                        EntryKind::Income => "expenses.add_income_title",
+                    }))
+                    .font(OPEN_SANS_BOLD)
+                    .size(32)
+                    .color(style::palette().heading),
+                )
+                .push(
+                    Column::new()
+                        .padding(20)
+                        .push(
+                            PickList::new(
+                                &mut add_entry.category_list,
+                                add_entry.categories.clone(),
+                                add_entry.category.clone(),
+                                Message::EditPaneCategorySelected,
+                            )
+                            .width(iced::Length::Fill)
+                            .padding(10)
+                            .text_size(28)
+                            .font(OPEN_SANS),
+                        )
+                        .push(
+                            TextInput::new(
+                                &mut add_entry.description_input,
+                                &tr("expenses.description"),
+                                &add_entry.description_value,
+                                Message::EditPaneDescriptionInputChanged,
+                            )
+                            .on_submit(Message::EditPaneConfirmButtonClicked)
+                            .padding(10)
+                            .size(28)
+                            .font(OPEN_SANS),
+                        )
+                        .push(
+                            TextInput::new(
+                                &mut add_entry.amount_input,
+                                &tr("column.amount"),
+                                &add_entry.amount_value,
+                                Message::EditPaneAmountInputChanged,
+                            )
+                            .on_submit(Message::EditPaneConfirmButtonClicked)
+                            .padding(10)
+                            .size(28)
+                            .font(OPEN_SANS),
+                        ),
+                )
+                .push(
+                    Text::new(add_entry.error_message.clone())
+                        .size(28)
+                        .font(OPEN_SANS)
+                        .color(style::palette().danger),
+                )
+                .push(
+                    Button::new(
+                        &mut add_entry.confirm_button,
+                        Text::new(tr("common.confirm")).size(28).font(OPEN_SANS),
+                    )
+                    .style(style::Button::Confirm)
+                    .on_press(Message::EditPaneConfirmButtonClicked),
+                ),
+        ))
+    }
+
+    Column::new().push(row)
+}
+
+/// The entries, newest first, with that of `highlighted_record` highlighted
+pub fn render_entries_list(
+    entries: &[Entry],
+    highlighted_record: Option<i64>,
+    currency: &Currency,
+) -> Column<'static, Message> {
+    let mut col = Column::new().padding(10);
+
+    for (i, entry) in entries.iter().enumerate() {
+        col = col.push(
+            Container::new(
+                Column::new()
+                    .push(
+                        Row::new()
+                            .push(
+                                Text::new(entry.category.clone())
+                                    .width(iced::Length::Fill)
+                                    .size(28)
+                                    .font(OPEN_SANS),
+                            )
+                            .push(
+                                Text::new(match entry.kind {
+                                    EntryKind::Expense => {
+                                        format!("-{}", currency.format(entry.amount))
+                                    }
+                                    EntryKind::Income => {
+                                        format!("+{}", currency.format(entry.amount))
+                                    }
+                                })
+                                .width(iced::Length::Fill)
+                                .horizontal_alignment(iced::HorizontalAlignment::Right)
+                                .size(28)
+                                .font(OPEN_SANS)
+                                .color(match entry.kind {
+                                    EntryKind::Expense => style::palette().danger,
+                                    EntryKind::Income => style::palette().accent,
+                                }),
+                            ),
+                    )
+                    .push(
+                        Text::new(format!("{}  {}", entry.date, entry.description))
+                            .size(20)
+                            .font(OPEN_SANS)
+                            .color(style::palette().muted),
+                    ),
+            )
+            .width(iced::Length::Fill)
+            .padding(10)
+            .style(
+                if entry.ledger_id.is_some() && entry.ledger_id == highlighted_record {
+                    style::TableRow::Highlighted
+                } else if i % 2 == 0 {
+                    style::TableRow::Lighter
+                } else {
+                    style::TableRow::Darker
+                },
+            ),
+        );
+    }
+
+    col
+}
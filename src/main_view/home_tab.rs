@@ -1,159 +1,194 @@
-use iced::{Column, Container, Row, Text};
+use iced::{button, Button, Column, Container, Row, Text};
 
-use super::UserDetails;
+use family_banking_core::{
+    ledger::TotalsPoint,
+    model::{Currency, Member, MemberQuery, SortColumn, Summary},
+};
+
+use super::{
+    charts,
+    table::{amount_header, render_member_table, table_cell, MemberTable},
+};
 
 use crate::{
-    format_decimal, style,
-    style::{ACCENT_COLOR, OPEN_SANS, OPEN_SANS_BOLD},
+    format_decimal,
+    i18n::tr,
+    style,
+    style::{OPEN_SANS, OPEN_SANS_BOLD},
 };
 
 use crate::family_banking::Message;
 
 #[derive(Debug, Clone, Default)]
 pub struct HomeTabData {
-    pub total_shares: f64,
-    pub total_debt: f64,
-    pub total_loans: f64,
-    pub total_cash: f64,
-    pub profit: f64,
-    pub user_details: Vec<UserDetails>,
+    pub summary: Summary,
+    pub totals_history: Vec<TotalsPoint>,
+    pub user_details: Vec<Member>,
+    pub charts_visible: bool,
+    pub charts_button: button::State,
+    pub table: MemberTable,
 }
 
-pub fn render_home_tab(
-    total_cash: f64,
-    total_debt: f64,
-    total_shares: f64,
-    profit: f64,
-    user_details: Vec<UserDetails>,
-) -> Column<'static, Message> {
+impl HomeTabData {
+    /// Whether the table of members is shown, rather
+    /// than the charts or the message that there are none
+    pub fn shows_table(&self) -> bool {
+        !self.charts_visible && !self.user_details.is_empty()
+    }
+}
+
+pub fn render_home_tab<'a>(
+    home_tab_data: &'a mut HomeTabData,
+    query: &MemberQuery,
+    currency: &Currency,
+) -> Column<'a, Message> {
+    let user_details = &home_tab_data.user_details;
+
     Column::new()
         .padding(20)
         .push(render_home_tab_summary(
-            total_cash,
-            total_debt,
-            total_shares,
-            profit,
+            currency,
+            home_tab_data.summary.total_cash,
+            home_tab_data.summary.total_debt,
+            home_tab_data.summary.total_shares,
+            home_tab_data.summary.profit,
+            home_tab_data.summary.total_expenses,
+            home_tab_data.summary.total_income,
         ))
-        .push(if user_details.len() > 0 {
-            Row::new()
-                .padding(10)
-                .push(render_table_column(
-                    "Member".to_string(),
-                    user_details.iter().map(|user| user.name.clone()).collect(),
-                    iced::HorizontalAlignment::Left,
-                ))
-                .push(render_table_column(
-                    "Shares (K)".to_string(),
-                    user_details
-                        .iter()
-                        .map(|user| format!("{}", format_decimal(user.contribution)))
-                        .collect(),
-                    iced::HorizontalAlignment::Right,
-                ))
-                .push(render_table_column(
-                    "Percentage".to_string(),
-                    user_details
-                        .iter()
-                        .map(|user| format!("{}%", format_decimal(user.percent)))
-                        .collect(),
-                    iced::HorizontalAlignment::Right,
-                ))
-                .push(render_table_column(
-                    "Debt (K)".to_string(),
-                    user_details
-                        .iter()
-                        .map(|user| format!("{}", format_decimal(user.loan + user.interest)))
-                        .collect(),
-                    iced::HorizontalAlignment::Right,
-                ))
-        } else {
+        .push(
+            Container::new(
+                Button::new(
+                    &mut home_tab_data.charts_button,
+                    Text::new(tr(if home_tab_data.charts_visible {
+                        "home.show_table"
+                    } else {
+                        "home.show_charts"
+                    }))
+                    .font(OPEN_SANS),
+                )
+                .style(style::Button::Icon)
+                .on_press(Message::ToggleChartsButtonPressed),
+            )
+            .padding(5),
+        )
+        .push(if user_details.is_empty() {
             Row::new()
                 .push(
-                    Text::new("No data yet. You can start entering data through the \"Users\" tab")
+                    Text::new(tr("home.no_data"))
                         .font(OPEN_SANS)
                         .size(28)
-                        .color(style::GREY),
+                        .color(style::palette().muted),
                 )
                 .padding(50)
+        } else if home_tab_data.charts_visible {
+            Row::new().padding(10).push(
+                Column::new()
+                    .push(
+                        Row::new()
+                            .push(charts::ownership_pie(user_details))
+                            .push(charts::debt_bars(user_details, currency)),
+                    )
+                    .push(charts::totals_lines(&home_tab_data.totals_history)),
+            )
+        } else {
+            let member_header = tr("column.member");
+            let shares_header = amount_header(&tr("column.shares"), currency);
+            let percent_header = tr("column.percentage");
+            let debt_header = amount_header(&tr("column.debt"), currency);
+            let mut rows = Vec::new();
+
+            for user in user_details.iter().filter(|user| query.matches(user)) {
+                rows.push((
+                    user,
+                    Row::new()
+                        .push(table_cell(
+                            currency.format_number(user.shares),
+                            iced::HorizontalAlignment::Right,
+                        ))
+                        .push(table_cell(
+                            format!("{}%", format_decimal(user.percent)),
+                            iced::HorizontalAlignment::Right,
+                        ))
+                        .push(table_cell(
+                            currency.format_number(user.debt()),
+                            iced::HorizontalAlignment::Right,
+                        )),
+                ));
+            }
+
+            Row::new().push(render_member_table(
+                &mut home_tab_data.table,
+                query,
+                &[
+                    (
+                        &member_header,
+                        SortColumn::Name,
+                        iced::HorizontalAlignment::Left,
+                    ),
+                    (
+                        &shares_header,
+                        SortColumn::Shares,
+                        iced::HorizontalAlignment::Right,
+                    ),
+                    (
+                        &percent_header,
+                        SortColumn::Percent,
+                        iced::HorizontalAlignment::Right,
+                    ),
+                    (
+                        &debt_header,
+                        SortColumn::Debt,
+                        iced::HorizontalAlignment::Right,
+                    ),
+                ],
+                false,
+                rows,
+            ))
         })
 }
 
 pub fn render_home_tab_summary(
+    currency: &Currency,
     total_cash: f64,
     total_debt: f64,
     total_shares: f64,
     profit: f64,
+    total_expenses: f64,
+    total_income: f64,
 ) -> Column<'static, Message> {
-    #[inline]
-    fn cash_display(cash: f64) -> Text {
-        Text::new(format!("K{}   ", format_decimal(cash)))
-            .color(ACCENT_COLOR)
+    let cash_display = |cash: f64| {
+        Text::new(format!("{}   ", currency.format(cash)))
+            .color(style::palette().accent)
             .size(32)
             .font(OPEN_SANS_BOLD)
-    }
+    };
 
     #[inline]
-    fn label_display(label: &str) -> Text {
-        Text::new(label).size(32).font(OPEN_SANS)
+    fn label_display(key: &str) -> Text {
+        Text::new(format!("{} ", tr(key))).size(32).font(OPEN_SANS)
     }
 
     Column::new()
         .padding(10)
         .push(
             Row::new()
-                .push(label_display("Available Cash: "))
+                .push(label_display("summary.available_cash"))
                 .push(cash_display(total_cash))
-                .push(label_display(" Total Debt: "))
+                .push(label_display("summary.total_debt"))
                 .push(cash_display(total_debt)),
         )
         .push(
             Row::new()
-                .push(label_display("Total Shares: "))
+                .push(label_display("summary.total_shares"))
                 .push(cash_display(total_shares))
-                .push(label_display(" Profit: "))
+                .push(label_display("summary.profit"))
                 .push(cash_display(profit)),
         )
-}
-
-pub fn render_table_column<T>(
-    label: String,
-    column_values: Vec<T>,
-    h_alignment: iced::HorizontalAlignment,
-) -> Column<'static, Message>
-where
-    T: Into<String> + Clone,
-{
-    let mut col = Column::new().width(iced::Length::Units(200)).push(
-        Container::new(
-            Text::new(label)
-                .size(28)
-                .font(OPEN_SANS_BOLD)
-                .width(iced::Length::Fill)
-                .horizontal_alignment(h_alignment),
+        .push(
+            Row::new()
+                .push(label_display("summary.expenses"))
+                .push(cash_display(total_expenses))
+                .push(label_display("summary.other_income"))
+                .push(cash_display(total_income)),
         )
-        .width(iced::Length::Fill)
-        .padding(10)
-        .style(style::TableRow::Header),
-    );
-
-    for (i, value) in column_values.iter().enumerate() {
-        col = col.push(
-            Container::new(
-                Text::new(value.clone())
-                    .width(iced::Length::Fill)
-                    .size(28)
-                    .font(OPEN_SANS)
-                    .horizontal_alignment(h_alignment),
-            )
-            .width(iced::Length::Fill)
-            .padding(10)
-            .style(if i % 2 == 0 {
-                style::TableRow::Lighter
-            } else {
-                style::TableRow::Darker
-            }),
-        );
-    }
-
-    col
 }
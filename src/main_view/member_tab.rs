@@ -0,0 +1,409 @@
+//! The page of one member: their profile, what they have paid in,
+//! their loans and what they would take home at a share-out. Every
+//! change that can be made to a member can be started from here
+
+use std::slice;
+
+use family_banking_core::model::{Currency, MemberDetails};
+use iced::{
+    button, scrollable, text_input, Button, Column, Container, Row, Scrollable, Text, TextInput,
+};
+
+use crate::{
+    family_banking::Message,
+    format_decimal,
+    i18n::{tr, tr_with},
+    style::{self, OPEN_SANS, OPEN_SANS_BOLD},
+    TAB_PORTION,
+};
+
+use super::{debts_tab, users_tab, EditingPane, Tab};
+
+#[derive(Debug, Clone, Default)]
+pub struct MemberTabData {
+    pub details: MemberDetails,
+    pub edit_pane: EditingPane,
+    pub phone_value: String,
+    pub phone_input: text_input::State,
+    pub notes_value: String,
+    pub notes_input: text_input::State,
+    /// Why the profile could not be saved
+    pub error_message: String,
+    pub save_button: button::State,
+    pub back_button: button::State,
+    pub edit_button: button::State,
+    pub lend_button: button::State,
+    pub repay_button: button::State,
+    pub delete_button: button::State,
+    pub scroll: scrollable::State,
+    /// The ledger record whose row is highlighted, after
+    /// following the link of a notification to it
+    pub highlighted_record: Option<i64>,
+}
+
+impl MemberTabData {
+    pub fn new(details: MemberDetails) -> Self {
+        MemberTabData {
+            phone_value: details.profile.phone.clone(),
+            notes_value: details.profile.notes.clone(),
+            details,
+            ..MemberTabData::default()
+        }
+    }
+
+    /// Whether the phone number or notes differ from those saved
+    pub fn profile_changed(&self) -> bool {
+        self.phone_value.trim() != self.details.profile.phone
+            || self.notes_value.trim() != self.details.profile.notes
+    }
+}
+
+pub fn render_member_tab<'a>(
+    tab_data: &'a mut MemberTabData,
+    currency: &Currency,
+) -> Column<'a, Message> {
+    let profile_changed = tab_data.profile_changed();
+    let MemberTabData {
+        details,
+        edit_pane,
+        phone_value,
+        phone_input,
+        notes_value,
+        notes_input,
+        error_message,
+        save_button,
+        back_button,
+        edit_button,
+        lend_button,
+        repay_button,
+        delete_button,
+        scroll,
+        highlighted_record,
+    } = tab_data;
+    let highlighted = |id: i64| Some(id) == *highlighted_record;
+    let member = &details.member;
+
+    let actions = Row::new()
+        .spacing(10)
+        .push(
+            Button::new(edit_button, Text::new(tr("member.edit")).font(OPEN_SANS))
+                .style(style::Button::Icon)
+                .on_press(Message::EditUserButtonPressed(member.id)),
+        )
+        .push(
+            Button::new(lend_button, Text::new(tr("debts.lend")).font(OPEN_SANS))
+                .style(style::Button::IconDestructive)
+                .on_press(Message::AddDebtButtonPressed(member.id)),
+        )
+        .push(
+            Button::new(repay_button, Text::new(tr("debts.repay")).font(OPEN_SANS))
+                .style(style::Button::Icon)
+                .on_press(Message::RepayDebtButtonPressed(member.id)),
+        )
+        .push(
+            Button::new(
+                delete_button,
+                Text::new(tr("member.delete")).font(OPEN_SANS),
+            )
+            .style(style::Button::IconDestructive)
+            .on_press(Message::DeleteUserButtonPressed(member.id)),
+        );
+
+    let balances = Row::new()
+        .spacing(40)
+        .push(render_figure(
+            "member.shares",
+            format!(
+                "{} ({}%)",
+                currency.format(member.shares),
+                format_decimal(member.percent)
+            ),
+        ))
+        .push(render_figure("member.owes", currency.format(member.debt())))
+        .push(render_figure(
+            "member.share_out",
+            currency.format(details.share_out),
+        ));
+
+    let mut save = Button::new(
+        save_button,
+        Text::new(tr(if profile_changed {
+            "common.save"
+        } else {
+            "common.saved"
+        }))
+        .size(24)
+        .font(OPEN_SANS),
+    )
+    .padding(10)
+    .style(if profile_changed {
+        style::Button::Confirm
+    } else {
+        style::Button::Deselected
+    });
+
+    if profile_changed {
+        save = save.on_press(Message::SaveMemberProfilePressed);
+    }
+
+    let profile = Column::new()
+        .spacing(10)
+        .push(
+            Text::new(match &details.profile.joined {
+                Some(joined) => tr_with("member.since", &[joined]),
+                None => tr("member.joined_unknown"),
+            })
+            .size(20)
+            .font(OPEN_SANS)
+            .color(style::palette().muted),
+        )
+        .push(
+            TextInput::new(
+                phone_input,
+                &tr("member.phone"),
+                phone_value,
+                Message::MemberPhoneInputChanged,
+            )
+            .on_submit(Message::SaveMemberProfilePressed)
+            .padding(10)
+            .size(24)
+            .font(OPEN_SANS),
+        )
+        .push(
+            TextInput::new(
+                notes_input,
+                &tr("member.notes"),
+                notes_value,
+                Message::MemberNotesInputChanged,
+            )
+            .on_submit(Message::SaveMemberProfilePressed)
+            .padding(10)
+            .size(24)
+            .font(OPEN_SANS),
+        )
+        .push(
+            Row::new()
+                .spacing(20)
+                .align_items(iced::Align::Center)
+                .push(save)
+                .push(
+                    Text::new(error_message.clone())
+                        .size(20)
+                        .font(OPEN_SANS)
+                        .color(style::palette().danger),
+                ),
+        );
+
+    let contributions = details
+        .contributions
+        .iter()
+        .map(|record| {
+            let values = vec![
+                record.date.clone(),
+                tr(if record.reverses.is_some() {
+                    "member.undone"
+                } else if record.amount < 0.0 {
+                    "member.taken_out"
+                } else {
+                    "member.paid_in"
+                }),
+                currency.format(record.amount),
+            ];
+
+            (values, highlighted(record.id))
+        })
+        .collect();
+
+    let loans = details
+        .loans
+        .iter()
+        .map(|loan| {
+            let values = vec![
+                loan.date.clone(),
+                currency.format(loan.loan),
+                currency.format(loan.interest),
+                currency.format(loan.repaid),
+                currency.format(loan.remaining()),
+            ];
+
+            (values, highlighted(loan.id))
+        })
+        .collect();
+
+    let repayments = details
+        .repayments
+        .iter()
+        .map(|record| {
+            let values = vec![
+                if record.reverses.is_some() {
+                    tr_with("member.date_undone", &[&record.date])
+                } else {
+                    record.date.clone()
+                },
+                currency.format(record.amount),
+                currency.format(record.interest),
+                currency.format(record.amount + record.interest),
+            ];
+
+            (values, highlighted(record.id))
+        })
+        .collect();
+
+    let page = Scrollable::new(scroll)
+        .padding(20)
+        .spacing(20)
+        .push(
+            Button::new(
+                back_button,
+                Text::new(tr("member.back")).size(20).font(OPEN_SANS),
+            )
+            .style(style::Button::Icon)
+            .on_press(Message::TabButtonPressed(Tab::Users)),
+        )
+        .push(
+            Text::new(member.name.clone())
+                .size(40)
+                .font(OPEN_SANS_BOLD)
+                .color(style::palette().heading),
+        )
+        .push(actions)
+        .push(balances)
+        .push(profile)
+        .push(render_section(
+            "member.contributions",
+            &["column.date", "", "column.amount"],
+            contributions,
+        ))
+        .push(render_section(
+            "member.loans",
+            &[
+                "column.date",
+                "column.loan",
+                "column.interest",
+                "column.repaid",
+                "column.remaining",
+            ],
+            loans,
+        ))
+        .push(render_section(
+            "member.repayments",
+            &[
+                "column.date",
+                "column.loan",
+                "column.interest",
+                "column.total",
+            ],
+            repayments,
+        ));
+
+    let mut row =
+        Row::new().push(Container::new(page).width(iced::Length::FillPortion(TAB_PORTION)));
+
+    let members = slice::from_ref(member);
+    let pane = match edit_pane {
+        EditingPane::AddingDebt(_) | EditingPane::RepayingDebt(_) => {
+            debts_tab::render_debts_pane(edit_pane, members, currency)
+        }
+        _ => users_tab::render_users_pane(edit_pane, members),
+    };
+
+    if let Some(pane) = pane {
+        row = row.push(pane);
+    }
+    Column::new().push(row)
+}
+
+/// An amount labelled with the text of `label` in
+/// the row of balances at the top of the page
+fn render_figure<'a>(label: &str, value: String) -> Column<'a, Message> {
+    Column::new()
+        .push(
+            Text::new(tr(label))
+                .size(20)
+                .font(OPEN_SANS)
+                .color(style::palette().muted),
+        )
+        .push(Text::new(value).size(28).font(OPEN_SANS))
+}
+
+/// A small table of `rows` under the text of `title`, each with whether
+/// it is highlighted. The first of `columns` is aligned to the left and
+/// the amounts after it to the right. Like `title`, they are catalog
+/// keys, or empty for no label
+fn render_section<'a>(
+    title: &str,
+    columns: &[&str],
+    rows: Vec<(Vec<String>, bool)>,
+) -> Column<'a, Message> {
+    fn cells<'a>(values: Vec<String>, font: iced::Font) -> Row<'a, Message> {
+        let mut row = Row::new();
+
+        for (i, value) in values.into_iter().enumerate() {
+            row = row.push(
+                Text::new(value)
+                    .width(iced::Length::Fill)
+                    .size(20)
+                    .font(font)
+                    .horizontal_alignment(if i == 0 {
+                        iced::HorizontalAlignment::Left
+                    } else {
+                        iced::HorizontalAlignment::Right
+                    }),
+            );
+        }
+
+        row
+    }
+
+    let mut col = Column::new()
+        .push(
+            Text::new(tr(title))
+                .size(28)
+                .font(OPEN_SANS_BOLD)
+                .color(style::palette().heading),
+        )
+        .push(
+            Container::new(cells(
+                columns
+                    .iter()
+                    .map(|column| match *column {
+                        "" => String::new(),
+                        key => tr(key),
+                    })
+                    .collect(),
+                OPEN_SANS_BOLD,
+            ))
+            .width(iced::Length::Fill)
+            .padding(10)
+            .style(style::TableRow::Header),
+        );
+
+    if rows.is_empty() {
+        col = col.push(
+            Container::new(
+                Text::new(tr("member.none_yet"))
+                    .size(20)
+                    .font(OPEN_SANS)
+                    .color(style::palette().muted),
+            )
+            .padding(10),
+        );
+    }
+
+    for (i, (values, highlighted)) in rows.into_iter().enumerate() {
+        col = col.push(
+            Container::new(cells(values, OPEN_SANS))
+                .width(iced::Length::Fill)
+                .padding(10)
+                .style(if highlighted {
+                    style::TableRow::Highlighted
+                } else if i % 2 == 0 {
+                    style::TableRow::Lighter
+                } else {
+                    style::TableRow::Darker
+                }),
+        );
+    }
+
+    col
+}
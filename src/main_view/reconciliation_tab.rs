@@ -0,0 +1,241 @@
+use family_banking_core::model::{Currency, Reconciliation};
+use iced::{button, text_input, Button, Checkbox, Column, Container, Row, Text, TextInput};
+
+use crate::{
+    family_banking::Message,
+    i18n::{tr, tr_with},
+    style::{self, OPEN_SANS, OPEN_SANS_BOLD},
+    TAB_PORTION,
+};
+
+use super::render_edit_pane;
+
+#[derive(Debug, Clone, Default)]
+pub struct ReconciliationTabData {
+    pub computed_cash: f64,
+    pub history: Vec<Reconciliation>,
+    pub counted_cash_value: String,
+    pub counted_cash_input: text_input::State,
+    pub bank_balance_value: String,
+    pub bank_balance_input: text_input::State,
+    pub post_adjustment: bool,
+    pub reason_value: String,
+    pub reason_input: text_input::State,
+    pub error_message: String,
+    pub confirm_button: button::State,
+}
+
+impl ReconciliationTabData {
+    pub fn new(computed_cash: f64, history: Vec<Reconciliation>) -> Self {
+        ReconciliationTabData {
+            computed_cash,
+            history,
+            ..ReconciliationTabData::default()
+        }
+    }
+
+    /// The variance for the values currently entered,
+    /// if they are both valid amounts in `currency`
+    pub fn variance(&self, currency: &Currency) -> Option<f64> {
+        let counted_cash = currency.parse(&self.counted_cash_value)?;
+        let bank_balance = currency.parse(&self.bank_balance_value)?;
+
+        Some(counted_cash + bank_balance - self.computed_cash)
+    }
+}
+
+pub fn render_reconciliation_tab<'a>(
+    tab_data: &'a mut ReconciliationTabData,
+    currency: &Currency,
+) -> Column<'a, Message> {
+    let variance = tab_data.variance(currency);
+
+    Column::new().push(
+        Row::new()
+            .push(
+                Column::new()
+                    .padding(20)
+                    .push(
+                        Container::new(
+                            Row::new()
+                                .push(
+                                    Text::new(format!("{} ", tr("reconcile.computed_cash")))
+                                        .size(32)
+                                        .font(OPEN_SANS),
+                                )
+                                .push(
+                                    Text::new(currency.format(tab_data.computed_cash))
+                                        .color(style::palette().accent)
+                                        .size(32)
+                                        .font(OPEN_SANS_BOLD),
+                                ),
+                        )
+                        .padding(10),
+                    )
+                    .push(if tab_data.history.is_empty() {
+                        Row::new().padding(10).push(
+                            Text::new(tr("reconcile.none_yet"))
+                                .font(OPEN_SANS)
+                                .size(28)
+                                .color(style::palette().muted),
+                        )
+                    } else {
+                        Row::new()
+                            .padding(10)
+                            .push(render_reconciliation_history(&tab_data.history, currency))
+                    })
+                    .width(iced::Length::FillPortion(TAB_PORTION)),
+            )
+            .push(render_edit_pane(
+                Column::new()
+                    .push(
+                        Text::new(tr("reconcile.title"))
+                            .font(OPEN_SANS_BOLD)
+                            .size(32)
+                            .color(style::palette().heading),
+                    )
+                    .push(
+                        Column::new()
+                            .padding(20)
+                            .push(
+                                TextInput::new(
+                                    &mut tab_data.counted_cash_input,
+                                    &tr("reconcile.counted_cash"),
+                                    &tab_data.counted_cash_value,
+                                    Message::CountedCashInputChanged,
+                                )
+                                .on_submit(Message::RecordReconciliationButtonPressed)
+                                .padding(10)
+                                .size(28)
+                                .font(OPEN_SANS),
+                            )
+                            .push(
+                                TextInput::new(
+                                    &mut tab_data.bank_balance_input,
+                                    &tr("reconcile.bank_balance"),
+                                    &tab_data.bank_balance_value,
+                                    Message::BankBalanceInputChanged,
+                                )
+                                .on_submit(Message::RecordReconciliationButtonPressed)
+                                .padding(10)
+                                .size(28)
+                                .font(OPEN_SANS),
+                            ),
+                    )
+                    .push(
+                        Text::new(match variance {
+                            Some(variance) => {
+                                tr_with("reconcile.variance", &[&currency.format(variance)])
+                            }
+                            None => tr_with("reconcile.variance", &[&"-"]),
+                        })
+                        .size(28)
+                        .font(OPEN_SANS)
+                        .color(match variance {
+                            Some(variance) if variance != 0.0 => style::palette().danger,
+                            _ => style::palette().heading,
+                        }),
+                    )
+                    .push(
+                        Container::new(
+                            Checkbox::new(
+                                tab_data.post_adjustment,
+                                tr("reconcile.post_adjustment"),
+                                Message::PostAdjustmentToggled,
+                            )
+                            .font(OPEN_SANS),
+                        )
+                        .padding(10),
+                    )
+                    .push(
+                        TextInput::new(
+                            &mut tab_data.reason_input,
+                            &tr("reconcile.reason"),
+                            &tab_data.reason_value,
+                            Message::ReconciliationReasonInputChanged,
+                        )
+                        .on_submit(Message::RecordReconciliationButtonPressed)
+                        .padding(10)
+                        .size(28)
+                        .font(OPEN_SANS),
+                    )
+                    .push(
+                        Text::new(tab_data.error_message.clone())
+                            .size(28)
+                            .font(OPEN_SANS)
+                            .color(style::palette().danger),
+                    )
+                    .push(
+                        Button::new(
+                            &mut tab_data.confirm_button,
+                            Text::new(tr("reconcile.record")).size(28).font(OPEN_SANS),
+                        )
+                        .style(style::Button::Confirm)
+                        .on_press(Message::RecordReconciliationButtonPressed),
+                    ),
+            )),
+    )
+}
+
+fn render_reconciliation_history(
+    history: &[Reconciliation],
+    currency: &Currency,
+) -> Column<'static, Message> {
+    let mut col = Column::new().push(
+        Container::new(
+            Text::new(tr("reconcile.history"))
+                .size(28)
+                .font(OPEN_SANS_BOLD)
+                .width(iced::Length::Fill),
+        )
+        .width(iced::Length::Fill)
+        .padding(10)
+        .style(style::TableRow::Header),
+    );
+
+    for (i, entry) in history.iter().enumerate() {
+        col = col.push(
+            Container::new(
+                Column::new()
+                    .push(
+                        Row::new()
+                            .push(
+                                Text::new(entry.date.clone())
+                                    .width(iced::Length::Fill)
+                                    .size(28)
+                                    .font(OPEN_SANS),
+                            )
+                            .push(
+                                Text::new(currency.format(entry.variance()))
+                                    .width(iced::Length::Fill)
+                                    .horizontal_alignment(iced::HorizontalAlignment::Right)
+                                    .size(28)
+                                    .font(OPEN_SANS),
+                            ),
+                    )
+                    .push(
+                        Text::new(if entry.adjustment == 0.0 {
+                            tr_with("reconcile.not_adjusted", &[&entry.reason])
+                        } else {
+                            tr_with(
+                                "reconcile.adjusted",
+                                &[&currency.format(entry.adjustment), &entry.reason],
+                            )
+                        })
+                        .size(20)
+                        .font(OPEN_SANS)
+                        .color(style::palette().muted),
+                    ),
+            )
+            .width(iced::Length::Fill)
+            .padding(10)
+            .style(if i % 2 == 0 {
+                style::TableRow::Lighter
+            } else {
+                style::TableRow::Darker
+            }),
+        );
+    }
+
+    col
+}
@@ -0,0 +1,682 @@
+use iced::{
+    button, scrollable, text_input, Button, Column, Container, Row, Scrollable, Text, TextInput,
+};
+
+use family_banking_core::{
+    backup::{BackupFile, BackupSettings},
+    model::{AllocationRule, Currency, SymbolPosition},
+};
+
+use crate::{
+    family_banking::Message,
+    i18n::{self, tr, tr_with, Language},
+    main_view::debts_tab::allocation_rule_key,
+    style::{self, Theme, OPEN_SANS, OPEN_SANS_BOLD},
+};
+
+pub const DEFAULT_EXPORT_PATH: &str = "./family_banking.json";
+
+#[derive(Debug, Clone)]
+pub struct SettingsTabData {
+    pub scroll: scrollable::State,
+    pub theme_buttons: [button::State; 3],
+    pub language_buttons: [button::State; 2],
+    /// How the group splits repayments between loans and interest
+    pub allocation_rule: AllocationRule,
+    pub allocation_buttons: [button::State; 3],
+    pub currency: CurrencyInputs,
+    pub export_path_value: String,
+    pub export_path_input: text_input::State,
+    pub export_button: button::State,
+    pub import_path_value: String,
+    pub import_path_input: text_input::State,
+    pub import_button: button::State,
+    pub backup_folder_value: String,
+    pub backup_folder_input: text_input::State,
+    pub backup_retention_value: String,
+    pub backup_retention_input: text_input::State,
+    pub snapshot_retention_value: String,
+    pub snapshot_retention_input: text_input::State,
+    pub save_backup_settings_button: button::State,
+    pub save_snapshot_settings_button: button::State,
+    pub backup_now_button: button::State,
+    pub backups: Vec<BackupFile>,
+    pub restore_button_states: Vec<button::State>,
+    /// Snapshots taken automatically before risky operations
+    pub snapshots: Vec<BackupFile>,
+    pub restore_snapshot_button_states: Vec<button::State>,
+    /// The backup or snapshot the user has asked to restore,
+    /// while waiting for them to confirm
+    pub pending_restore: Option<BackupFile>,
+    pub confirm_restore_button: button::State,
+    pub cancel_restore_button: button::State,
+    pub status_message: String,
+    pub error_message: String,
+}
+
+impl SettingsTabData {
+    pub fn new(
+        backup_settings: BackupSettings,
+        backups: Vec<BackupFile>,
+        snapshots: Vec<BackupFile>,
+        currency: &Currency,
+    ) -> Self {
+        SettingsTabData {
+            currency: CurrencyInputs::new(currency),
+            backup_folder_value: backup_settings.folder.to_string_lossy().to_string(),
+            backup_retention_value: backup_settings.retention.to_string(),
+            snapshot_retention_value: backup_settings.snapshot_retention.to_string(),
+            restore_button_states: backups.iter().map(|_| button::State::new()).collect(),
+            backups,
+            restore_snapshot_button_states: snapshots
+                .iter()
+                .map(|_| button::State::new())
+                .collect(),
+            snapshots,
+            ..SettingsTabData::default()
+        }
+    }
+}
+
+impl Default for SettingsTabData {
+    fn default() -> Self {
+        SettingsTabData {
+            scroll: scrollable::State::new(),
+            theme_buttons: Default::default(),
+            language_buttons: Default::default(),
+            allocation_rule: AllocationRule::default(),
+            allocation_buttons: Default::default(),
+            currency: CurrencyInputs::default(),
+            export_path_value: DEFAULT_EXPORT_PATH.to_string(),
+            export_path_input: text_input::State::new(),
+            export_button: button::State::new(),
+            import_path_value: DEFAULT_EXPORT_PATH.to_string(),
+            import_path_input: text_input::State::new(),
+            import_button: button::State::new(),
+            backup_folder_value: String::new(),
+            backup_folder_input: text_input::State::new(),
+            backup_retention_value: String::new(),
+            backup_retention_input: text_input::State::new(),
+            snapshot_retention_value: String::new(),
+            snapshot_retention_input: text_input::State::new(),
+            save_backup_settings_button: button::State::new(),
+            save_snapshot_settings_button: button::State::new(),
+            backup_now_button: button::State::new(),
+            backups: Vec::new(),
+            restore_button_states: Vec::new(),
+            snapshots: Vec::new(),
+            restore_snapshot_button_states: Vec::new(),
+            pending_restore: None,
+            confirm_restore_button: button::State::new(),
+            cancel_restore_button: button::State::new(),
+            status_message: String::new(),
+            error_message: String::new(),
+        }
+    }
+}
+
+/// The inputs for how the group writes amounts of money
+#[derive(Debug, Clone, Default)]
+pub struct CurrencyInputs {
+    pub symbol_value: String,
+    pub symbol_input: text_input::State,
+    pub position: SymbolPosition,
+    pub position_buttons: [button::State; 2],
+    pub decimals_value: String,
+    pub decimals_input: text_input::State,
+    pub grouping_value: String,
+    pub grouping_input: text_input::State,
+    pub decimal_separator_value: String,
+    pub decimal_separator_input: text_input::State,
+    pub save_button: button::State,
+}
+
+impl CurrencyInputs {
+    pub fn new(currency: &Currency) -> Self {
+        CurrencyInputs {
+            symbol_value: currency.symbol.clone(),
+            position: currency.position,
+            decimals_value: currency.decimals.to_string(),
+            grouping_value: currency.grouping.map(String::from).unwrap_or_default(),
+            decimal_separator_value: currency.decimal_separator.to_string(),
+            ..CurrencyInputs::default()
+        }
+    }
+
+    /// The currency as entered, or why it cannot be used
+    pub fn entered_currency(&self) -> Result<Currency, String> {
+        fn single_char(value: &str, error_key: &str) -> Result<char, String> {
+            let mut chars = value.chars();
+
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Ok(c),
+                _ => Err(tr(error_key)),
+            }
+        }
+
+        let currency = Currency {
+            symbol: self.symbol_value.trim().to_string(),
+            position: self.position,
+            decimals: self
+                .decimals_value
+                .trim()
+                .parse()
+                .map_err(|_| tr("currency.invalid_decimals"))?,
+            // Not trimmed, as a space is a common thousands separator
+            grouping: match self.grouping_value.as_str() {
+                "" => None,
+                value => Some(single_char(value, "currency.invalid_grouping")?),
+            },
+            decimal_separator: single_char(
+                self.decimal_separator_value.trim(),
+                "currency.invalid_decimal_separator",
+            )?,
+        };
+
+        currency.validate().map_err(|err| i18n::error(&err))?;
+        Ok(currency)
+    }
+}
+
+fn section_title(title: &str) -> Text {
+    Text::new(tr(title))
+        .font(OPEN_SANS_BOLD)
+        .size(28)
+        .color(style::palette().heading)
+}
+
+/// A button for each theme, with the one in use picked out
+fn theme_row<'a>(theme_buttons: &'a mut [button::State; 3], active: Theme) -> Row<'a, Message> {
+    let mut row = Row::new().padding(10).spacing(10);
+
+    for (theme, state) in Theme::ALL.iter().zip(theme_buttons.iter_mut()) {
+        row = row.push(
+            Button::new(
+                state,
+                Text::new(tr(match theme {
+                    Theme::Light => "theme.light",
+                    Theme::Dark => "theme.dark",
+                    Theme::HighContrast => "theme.high_contrast",
+                }))
+                .size(24)
+                .font(OPEN_SANS),
+            )
+            .padding(10)
+            .style(if *theme == active {
+                style::Button::Confirm
+            } else {
+                style::Button::Deselected
+            })
+            .on_press(Message::ThemeSelected(*theme)),
+        );
+    }
+
+    row
+}
+
+/// A button for each language, with the one in use picked out
+fn language_row<'a>(
+    language_buttons: &'a mut [button::State; 2],
+    active: Language,
+) -> Row<'a, Message> {
+    let mut row = Row::new().padding(10).spacing(10);
+
+    for (language, state) in Language::ALL.iter().zip(language_buttons.iter_mut()) {
+        row = row.push(
+            Button::new(
+                state,
+                Text::new(language.to_string()).size(24).font(OPEN_SANS),
+            )
+            .padding(10)
+            .style(if *language == active {
+                style::Button::Confirm
+            } else {
+                style::Button::Deselected
+            })
+            .on_press(Message::LanguageSelected(*language)),
+        );
+    }
+
+    row
+}
+
+/// A button for each allocation rule, with the group's picked out
+fn allocation_row<'a>(
+    allocation_buttons: &'a mut [button::State; 3],
+    active: AllocationRule,
+) -> Row<'a, Message> {
+    let mut row = Row::new().padding(10).spacing(10);
+
+    for (rule, state) in AllocationRule::ALL
+        .iter()
+        .zip(allocation_buttons.iter_mut())
+    {
+        row = row.push(
+            Button::new(
+                state,
+                Text::new(tr(allocation_rule_key(*rule)))
+                    .size(24)
+                    .font(OPEN_SANS),
+            )
+            .padding(10)
+            .style(if *rule == active {
+                style::Button::Confirm
+            } else {
+                style::Button::Deselected
+            })
+            .on_press(Message::AllocationRuleSelected(*rule)),
+        );
+    }
+
+    row
+}
+
+fn currency_input<'a>(
+    input: &'a mut text_input::State,
+    placeholder: &str,
+    value: &str,
+    on_change: fn(String) -> Message,
+) -> TextInput<'a, Message> {
+    TextInput::new(input, placeholder, value, on_change)
+        .on_submit(Message::SaveCurrencyButtonPressed)
+        .width(iced::Length::Units(80))
+        .padding(10)
+        .size(24)
+        .font(OPEN_SANS)
+}
+
+/// The inputs for how amounts of money are written, with
+/// an example of an amount written the way they describe
+fn currency_section(inputs: &mut CurrencyInputs) -> Column<'_, Message> {
+    let example = match inputs.entered_currency() {
+        Ok(currency) => tr_with("currency.example", &[&currency.format(-1234567.891)]),
+        Err(reason) => reason,
+    };
+    let mut positions = Row::new().spacing(10);
+
+    for (position, state) in SymbolPosition::ALL
+        .iter()
+        .zip(inputs.position_buttons.iter_mut())
+    {
+        positions = positions.push(
+            Button::new(
+                state,
+                Text::new(tr(match position {
+                    SymbolPosition::Before => "currency.before",
+                    SymbolPosition::After => "currency.after",
+                }))
+                .size(24)
+                .font(OPEN_SANS),
+            )
+            .padding(10)
+            .style(if *position == inputs.position {
+                style::Button::Confirm
+            } else {
+                style::Button::Deselected
+            })
+            .on_press(Message::CurrencyPositionSelected(*position)),
+        );
+    }
+
+    let label = |key: &str| Text::new(tr(key)).size(24).font(OPEN_SANS);
+
+    Column::new()
+        .push(
+            Row::new()
+                .padding(10)
+                .spacing(10)
+                .align_items(iced::Align::Center)
+                .push(label("currency.symbol"))
+                .push(currency_input(
+                    &mut inputs.symbol_input,
+                    &tr("currency.none"),
+                    &inputs.symbol_value,
+                    Message::CurrencySymbolInputChanged,
+                ))
+                .push(positions),
+        )
+        .push(
+            Row::new()
+                .padding(10)
+                .spacing(10)
+                .align_items(iced::Align::Center)
+                .push(label("currency.decimals"))
+                .push(currency_input(
+                    &mut inputs.decimals_input,
+                    "2",
+                    &inputs.decimals_value,
+                    Message::CurrencyDecimalsInputChanged,
+                ))
+                .push(label("currency.grouping"))
+                .push(currency_input(
+                    &mut inputs.grouping_input,
+                    &tr("currency.none"),
+                    &inputs.grouping_value,
+                    Message::CurrencyGroupingInputChanged,
+                ))
+                .push(label("currency.decimal_separator"))
+                .push(currency_input(
+                    &mut inputs.decimal_separator_input,
+                    ".",
+                    &inputs.decimal_separator_value,
+                    Message::CurrencyDecimalSeparatorInputChanged,
+                ))
+                .push(
+                    Button::new(
+                        &mut inputs.save_button,
+                        Text::new(tr("common.save")).size(24).font(OPEN_SANS),
+                    )
+                    .style(style::Button::Confirm)
+                    .padding(10)
+                    .on_press(Message::SaveCurrencyButtonPressed),
+                ),
+        )
+        .push(
+            Text::new(example)
+                .size(20)
+                .font(OPEN_SANS)
+                .color(style::palette().muted),
+        )
+}
+
+fn path_row<'a>(
+    input: &'a mut text_input::State,
+    value: &str,
+    on_change: fn(String) -> Message,
+    button: &'a mut button::State,
+    label: &str,
+    on_press: Message,
+) -> Row<'a, Message> {
+    Row::new()
+        .padding(10)
+        .spacing(10)
+        .push(
+            TextInput::new(input, &tr("settings.file_path"), value, on_change)
+                .on_submit(on_press.clone())
+                .width(iced::Length::Units(450))
+                .padding(10)
+                .size(24)
+                .font(OPEN_SANS),
+        )
+        .push(
+            Button::new(button, Text::new(tr(label)).size(24).font(OPEN_SANS))
+                .style(style::Button::Confirm)
+                .padding(10)
+                .on_press(on_press),
+        )
+}
+
+pub fn backup_list<'a>(
+    backups: &[BackupFile],
+    restore_button_states: &'a mut [button::State],
+    on_press: fn(usize) -> Message,
+    empty_message: &str,
+) -> Column<'a, Message> {
+    let mut list = Column::new().padding(10);
+
+    for (i, (backup, state)) in backups
+        .iter()
+        .zip(restore_button_states.iter_mut())
+        .enumerate()
+    {
+        list = list.push(
+            Container::new(
+                Row::new()
+                    .push(
+                        Text::new(backup.name.clone())
+                            .width(iced::Length::Units(450))
+                            .size(24)
+                            .font(OPEN_SANS),
+                    )
+                    .push(
+                        Button::new(state, Text::new(tr("settings.restore")).font(OPEN_SANS))
+                            .style(style::Button::IconDestructive)
+                            .on_press(on_press(i)),
+                    ),
+            )
+            .padding(5)
+            .style(if i % 2 == 0 {
+                style::TableRow::Lighter
+            } else {
+                style::TableRow::Darker
+            }),
+        );
+    }
+
+    if backups.is_empty() {
+        list = list.push(
+            Text::new(tr(empty_message))
+                .size(24)
+                .font(OPEN_SANS)
+                .color(style::palette().muted),
+        );
+    }
+
+    list
+}
+
+/// Asks whether to go ahead with restoring the backup waiting
+/// to be restored, if there is one
+pub fn restore_confirmation<'a>(
+    pending_restore: &Option<BackupFile>,
+    cancel_button: &'a mut button::State,
+    confirm_button: &'a mut button::State,
+) -> Column<'a, Message> {
+    match pending_restore {
+        Some(backup) => Column::new()
+            .padding(10)
+            .push(
+                Text::new(tr_with("settings.confirm_restore", &[&backup.name]))
+                    .size(24)
+                    .font(OPEN_SANS),
+            )
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .push(
+                        Button::new(
+                            cancel_button,
+                            Text::new(tr("common.no")).size(24).font(OPEN_SANS),
+                        )
+                        .on_press(Message::CancelRestoreButtonPressed)
+                        .style(style::Button::Confirm),
+                    )
+                    .push(
+                        Button::new(
+                            confirm_button,
+                            Text::new(tr("common.yes")).size(24).font(OPEN_SANS),
+                        )
+                        .on_press(Message::ConfirmRestoreButtonPressed)
+                        .style(style::Button::Destructive),
+                    ),
+            ),
+        None => Column::new(),
+    }
+}
+
+fn retention_input<'a>(
+    input: &'a mut text_input::State,
+    value: &str,
+    on_change: fn(String) -> Message,
+) -> TextInput<'a, Message> {
+    TextInput::new(input, &tr("settings.keep"), value, on_change)
+        .on_submit(Message::SaveBackupSettingsButtonPressed)
+        .width(iced::Length::Units(110))
+        .padding(10)
+        .size(24)
+        .font(OPEN_SANS)
+}
+
+pub fn render_settings_tab(tab_data: &mut SettingsTabData, theme: Theme) -> Column<'_, Message> {
+    let backups_list = backup_list(
+        &tab_data.backups,
+        &mut tab_data.restore_button_states,
+        Message::RestoreBackupButtonPressed,
+        "settings.no_backups",
+    );
+    let snapshots_list = backup_list(
+        &tab_data.snapshots,
+        &mut tab_data.restore_snapshot_button_states,
+        Message::RestoreSnapshotButtonPressed,
+        "settings.no_snapshots",
+    );
+
+    let restore_confirmation = restore_confirmation(
+        &tab_data.pending_restore,
+        &mut tab_data.cancel_restore_button,
+        &mut tab_data.confirm_restore_button,
+    );
+
+    Column::new().push(
+        Scrollable::new(&mut tab_data.scroll)
+            .padding(20)
+            .height(iced::Length::Fill)
+            .push(
+                Container::new(
+                    Text::new(tr("tab.settings"))
+                        .font(OPEN_SANS_BOLD)
+                        .size(32)
+                        .color(style::palette().accent),
+                )
+                .padding(10),
+            )
+            .push(
+                Text::new(tab_data.status_message.clone())
+                    .size(24)
+                    .font(OPEN_SANS)
+                    .color(style::palette().accent),
+            )
+            .push(
+                Text::new(tab_data.error_message.clone())
+                    .size(24)
+                    .font(OPEN_SANS)
+                    .color(style::palette().danger),
+            )
+            .push(section_title("settings.language"))
+            .push(language_row(
+                &mut tab_data.language_buttons,
+                i18n::language(),
+            ))
+            .push(section_title("settings.theme"))
+            .push(theme_row(&mut tab_data.theme_buttons, theme))
+            .push(section_title("settings.allocation"))
+            .push(
+                Text::new(tr("settings.allocation_explained"))
+                    .size(20)
+                    .font(OPEN_SANS)
+                    .color(style::palette().muted),
+            )
+            .push(allocation_row(
+                &mut tab_data.allocation_buttons,
+                tab_data.allocation_rule,
+            ))
+            .push(section_title("settings.currency"))
+            .push(currency_section(&mut tab_data.currency))
+            .push(section_title("settings.export"))
+            .push(path_row(
+                &mut tab_data.export_path_input,
+                &tab_data.export_path_value,
+                Message::ExportPathInputChanged,
+                &mut tab_data.export_button,
+                "settings.export_button",
+                Message::ExportButtonPressed,
+            ))
+            .push(section_title("settings.import"))
+            .push(
+                Text::new(tr("settings.import_warning"))
+                    .size(20)
+                    .font(OPEN_SANS)
+                    .color(style::palette().muted),
+            )
+            .push(path_row(
+                &mut tab_data.import_path_input,
+                &tab_data.import_path_value,
+                Message::ImportPathInputChanged,
+                &mut tab_data.import_button,
+                "settings.import_button",
+                Message::ImportButtonPressed,
+            ))
+            .push(section_title("settings.backups"))
+            .push(
+                Row::new()
+                    .padding(10)
+                    .spacing(10)
+                    .push(
+                        TextInput::new(
+                            &mut tab_data.backup_folder_input,
+                            &tr("settings.backup_folder"),
+                            &tab_data.backup_folder_value,
+                            Message::BackupFolderInputChanged,
+                        )
+                        .on_submit(Message::SaveBackupSettingsButtonPressed)
+                        .width(iced::Length::Units(330))
+                        .padding(10)
+                        .size(24)
+                        .font(OPEN_SANS),
+                    )
+                    .push(retention_input(
+                        &mut tab_data.backup_retention_input,
+                        &tab_data.backup_retention_value,
+                        Message::BackupRetentionInputChanged,
+                    ))
+                    .push(
+                        Button::new(
+                            &mut tab_data.save_backup_settings_button,
+                            Text::new(tr("common.save")).size(24).font(OPEN_SANS),
+                        )
+                        .style(style::Button::Confirm)
+                        .padding(10)
+                        .on_press(Message::SaveBackupSettingsButtonPressed),
+                    ),
+            )
+            .push(
+                Container::new(
+                    Button::new(
+                        &mut tab_data.backup_now_button,
+                        Text::new(tr("settings.back_up_now"))
+                            .size(24)
+                            .font(OPEN_SANS),
+                    )
+                    .style(style::Button::Confirm)
+                    .padding(10)
+                    .on_press(Message::BackupNowButtonPressed),
+                )
+                .padding(10),
+            )
+            .push(restore_confirmation)
+            .push(backups_list)
+            .push(section_title("settings.snapshots"))
+            .push(
+                Text::new(tr("settings.snapshots_taken"))
+                    .size(20)
+                    .font(OPEN_SANS)
+                    .color(style::palette().muted),
+            )
+            .push(
+                Row::new()
+                    .padding(10)
+                    .spacing(10)
+                    .align_items(iced::Align::Center)
+                    .push(
+                        Text::new(tr("settings.snapshots_to_keep"))
+                            .size(24)
+                            .font(OPEN_SANS),
+                    )
+                    .push(retention_input(
+                        &mut tab_data.snapshot_retention_input,
+                        &tab_data.snapshot_retention_value,
+                        Message::SnapshotRetentionInputChanged,
+                    ))
+                    .push(
+                        Button::new(
+                            &mut tab_data.save_snapshot_settings_button,
+                            Text::new(tr("common.save")).size(24).font(OPEN_SANS),
+                        )
+                        .style(style::Button::Confirm)
+                        .padding(10)
+                        .on_press(Message::SaveBackupSettingsButtonPressed),
+                    ),
+            )
+            .push(snapshots_list),
+    )
+}
@@ -0,0 +1,285 @@
+//! The tables of members on the home, users and debts tabs. Their
+//! headers stay in place while the rows below them scroll, and
+//! clicking a header sorts the table by that column. The search box
+//! and filters above them are shared, so every table shows the same
+//! members. Clicking the name of a member opens their page. The
+//! columns share the width of the table, which grows with the window
+
+use family_banking_core::model::{
+    Currency, Member, MemberFilter, MemberQuery, MemberSort, SortColumn,
+};
+use iced::{
+    button, canvas, canvas::Cursor, canvas::Frame, canvas::Geometry, canvas::Path, scrollable,
+    text_input, Button, Canvas, Column, Container, HorizontalAlignment, Length, Point, Rectangle,
+    Row, Scrollable, Space, Text, TextInput,
+};
+
+use crate::{
+    family_banking::Message,
+    i18n::tr,
+    main_view::Tab,
+    style::{self, OPEN_SANS, OPEN_SANS_BOLD},
+};
+
+const SEARCH_WIDTH: u16 = 400;
+const ARROW_SIZE: u16 = 12;
+
+/// The widget state of a member table, along with its sort order
+#[derive(Debug, Clone, Default)]
+pub struct MemberTable {
+    pub sort: MemberSort,
+    pub header_buttons: [button::State; 4],
+    pub scroll: scrollable::State,
+    pub search_input: text_input::State,
+    pub filter_buttons: [button::State; 5],
+    /// One for each row, as many as the table has shown
+    pub name_buttons: Vec<button::State>,
+}
+
+impl MemberTable {
+    pub fn new(sort: MemberSort) -> Self {
+        MemberTable {
+            sort,
+            ..MemberTable::default()
+        }
+    }
+}
+
+/// A column of a member table: its label, what it
+/// sorts by and how its values are aligned
+pub type TableColumn<'l> = (&'l str, SortColumn, HorizontalAlignment);
+
+/// The label of a column of amounts, such as "Shares (K)"
+pub fn amount_header(label: &str, currency: &Currency) -> String {
+    if currency.symbol.is_empty() {
+        label.to_string()
+    } else {
+        format!("{} ({})", label, currency.symbol)
+    }
+}
+
+/// A small triangle next to the label of the sorted column, pointing
+/// up when the smallest values are on top and down otherwise
+struct SortArrow {
+    descending: bool,
+}
+
+impl canvas::Program<Message> for SortArrow {
+    fn draw(&self, bounds: Rectangle, _cursor: Cursor) -> Vec<Geometry> {
+        let mut frame = Frame::new(bounds.size());
+        let (width, height) = (frame.width(), frame.height());
+        let (tip, base) = if self.descending {
+            (height, 0.0)
+        } else {
+            (0.0, height)
+        };
+
+        frame.fill(
+            &Path::new(|builder| {
+                builder.move_to(Point::new(width / 2.0, tip));
+                builder.line_to(Point::new(0.0, base));
+                builder.line_to(Point::new(width, base));
+                builder.close();
+            }),
+            style::palette().table_header_text,
+        );
+
+        vec![frame.into_geometry()]
+    }
+}
+
+/// A cell of a member table, the width of one column
+pub fn table_cell<'a>(value: String, alignment: HorizontalAlignment) -> Container<'a, Message> {
+    Container::new(
+        Text::new(value)
+            .width(Length::Fill)
+            .size(28)
+            .font(OPEN_SANS)
+            .horizontal_alignment(alignment),
+    )
+    .width(Length::Fill)
+    .padding(10)
+}
+
+/// The buttons at the end of a row, in a column of their own
+pub fn actions_cell<'a>(buttons: Row<'a, Message>) -> Container<'a, Message> {
+    Container::new(buttons.spacing(5))
+        .width(Length::Fill)
+        .padding(5)
+}
+
+/// The first cell of a row, the name of the member linking to their page
+fn name_cell<'a>(state: &'a mut button::State, member: &Member) -> Button<'a, Message> {
+    Button::new(
+        state,
+        Text::new(member.name.clone())
+            .width(Length::Fill)
+            .size(28)
+            .font(OPEN_SANS),
+    )
+    .width(Length::Fill)
+    .padding(10)
+    .style(style::Button::Icon)
+    .on_press(Message::TabButtonPressed(Tab::Member(member.id)))
+}
+
+/// The catalog key of the label of the button for `filter`
+fn filter_key(filter: MemberFilter) -> &'static str {
+    match filter {
+        MemberFilter::All => "filter.all",
+        MemberFilter::HasDebt => "filter.has_debt",
+        MemberFilter::Overdue => "filter.overdue",
+        MemberFilter::ZeroShares => "filter.zero_shares",
+        MemberFilter::Inactive => "filter.inactive",
+    }
+}
+
+/// The search box and quick filters above a table of members
+fn render_member_search<'a>(
+    search_input: &'a mut text_input::State,
+    filter_buttons: &'a mut [button::State; 5],
+    query: &MemberQuery,
+) -> Column<'a, Message> {
+    let mut filters = Row::new().spacing(5);
+
+    for (filter, state) in MemberFilter::ALL.iter().zip(filter_buttons.iter_mut()) {
+        filters = filters.push(
+            Button::new(
+                state,
+                Text::new(tr(filter_key(*filter))).size(18).font(OPEN_SANS),
+            )
+            .padding(5)
+            .style(if query.filter == *filter {
+                style::Button::Confirm
+            } else {
+                style::Button::Deselected
+            })
+            .on_press(Message::MemberFilterSelected(*filter)),
+        );
+    }
+
+    Column::new()
+        .spacing(5)
+        .push(
+            TextInput::new(
+                search_input,
+                &tr("table.search"),
+                &query.search,
+                Message::MemberSearchChanged,
+            )
+            .padding(5)
+            .size(20)
+            .width(Length::Units(SEARCH_WIDTH))
+            .font(OPEN_SANS),
+        )
+        .push(filters)
+}
+
+/// Draws `rows` under a header of `columns`. Each row starts with the
+/// name of its member, followed by the cells of the other columns and,
+/// if `with_actions` is set, an `actions_cell`. The rows are wrapped in
+/// a scrollable, so that long tables fit in the window. `rows` should
+/// only be those of the members that match `query`
+pub fn render_member_table<'a>(
+    table: &'a mut MemberTable,
+    query: &MemberQuery,
+    columns: &[TableColumn<'_>],
+    with_actions: bool,
+    rows: Vec<(&Member, Row<'a, Message>)>,
+) -> Column<'a, Message> {
+    let MemberTable {
+        sort,
+        header_buttons,
+        scroll,
+        search_input,
+        filter_buttons,
+        name_buttons,
+    } = table;
+
+    if name_buttons.len() < rows.len() {
+        name_buttons.resize_with(rows.len(), button::State::new);
+    }
+
+    // The name takes one part of the width and the other cells the rest
+    let cell_portions = (columns.len() - 1 + usize::from(with_actions)) as u16;
+    let mut header = Row::new().width(Length::Fill);
+
+    for ((label, column, alignment), state) in columns.iter().zip(header_buttons.iter_mut()) {
+        let mut contents = Row::new().align_items(iced::Align::Center).push(
+            Text::new(*label)
+                .size(28)
+                .font(OPEN_SANS_BOLD)
+                .width(Length::Fill)
+                .horizontal_alignment(*alignment),
+        );
+
+        if sort.column == *column {
+            contents = contents.spacing(5).push(
+                Canvas::new(SortArrow {
+                    descending: sort.descending,
+                })
+                .width(Length::Units(ARROW_SIZE))
+                .height(Length::Units(ARROW_SIZE)),
+            );
+        }
+
+        header = header.push(
+            Button::new(state, contents)
+                .width(Length::Fill)
+                .padding(10)
+                .style(style::Button::TableHeader)
+                .on_press(Message::SortColumnPressed(*column)),
+        );
+    }
+
+    if with_actions {
+        header = header.push(Space::with_width(Length::Fill));
+    }
+
+    let mut body = Scrollable::new(scroll).height(Length::Fill);
+
+    if rows.is_empty() {
+        body = body.push(
+            Container::new(
+                Text::new(tr("table.no_match"))
+                    .size(24)
+                    .font(OPEN_SANS)
+                    .color(style::palette().muted),
+            )
+            .padding(10),
+        );
+    }
+
+    for (i, ((member, cells), state)) in rows.into_iter().zip(name_buttons.iter_mut()).enumerate() {
+        let row = Row::new()
+            .width(Length::Fill)
+            .align_items(iced::Align::Center)
+            .push(name_cell(state, member))
+            .push(cells.width(Length::FillPortion(cell_portions)));
+
+        body = body.push(
+            Container::new(row)
+                .width(Length::Fill)
+                .style(if i % 2 == 0 {
+                    style::TableRow::Lighter
+                } else {
+                    style::TableRow::Darker
+                }),
+        );
+    }
+
+    Column::new()
+        .width(Length::Fill)
+        .padding(10)
+        .spacing(10)
+        .push(render_member_search(search_input, filter_buttons, query))
+        .push(
+            Column::new()
+                .push(
+                    Container::new(header)
+                        .width(Length::Fill)
+                        .style(style::TableRow::Header),
+                )
+                .push(body),
+        )
+}
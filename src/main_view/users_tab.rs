@@ -2,24 +2,31 @@ use iced::{button, text_input, Button, Column, Container, Row, Text, TextInput};
 
 use crate::{
     family_banking::Message,
-    format_decimal,
+    i18n::{tr, tr_with},
     style::{self, OPEN_SANS, OPEN_SANS_BOLD},
-    EDIT_PANE_WIDTH, SIDEBAR_WIDTH, WINDOW_WIDTH,
+    TAB_PORTION,
 };
 
-use super::{render_edit_pane, EditingPane, UserDetails};
+use family_banking_core::model::{Currency, Member, MemberQuery, MemberSort, SortColumn};
+
+use super::{
+    render_edit_pane,
+    table::{actions_cell, amount_header, render_member_table, table_cell, MemberTable},
+    EditingPane,
+};
 
 #[derive(Debug, Clone)]
 pub struct UsersTabData {
-    pub user_details: Vec<UserDetails>,
+    pub user_details: Vec<Member>,
     pub edit_pane: EditingPane,
     pub add_user_button: button::State,
     pub edit_button_states: Vec<button::State>,
     pub delete_button_states: Vec<button::State>,
+    pub table: MemberTable,
 }
 
 impl UsersTabData {
-    pub fn new(user_details: Vec<UserDetails>) -> Self {
+    pub fn new(user_details: Vec<Member>, sort: MemberSort) -> Self {
         let mut edit_button_states = Vec::new();
         let mut delete_button_states = Vec::new();
 
@@ -34,6 +41,7 @@ impl UsersTabData {
             delete_button_states,
             add_user_button: button::State::new(),
             edit_pane: EditingPane::default(),
+            table: MemberTable::new(sort),
         }
     }
 }
@@ -57,12 +65,18 @@ pub struct ConfirmDeletion {
 }
 
 pub fn render_users_tab<'a>(
-    add_user_button: &'a mut button::State,
-    user_details: &'a mut Vec<UserDetails>,
-    edit_button_states: &'a mut Vec<button::State>,
-    delete_button_states: &'a mut Vec<button::State>,
-    edit_pane: &'a mut EditingPane,
+    tab_data: &'a mut UsersTabData,
+    query: &MemberQuery,
+    currency: &Currency,
 ) -> Column<'a, Message> {
+    let UsersTabData {
+        user_details,
+        edit_pane,
+        add_user_button,
+        edit_button_states,
+        delete_button_states,
+        table,
+    } = tab_data;
     let mut row = Row::new().push(
         Column::new()
             .padding(20)
@@ -70,265 +84,250 @@ pub fn render_users_tab<'a>(
                 Container::new(
                     Button::new(
                         add_user_button,
-                        Text::new("Add User").size(28).font(OPEN_SANS),
+                        Text::new(tr("users.add_user")).size(28).font(OPEN_SANS),
                     )
                     .style(style::Button::Confirm)
                     .on_press(Message::AddUserButtonPressed),
                 )
                 .padding(10),
             )
-            .push(if user_details.len() > 0 {
-                render_users_list(user_details, edit_button_states, delete_button_states)
+            .push(if !user_details.is_empty() {
+                render_users_list(
+                    user_details,
+                    edit_button_states,
+                    delete_button_states,
+                    table,
+                    query,
+                    currency,
+                )
             } else {
                 Column::new()
             })
-            .width(match edit_pane {
-                EditingPane::Closed => iced::Length::Units(WINDOW_WIDTH - SIDEBAR_WIDTH),
-                _ => iced::Length::Units(WINDOW_WIDTH - SIDEBAR_WIDTH - EDIT_PANE_WIDTH),
-            }),
+            .width(iced::Length::FillPortion(TAB_PORTION)),
     );
+    if let Some(pane) = render_users_pane(edit_pane, user_details) {
+        row = row.push(pane);
+    }
+    Column::new().push(row)
+}
+
+/// The edit pane for adding, editing or deleting a member, if one is open
+pub fn render_users_pane<'a>(
+    edit_pane: &'a mut EditingPane,
+    user_details: &[Member],
+) -> Option<Container<'a, Message>> {
     match edit_pane {
-        EditingPane::Closed => {}
-        EditingPane::AddingUser(edit_user_details) => {
-            row = row.push(render_edit_pane(
-                Column::new()
-                    .push(
-                        Text::new("Add new user")
-                            .font(OPEN_SANS_BOLD)
-                            .size(32)
-                            .color(style::DARK_GREY),
-                    )
-                    .push(
-                        Column::new()
-                            .padding(20)
-                            .push(
-                                TextInput::new(
-                                    &mut edit_user_details.name_input,
-                                    "Username",
-                                    &edit_user_details.name_value,
-                                    Message::EditPaneUserNameInputChanged,
-                                )
-                                .padding(10)
-                                .size(28)
-                                .font(OPEN_SANS),
+        EditingPane::AddingUser(edit_user_details) => Some(render_edit_pane(
+            Column::new()
+                .push(
+                    Text::new(tr("users.add_title"))
+                        .font(OPEN_SANS_BOLD)
+                        .size(32)
+                        .color(style::palette().heading),
+                )
+                .push(
+                    Column::new()
+                        .padding(20)
+                        .push(
+                            TextInput::new(
+                                &mut edit_user_details.name_input,
+                                &tr("users.username"),
+                                &edit_user_details.name_value,
+                                Message::EditPaneUserNameInputChanged,
                             )
-                            .push(
-                                TextInput::new(
-                                    &mut edit_user_details.shares_input,
-                                    "Ammount of shares",
-                                    &edit_user_details.shares_value,
-                                    Message::EditPaneUserShareInputChanged,
-                                )
-                                .padding(10)
-                                .size(28)
-                                .font(OPEN_SANS),
-                            ),
-                    )
-                    .push(
-                        Text::new(edit_user_details.error_message.clone())
+                            .on_submit(Message::EditPaneConfirmButtonClicked)
+                            .padding(10)
                             .size(28)
-                            .font(OPEN_SANS)
-                            .color(style::RED),
-                    )
-                    .push(
-                        Button::new(
-                            &mut edit_user_details.confirm_button,
-                            Text::new("Confirm").size(28).font(OPEN_SANS),
+                            .font(OPEN_SANS),
                         )
-                        .style(style::Button::Confirm)
-                        .on_press(Message::EditPaneConfirmButtonClicked),
-                    ),
-            ))
-        }
-        EditingPane::EditingUser(_user_id, edit_user_details) => {
-            row = row.push(render_edit_pane(
-                Column::new()
-                    .push(
-                        Text::new(format!("Edit User"))
-                            .font(OPEN_SANS_BOLD)
-                            .size(32)
-                            .color(style::DARK_GREY),
-                    )
-                    .push(
-                        Column::new()
-                            .padding(20)
-                            .push(
-                                TextInput::new(
-                                    &mut edit_user_details.name_input,
-                                    "Username",
-                                    &edit_user_details.name_value,
-                                    Message::EditPaneUserNameInputChanged,
-                                )
-                                .size(28)
-                                .padding(10)
-                                .font(OPEN_SANS),
+                        .push(
+                            TextInput::new(
+                                &mut edit_user_details.shares_input,
+                                &tr("users.shares_amount"),
+                                &edit_user_details.shares_value,
+                                Message::EditPaneUserShareInputChanged,
                             )
-                            .push(
-                                TextInput::new(
-                                    &mut edit_user_details.shares_input,
-                                    "Ammount of shares",
-                                    &edit_user_details.shares_value,
-                                    Message::EditPaneUserShareInputChanged,
-                                )
-                                .size(28)
-                                .padding(10)
-                                .font(OPEN_SANS),
-                            ),
-                    )
-                    .push(
-                        Text::new(edit_user_details.error_message.clone())
+                            .on_submit(Message::EditPaneConfirmButtonClicked)
+                            .padding(10)
                             .size(28)
-                            .font(OPEN_SANS)
-                            .color(style::RED),
+                            .font(OPEN_SANS),
+                        ),
+                )
+                .push(
+                    Text::new(edit_user_details.error_message.clone())
+                        .size(28)
+                        .font(OPEN_SANS)
+                        .color(style::palette().danger),
+                )
+                .push(
+                    Button::new(
+                        &mut edit_user_details.confirm_button,
+                        Text::new(tr("common.confirm")).size(28).font(OPEN_SANS),
                     )
-                    .push(
-                        Button::new(
-                            &mut edit_user_details.confirm_button,
-                            Text::new("Confirm").size(28).font(OPEN_SANS),
+                    .style(style::Button::Confirm)
+                    .on_press(Message::EditPaneConfirmButtonClicked),
+                ),
+        )),
+        EditingPane::EditingUser(_user_id, edit_user_details) => Some(render_edit_pane(
+            Column::new()
+                .push(
+                    Text::new(tr("users.edit_title"))
+                        .font(OPEN_SANS_BOLD)
+                        .size(32)
+                        .color(style::palette().heading),
+                )
+                .push(
+                    Column::new()
+                        .padding(20)
+                        .push(
+                            TextInput::new(
+                                &mut edit_user_details.name_input,
+                                &tr("users.username"),
+                                &edit_user_details.name_value,
+                                Message::EditPaneUserNameInputChanged,
+                            )
+                            .on_submit(Message::EditPaneConfirmButtonClicked)
+                            .size(28)
+                            .padding(10)
+                            .font(OPEN_SANS),
                         )
-                        .style(style::Button::Confirm)
-                        .on_press(Message::EditPaneConfirmButtonClicked),
-                    ),
-            ))
-        }
+                        .push(
+                            TextInput::new(
+                                &mut edit_user_details.shares_input,
+                                &tr("users.shares_amount"),
+                                &edit_user_details.shares_value,
+                                Message::EditPaneUserShareInputChanged,
+                            )
+                            .on_submit(Message::EditPaneConfirmButtonClicked)
+                            .size(28)
+                            .padding(10)
+                            .font(OPEN_SANS),
+                        ),
+                )
+                .push(
+                    Text::new(edit_user_details.error_message.clone())
+                        .size(28)
+                        .font(OPEN_SANS)
+                        .color(style::palette().danger),
+                )
+                .push(
+                    Button::new(
+                        &mut edit_user_details.confirm_button,
+                        Text::new(tr("common.confirm")).size(28).font(OPEN_SANS),
+                    )
+                    .style(style::Button::Confirm)
+                    .on_press(Message::EditPaneConfirmButtonClicked),
+                ),
+        )),
         EditingPane::ConfirmingDeletion(ConfirmDeletion {
             user_id,
             delete_button,
             error_message,
             cancel_button,
-        }) => {
-            row = row.push(render_edit_pane(
-                Column::new()
-                    .push(
-                        Text::new(format!(
-                            "Are you sure you want to delete \"{}\"",
-                            user_details
-                                .clone()
-                                .iter()
-                                .find(|user| { user.id == *user_id })
-                                .unwrap()
-                                .name
-                                .clone()
-                        ))
-                        .size(28)
-                        .font(OPEN_SANS),
-                    )
-                    .push(
-                        Row::new()
-                            .push(
-                                Button::new(
-                                    cancel_button,
-                                    Text::new("No").size(28).font(OPEN_SANS),
-                                )
-                                .on_press(Message::CloseEditPane)
-                                .style(style::Button::Confirm),
+        }) => Some(render_edit_pane(
+            Column::new()
+                .push(
+                    Text::new(tr_with(
+                        "users.confirm_delete",
+                        &[&user_details
+                            .iter()
+                            .find(|user| user.id == *user_id)
+                            .unwrap()
+                            .name],
+                    ))
+                    .size(28)
+                    .font(OPEN_SANS),
+                )
+                .push(
+                    Row::new()
+                        .push(
+                            Button::new(
+                                cancel_button,
+                                Text::new(tr("common.no")).size(28).font(OPEN_SANS),
                             )
-                            .push(
-                                Button::new(
-                                    delete_button,
-                                    Text::new("Yes").size(28).font(OPEN_SANS),
-                                )
-                                .on_press(Message::EditPaneConfirmButtonClicked)
-                                .style(style::Button::Destructive),
-                            ),
-                    )
-                    .push(
-                        Text::new(error_message.clone())
-                            .size(28)
-                            .font(OPEN_SANS)
-                            .color(style::RED),
-                    ),
-            ))
-        }
-        _ => {}
+                            .on_press(Message::CloseEditPane)
+                            .style(style::Button::Confirm),
+                        )
+                        .push(
+                            Button::new(
+                                delete_button,
+                                Text::new(tr("common.yes")).size(28).font(OPEN_SANS),
+                            )
+                            .on_press(Message::EditPaneConfirmButtonClicked)
+                            .style(style::Button::Destructive),
+                        ),
+                )
+                .push(
+                    Text::new(error_message.clone())
+                        .size(28)
+                        .font(OPEN_SANS)
+                        .color(style::palette().danger),
+                ),
+        )),
+        _ => None,
     }
-    Column::new().push(row)
 }
 
 pub fn render_users_list<'a>(
-    user_details: &'a Vec<UserDetails>,
-    edit_button_states: &'a mut Vec<button::State>,
-    delete_button_states: &'a mut Vec<button::State>,
+    user_details: &'a [Member],
+    edit_button_states: &'a mut [button::State],
+    delete_button_states: &'a mut [button::State],
+    table: &'a mut MemberTable,
+    query: &MemberQuery,
+    currency: &Currency,
 ) -> Column<'a, Message> {
-    let mut col = Column::new().padding(10).push(
-        Row::new()
-            .push(
-                Container::new(
-                    Text::new("Users")
-                        .size(28)
-                        .font(OPEN_SANS_BOLD)
-                        .width(iced::Length::Units(200)),
-                )
-                .width(iced::Length::Units(200))
-                .padding(10),
-            )
-            .push(
-                Container::new(
-                    Text::new("Shares (K)")
-                        .size(28)
-                        .font(OPEN_SANS_BOLD)
-                        .width(iced::Length::Units(200))
-                        .horizontal_alignment(iced::HorizontalAlignment::Right),
-                )
-                .width(iced::Length::Units(200))
-                .padding(10),
-            ),
-    );
+    let users_header = tr("column.users");
+    let shares_header = amount_header(&tr("column.shares"), currency);
+    let mut rows = Vec::new();
 
-    let mut edit_buttons = Vec::new();
-
-    for (i, state) in edit_button_states.iter_mut().enumerate() {
-        edit_buttons.push(
-            Button::new(state, Text::new("edit").font(OPEN_SANS))
-                .style(style::Button::Icon)
-                .on_press(Message::EditUserButtonPressed(user_details[i].id)),
-        );
-    }
-
-    let mut delete_buttons = Vec::new();
-
-    for (i, state) in delete_button_states.iter_mut().enumerate() {
-        delete_buttons.push(
-            Button::new(state, Text::new("delete").font(OPEN_SANS))
-                .style(style::Button::IconDestructive)
-                .on_press(Message::DeleteUserButtonPressed(user_details[i].id)),
-        );
-    }
-
-    for (i, user) in user_details.iter().enumerate() {
-        col = col.push(
-            Container::new(
-                Row::new()
-                    .push(
-                        Container::new(
-                            Text::new(user.name.clone())
-                                .width(iced::Length::Units(200))
-                                .size(28)
-                                .font(OPEN_SANS),
-                        )
-                        .padding(10),
-                    )
-                    .push(
-                        Container::new(
-                            Text::new(format_decimal(user.contribution))
-                                .width(iced::Length::Units(200))
-                                .horizontal_alignment(iced::HorizontalAlignment::Right)
-                                .size(28)
-                                .font(OPEN_SANS),
+    for ((user, edit_state), delete_state) in user_details
+        .iter()
+        .zip(edit_button_states.iter_mut())
+        .zip(delete_button_states.iter_mut())
+        .filter(|((user, _), _)| query.matches(user))
+    {
+        rows.push((
+            user,
+            Row::new()
+                .push(table_cell(
+                    currency.format_number(user.shares),
+                    iced::HorizontalAlignment::Right,
+                ))
+                .push(actions_cell(
+                    Row::new()
+                        .push(
+                            Button::new(edit_state, Text::new(tr("users.edit")).font(OPEN_SANS))
+                                .style(style::Button::Icon)
+                                .on_press(Message::EditUserButtonPressed(user.id)),
                         )
-                        .width(iced::Length::Units(200))
-                        .padding(10),
-                    )
-                    .push(edit_buttons.remove(0))
-                    .push(delete_buttons.remove(0)),
-            )
-            .style(if i % 2 == 0 {
-                style::TableRow::Lighter
-            } else {
-                style::TableRow::Darker
-            }),
-        )
+                        .push(
+                            Button::new(
+                                delete_state,
+                                Text::new(tr("users.delete")).font(OPEN_SANS),
+                            )
+                            .style(style::Button::IconDestructive)
+                            .on_press(Message::DeleteUserButtonPressed(user.id)),
+                        ),
+                )),
+        ));
     }
 
-    col
+    render_member_table(
+        table,
+        query,
+        &[
+            (
+                &users_header,
+                SortColumn::Name,
+                iced::HorizontalAlignment::Left,
+            ),
+            (
+                &shares_header,
+                SortColumn::Shares,
+                iced::HorizontalAlignment::Right,
+            ),
+        ],
+        true,
+        rows,
+    )
 }
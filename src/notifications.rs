@@ -0,0 +1,157 @@
+//! Short notes of what has just been done, shown above the open
+//! tab for a few seconds so that the tab can still be used meanwhile
+
+use std::{
+    hash::Hash,
+    thread,
+    time::{Duration, Instant},
+};
+
+use iced::{button, futures, Button, Column, Container, Row, Text};
+use iced_native::{
+    event,
+    subscription::{EventStream, Recipe},
+    Event, Hasher,
+};
+
+use crate::{
+    family_banking::Message,
+    i18n::tr,
+    main_view::Tab,
+    style::{self, OPEN_SANS},
+};
+
+/// How long a notification is shown before it goes by itself
+pub const SHOWN_FOR: Duration = Duration::from_secs(6);
+/// The most shown at once. Older ones give way to newer ones
+pub const MAX_SHOWN: usize = 3;
+/// How often shown notifications are checked for having run their time
+const TICK: Duration = Duration::from_millis(500);
+
+/// Where the link of a notification goes: the tab the change can be
+/// seen on and the ledger record it was posted as, if it was, whose
+/// row is highlighted there
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Link {
+    pub tab: Tab,
+    pub record: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub text: String,
+    /// Where the change can be seen, if not on the tab in use
+    pub link: Option<Link>,
+    pub shown_at: Instant,
+    pub link_button: button::State,
+    pub dismiss_button: button::State,
+}
+
+impl Notification {
+    pub fn new(text: String, link: Option<Link>) -> Self {
+        Notification {
+            text,
+            link,
+            shown_at: Instant::now(),
+            link_button: button::State::new(),
+            dismiss_button: button::State::new(),
+        }
+    }
+
+    /// Whether the notification has been shown for long enough at `now`
+    pub fn has_expired(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.shown_at) >= SHOWN_FOR
+    }
+}
+
+/// Adds `notification` to those shown, dropping the oldest beyond `MAX_SHOWN`
+pub fn push(notifications: &mut Vec<Notification>, notification: Notification) {
+    notifications.push(notification);
+
+    if notifications.len() > MAX_SHOWN {
+        notifications.drain(..notifications.len() - MAX_SHOWN);
+    }
+}
+
+pub fn render_notifications(notifications: &mut [Notification]) -> Column<'_, Message> {
+    let mut column = Column::new().spacing(5);
+
+    for (i, notification) in notifications.iter_mut().enumerate() {
+        let mut row = Row::new()
+            .spacing(10)
+            .align_items(iced::Align::Center)
+            .push(
+                Text::new(notification.text.clone())
+                    .size(20)
+                    .font(OPEN_SANS)
+                    .width(iced::Length::Fill),
+            );
+
+        if notification.link.is_some() {
+            row = row.push(
+                Button::new(
+                    &mut notification.link_button,
+                    Text::new(tr("notice.view")).size(20).font(OPEN_SANS),
+                )
+                .style(style::Button::Icon)
+                .on_press(Message::NotificationLinkPressed(i)),
+            );
+        }
+
+        column = column.push(
+            Container::new(
+                row.push(
+                    Button::new(
+                        &mut notification.dismiss_button,
+                        Text::new(tr("notice.dismiss")).size(20).font(OPEN_SANS),
+                    )
+                    .style(style::Button::Icon)
+                    .on_press(Message::DismissNotification(i)),
+                ),
+            )
+            .width(iced::Length::Fill)
+            .padding(10)
+            .style(style::Notification),
+        );
+    }
+
+    column
+}
+
+/// Ticks every `TICK` while notifications are shown, so they
+/// can be taken down once they have run their time
+pub fn ticks() -> iced::Subscription<Message> {
+    iced::Subscription::from_recipe(Ticks(TICK)).map(Message::NotificationTick)
+}
+
+/// A clock on its own thread, as the app runs without an
+/// async runtime that has timers of its own
+struct Ticks(Duration);
+
+impl Recipe<Hasher, (Event, event::Status)> for Ticks {
+    type Output = Instant;
+
+    fn hash(&self, state: &mut Hasher) {
+        std::any::TypeId::of::<Self>().hash(state);
+        self.0.hash(state);
+    }
+
+    fn stream(
+        self: Box<Self>,
+        _input: EventStream,
+    ) -> futures::stream::BoxStream<'static, Instant> {
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+        let interval = self.0;
+
+        // Stops once the subscription is dropped and the receiver with it
+        thread::spawn(move || loop {
+            thread::sleep(interval);
+
+            if sender.unbounded_send(Instant::now()).is_err() {
+                break;
+            }
+        });
+
+        Box::pin(receiver)
+    }
+}
@@ -0,0 +1,139 @@
+//! Shown in place of the login when the books cannot be read at all,
+//! such as when the file is damaged or another program has it open,
+//! so that a backup or snapshot can be restored without logging in
+
+use iced::{button, scrollable, Button, Column, Row, Scrollable, Text};
+use rusqlite::Connection;
+
+use family_banking_core::{
+    backup::{self, BackupFile},
+    Error,
+};
+
+use crate::{
+    family_banking::Message,
+    i18n::{self, tr},
+    main_view::{load_error_keys, settings_tab},
+    style::{self, OPEN_SANS, OPEN_SANS_BOLD},
+};
+
+#[derive(Debug)]
+pub struct RecoveryView {
+    /// Why the books could not be read
+    pub error: Error,
+    pub scroll: scrollable::State,
+    pub retry_button: button::State,
+    pub backups: Vec<BackupFile>,
+    pub restore_button_states: Vec<button::State>,
+    pub snapshots: Vec<BackupFile>,
+    pub restore_snapshot_button_states: Vec<button::State>,
+    /// The backup or snapshot the user has asked to restore,
+    /// while waiting for them to confirm
+    pub pending_restore: Option<BackupFile>,
+    pub confirm_restore_button: button::State,
+    pub cancel_restore_button: button::State,
+    pub error_message: String,
+}
+
+impl RecoveryView {
+    /// The recovery screen for the books open on `conn`, listing the
+    /// backups and snapshots kept for them. The folders are the
+    /// default ones if the settings cannot be read either
+    pub fn new(error: Error, conn: &Connection) -> Self {
+        let backup_settings = backup::backup_settings_or_default(conn);
+        let backups = backup::list_backups(&backup_settings.folder);
+        let snapshots = backup::list_backups(&backup_settings.snapshot_folder());
+
+        RecoveryView {
+            error,
+            scroll: scrollable::State::new(),
+            retry_button: button::State::new(),
+            restore_button_states: backups.iter().map(|_| button::State::new()).collect(),
+            backups,
+            restore_snapshot_button_states: snapshots
+                .iter()
+                .map(|_| button::State::new())
+                .collect(),
+            snapshots,
+            pending_restore: None,
+            confirm_restore_button: button::State::new(),
+            cancel_restore_button: button::State::new(),
+            error_message: String::new(),
+        }
+    }
+}
+
+fn section_title(title: &str) -> Text {
+    Text::new(tr(title))
+        .font(OPEN_SANS_BOLD)
+        .size(28)
+        .color(style::palette().heading)
+}
+
+pub fn render_recovery_view(recovery_view: &mut RecoveryView) -> Column<'_, Message> {
+    let (heading, advice) = load_error_keys(&recovery_view.error);
+    let backups_list = settings_tab::backup_list(
+        &recovery_view.backups,
+        &mut recovery_view.restore_button_states,
+        Message::RestoreBackupButtonPressed,
+        "settings.no_backups",
+    );
+    let snapshots_list = settings_tab::backup_list(
+        &recovery_view.snapshots,
+        &mut recovery_view.restore_snapshot_button_states,
+        Message::RestoreSnapshotButtonPressed,
+        "settings.no_snapshots",
+    );
+
+    Column::new().push(
+        Scrollable::new(&mut recovery_view.scroll)
+            .padding(40)
+            .spacing(20)
+            .height(iced::Length::Fill)
+            .push(
+                Text::new(tr(heading))
+                    .size(28)
+                    .font(OPEN_SANS)
+                    .color(style::palette().danger),
+            )
+            .push(
+                Text::new(i18n::error(&recovery_view.error))
+                    .size(20)
+                    .font(OPEN_SANS),
+            )
+            .push(
+                Text::new(tr(advice))
+                    .size(20)
+                    .font(OPEN_SANS)
+                    .color(style::palette().heading),
+            )
+            .push(
+                Row::new().push(
+                    Button::new(
+                        &mut recovery_view.retry_button,
+                        Text::new(tr("load_error.try_again"))
+                            .size(24)
+                            .font(OPEN_SANS),
+                    )
+                    .style(style::Button::Confirm)
+                    .padding(10)
+                    .on_press(Message::RetryOpeningBooksPressed),
+                ),
+            )
+            .push(
+                Text::new(recovery_view.error_message.clone())
+                    .size(24)
+                    .font(OPEN_SANS)
+                    .color(style::palette().danger),
+            )
+            .push(settings_tab::restore_confirmation(
+                &recovery_view.pending_restore,
+                &mut recovery_view.cancel_restore_button,
+                &mut recovery_view.confirm_restore_button,
+            ))
+            .push(section_title("settings.backups"))
+            .push(backups_list)
+            .push(section_title("settings.snapshots"))
+            .push(snapshots_list),
+    )
+}
@@ -1,3 +1,5 @@
+use std::cell::Cell;
+
 use iced::{button, Background, Color, Font, Vector};
 
 pub const OPEN_SANS: Font = Font::External {
@@ -10,10 +12,171 @@ pub const OPEN_SANS_BOLD: Font = Font::External {
     bytes: include_bytes!("../fonts/OpenSans-Bold.ttf"),
 };
 
-pub const ACCENT_COLOR: Color = Color::from_rgb(0.0, 0.76, 0.04);
-pub const RED: Color = Color::from_rgb(1.0, 0.0, 0.0);
-pub const DARK_GREY: Color = Color::from_rgb(0.3, 0.3, 0.3);
-pub const GREY: Color = Color::from_rgb(0.6, 0.6, 0.6);
+/// The setting the chosen theme is saved under
+pub const THEME_SETTING: &str = "theme";
+
+/// The colours of everything drawn in the window
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    /// Behind the sidebar and the login screen
+    pub background: Color,
+    /// Behind the open tab
+    pub surface: Color,
+    /// The edit pane and every other table row
+    pub raised: Color,
+    /// The table rows between the raised ones
+    pub raised_alt: Color,
+    pub text: Color,
+    pub heading: Color,
+    /// Hints, and text that is not needed right now
+    pub muted: Color,
+    pub accent: Color,
+    /// Text on top of the accent colour
+    pub on_accent: Color,
+    /// Errors, and amounts going out
+    pub danger: Color,
+    /// Behind buttons that cannot be undone
+    pub destructive: Color,
+    pub table_header: Color,
+    pub table_header_text: Color,
+    /// Used one after the other for the series of a chart
+    pub chart: [Color; 8],
+}
+
+const LIGHT: Palette = Palette {
+    background: Color::WHITE,
+    surface: Color::from_rgb(0.95, 0.95, 0.95),
+    raised: Color::WHITE,
+    raised_alt: Color::from_rgb(0.975, 0.975, 0.975),
+    text: Color::BLACK,
+    heading: Color::from_rgb(0.3, 0.3, 0.3),
+    muted: Color::from_rgb(0.6, 0.6, 0.6),
+    accent: Color::from_rgb(0.0, 0.76, 0.04),
+    on_accent: Color::WHITE,
+    danger: Color::from_rgb(1.0, 0.0, 0.0),
+    destructive: Color::from_rgb(0.8, 0.2, 0.2),
+    table_header: Color::from_rgb(0.2, 0.2, 0.2),
+    table_header_text: Color::WHITE,
+    chart: [
+        Color::from_rgb(0.0, 0.76, 0.04),
+        Color::from_rgb(0.13, 0.45, 0.85),
+        Color::from_rgb(0.95, 0.55, 0.1),
+        Color::from_rgb(0.55, 0.3, 0.75),
+        Color::from_rgb(0.85, 0.25, 0.25),
+        Color::from_rgb(0.1, 0.65, 0.65),
+        Color::from_rgb(0.9, 0.75, 0.1),
+        Color::from_rgb(0.3, 0.3, 0.3),
+    ],
+};
+
+const DARK: Palette = Palette {
+    background: Color::from_rgb(0.11, 0.11, 0.12),
+    surface: Color::from_rgb(0.15, 0.15, 0.16),
+    raised: Color::from_rgb(0.2, 0.2, 0.21),
+    raised_alt: Color::from_rgb(0.18, 0.18, 0.19),
+    text: Color::from_rgb(0.9, 0.9, 0.9),
+    heading: Color::from_rgb(0.8, 0.8, 0.8),
+    muted: Color::from_rgb(0.55, 0.55, 0.55),
+    accent: Color::from_rgb(0.2, 0.8, 0.3),
+    on_accent: Color::from_rgb(0.05, 0.05, 0.05),
+    danger: Color::from_rgb(1.0, 0.4, 0.4),
+    destructive: Color::from_rgb(0.7, 0.2, 0.2),
+    table_header: Color::from_rgb(0.05, 0.05, 0.05),
+    table_header_text: Color::from_rgb(0.9, 0.9, 0.9),
+    chart: [
+        Color::from_rgb(0.2, 0.8, 0.3),
+        Color::from_rgb(0.35, 0.6, 0.95),
+        Color::from_rgb(1.0, 0.65, 0.25),
+        Color::from_rgb(0.7, 0.5, 0.9),
+        Color::from_rgb(0.95, 0.4, 0.4),
+        Color::from_rgb(0.3, 0.8, 0.8),
+        Color::from_rgb(0.95, 0.85, 0.3),
+        Color::from_rgb(0.8, 0.8, 0.8),
+    ],
+};
+
+/// Black on white, with nothing in between,
+/// for screens in bright light or poor eyesight
+const HIGH_CONTRAST: Palette = Palette {
+    background: Color::WHITE,
+    surface: Color::WHITE,
+    raised: Color::WHITE,
+    raised_alt: Color::from_rgb(0.9, 0.9, 0.9),
+    text: Color::BLACK,
+    heading: Color::BLACK,
+    muted: Color::from_rgb(0.25, 0.25, 0.25),
+    accent: Color::from_rgb(0.0, 0.3, 0.75),
+    on_accent: Color::WHITE,
+    danger: Color::from_rgb(0.7, 0.0, 0.0),
+    destructive: Color::from_rgb(0.7, 0.0, 0.0),
+    table_header: Color::BLACK,
+    table_header_text: Color::WHITE,
+    chart: [
+        Color::from_rgb(0.0, 0.3, 0.75),
+        Color::BLACK,
+        Color::from_rgb(0.85, 0.35, 0.0),
+        Color::from_rgb(0.45, 0.0, 0.6),
+        Color::from_rgb(0.7, 0.0, 0.0),
+        Color::from_rgb(0.0, 0.45, 0.2),
+        Color::from_rgb(0.6, 0.5, 0.0),
+        Color::from_rgb(0.4, 0.4, 0.4),
+    ],
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Theme {
+    #[default]
+    Light,
+    Dark,
+    HighContrast,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::Light, Theme::Dark, Theme::HighContrast];
+
+    pub fn palette(self) -> &'static Palette {
+        match self {
+            Theme::Light => &LIGHT,
+            Theme::Dark => &DARK,
+            Theme::HighContrast => &HIGH_CONTRAST,
+        }
+    }
+
+    pub fn as_setting(self) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+            Theme::HighContrast => "high contrast",
+        }
+    }
+
+    /// The theme saved as `setting`, or the light
+    /// theme if it is not one that is known
+    pub fn from_setting(setting: &str) -> Theme {
+        Theme::ALL
+            .iter()
+            .copied()
+            .find(|theme| theme.as_setting() == setting)
+            .unwrap_or_default()
+    }
+}
+
+thread_local! {
+    static ACTIVE_THEME: Cell<Theme> = Cell::new(Theme::default());
+}
+
+/// Makes `theme` the one the style sheets and `palette` draw with.
+/// The style sheets are boxed by the widgets that use them, long
+/// after they are chosen, so they look the theme up when they draw
+/// rather than each being handed it
+pub fn set_theme(theme: Theme) {
+    ACTIVE_THEME.with(|active| active.set(theme));
+}
+
+/// The colours of the active theme
+pub fn palette() -> &'static Palette {
+    ACTIVE_THEME.with(|active| active.get().palette())
+}
 
 pub enum Button {
     Deselected,
@@ -21,33 +184,44 @@ pub enum Button {
     Icon,
     IconDestructive,
     Destructive,
+    /// The clickable header of a table column
+    TableHeader,
 }
 
 impl button::StyleSheet for Button {
     fn active(&self) -> button::Style {
+        let palette = palette();
+
         match self {
-            Button::Deselected => button::Style::default(),
+            Button::Deselected => button::Style {
+                text_color: palette.text,
+                ..button::Style::default()
+            },
             Button::Confirm => button::Style {
-                background: Some(Background::Color(ACCENT_COLOR)),
+                background: Some(Background::Color(palette.accent)),
                 border_radius: 3.0,
-                text_color: Color::WHITE,
+                text_color: palette.on_accent,
                 ..button::Style::default()
             },
             Button::Icon => button::Style {
-                text_color: ACCENT_COLOR,
+                text_color: palette.accent,
                 ..button::Style::default()
             },
             Button::IconDestructive => button::Style {
-                text_color: Color::from_rgb(1.0, 0.0, 0.0),
+                text_color: palette.danger,
                 ..button::Style::default()
             },
             Button::Destructive => button::Style {
-                background: Some(Background::Color(Color::from_rgb(0.8, 0.2, 0.2))),
+                background: Some(Background::Color(palette.destructive)),
                 border_radius: 3.0,
                 text_color: Color::WHITE,
                 shadow_offset: Vector::new(1.0, 1.0),
                 ..button::Style::default()
             },
+            Button::TableHeader => button::Style {
+                text_color: palette.table_header_text,
+                ..button::Style::default()
+            },
         }
     }
 
@@ -56,8 +230,9 @@ impl button::StyleSheet for Button {
 
         button::Style {
             text_color: match self {
-                Button::Icon => ACCENT_COLOR,
-                Button::Deselected => ACCENT_COLOR,
+                Button::Icon => palette().accent,
+                Button::Deselected => palette().accent,
+                Button::TableHeader => palette().accent,
                 _ => active.text_color,
             },
             shadow_offset: active.shadow_offset + Vector::new(0.0, 1.0),
@@ -74,11 +249,14 @@ pub enum SidebarButton {
 impl button::StyleSheet for SidebarButton {
     fn active(&self) -> button::Style {
         match self {
-            SidebarButton::Deselected => button::Style::default(),
+            SidebarButton::Deselected => button::Style {
+                text_color: palette().text,
+                ..button::Style::default()
+            },
             SidebarButton::Selected => button::Style {
-                background: Some(Background::Color(ACCENT_COLOR)),
+                background: Some(Background::Color(palette().accent)),
                 border_radius: 0.0,
-                text_color: Color::WHITE,
+                text_color: palette().on_accent,
                 ..button::Style::default()
             },
         }
@@ -89,7 +267,7 @@ impl button::StyleSheet for SidebarButton {
 
         button::Style {
             text_color: match self {
-                SidebarButton::Deselected => ACCENT_COLOR,
+                SidebarButton::Deselected => palette().accent,
                 _ => active.text_color,
             },
             shadow_offset: active.shadow_offset + Vector::new(0.0, 1.0),
@@ -103,7 +281,8 @@ pub struct TabContents;
 impl iced::container::StyleSheet for TabContents {
     fn style(&self) -> iced::container::Style {
         iced::container::Style {
-            background: Some(Background::Color(Color::from_rgb(0.95, 0.95, 0.95))),
+            text_color: Some(palette().text),
+            background: Some(Background::Color(palette().surface)),
             ..iced::container::Style::default()
         }
     }
@@ -114,31 +293,69 @@ pub struct EditPane;
 impl iced::container::StyleSheet for EditPane {
     fn style(&self) -> iced::container::Style {
         iced::container::Style {
-            background: Some(Background::Color(Color::from_rgb(1.0, 1.0, 1.0))),
+            text_color: Some(palette().text),
+            background: Some(Background::Color(palette().raised)),
             ..iced::container::Style::default()
         }
     }
 }
 
+/// A notification of what has just been done
+pub struct Notification;
+
+impl iced::container::StyleSheet for Notification {
+    fn style(&self) -> iced::container::Style {
+        iced::container::Style {
+            text_color: Some(palette().text),
+            background: Some(Background::Color(palette().raised)),
+            border_radius: 3.0,
+            border_width: 2.0,
+            border_color: palette().accent,
+        }
+    }
+}
+
 pub enum TableRow {
     Header,
     Darker,
     Lighter,
+    /// The row a notification's link was followed to
+    Highlighted,
 }
 
 impl iced::container::StyleSheet for TableRow {
     fn style(&self) -> iced::container::Style {
+        let palette = palette();
+
         iced::container::Style {
             text_color: Some(match self {
-                TableRow::Header => Color::WHITE,
-                _ => Color::BLACK,
+                TableRow::Header => palette.table_header_text,
+                _ => palette.text,
             }),
             background: Some(Background::Color(match self {
-                TableRow::Darker => Color::from_rgb(0.975, 0.975, 0.975),
-                TableRow::Lighter => Color::from_rgb(1.0, 1.0, 1.0),
-                TableRow::Header => Color::from_rgb(0.2, 0.2, 0.2),
+                TableRow::Darker => palette.raised_alt,
+                TableRow::Lighter => palette.raised,
+                TableRow::Header => palette.table_header,
+                TableRow::Highlighted => Color {
+                    a: 0.25,
+                    ..palette.accent
+                },
             })),
             ..iced::container::Style::default()
         }
     }
 }
+
+/// The whole window, so that text outside the tabs, such
+/// as on the login screen, is drawn in the theme's colour
+pub struct Window;
+
+impl iced::container::StyleSheet for Window {
+    fn style(&self) -> iced::container::Style {
+        iced::container::Style {
+            text_color: Some(palette().text),
+            background: Some(Background::Color(palette().background)),
+            ..iced::container::Style::default()
+        }
+    }
+}
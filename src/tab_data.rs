@@ -0,0 +1,95 @@
+//! Loads what each tab shows from the books, along with
+//! the widget state the tab needs to draw it
+
+use family_banking_core::{
+    backup, db_operations,
+    model::{Currency, Member, MemberSort},
+    Result,
+};
+use rusqlite::Connection;
+
+use crate::{
+    i18n,
+    main_view::{
+        debts_tab::DebtsTabData, expenses_tab::ExpensesTabData, home_tab::HomeTabData,
+        member_tab::MemberTabData, reconciliation_tab::ReconciliationTabData,
+        settings_tab::SettingsTabData, table::MemberTable, users_tab::UsersTabData,
+    },
+};
+
+/// The names the sort order of each member table is saved under
+pub const HOME_TABLE: &str = "home";
+pub const USERS_TABLE: &str = "users";
+pub const DEBTS_TABLE: &str = "debts";
+
+/// The members, in the order `table` was last sorted in
+fn sorted_members(conn: &Connection, table: &str) -> Result<(Vec<Member>, MemberSort)> {
+    let mut members = db_operations::members(conn)?;
+    let sort = db_operations::member_sort(conn, table)?;
+
+    sort.sort(&mut members);
+    Ok((members, sort))
+}
+
+pub fn home_tab_data(conn: &Connection) -> Result<HomeTabData> {
+    let (user_details, sort) = sorted_members(conn, HOME_TABLE)?;
+
+    Ok(HomeTabData {
+        summary: db_operations::summary(conn)?,
+        totals_history: db_operations::totals_history(conn)?,
+        user_details,
+        table: MemberTable::new(sort),
+        ..HomeTabData::default()
+    })
+}
+
+pub fn users_tab_data(conn: &Connection) -> Result<UsersTabData> {
+    let (user_details, sort) = sorted_members(conn, USERS_TABLE)?;
+
+    Ok(UsersTabData::new(user_details, sort))
+}
+
+pub fn debts_tab_data(conn: &Connection) -> Result<DebtsTabData> {
+    let (user_details, sort) = sorted_members(conn, DEBTS_TABLE)?;
+
+    Ok(DebtsTabData::new(user_details, sort))
+}
+
+pub fn member_tab_data(conn: &Connection, id: i32) -> Result<MemberTabData> {
+    Ok(MemberTabData::new(db_operations::member_details(conn, id)?))
+}
+
+pub fn reconciliation_tab_data(conn: &Connection) -> Result<ReconciliationTabData> {
+    Ok(ReconciliationTabData::new(
+        db_operations::summary(conn)?.total_cash,
+        db_operations::fetch_reconciliations(conn)?,
+    ))
+}
+
+pub fn expenses_tab_data(conn: &Connection) -> Result<ExpensesTabData> {
+    Ok(ExpensesTabData::new(
+        db_operations::fetch_entries(conn)?,
+        db_operations::fetch_categories(conn)?,
+    ))
+}
+
+/// Backups are restored from this tab, so it still opens
+/// when the saved settings cannot be read
+pub fn settings_tab_data(conn: &Connection) -> Result<SettingsTabData> {
+    let (backup_settings, error_message) = match backup::backup_settings(conn) {
+        Ok(backup_settings) => (backup_settings, String::new()),
+        Err(err) => (backup::backup_settings_or_default(conn), i18n::error(&err)),
+    };
+    let backups = backup::list_backups(&backup_settings.folder);
+    let snapshots = backup::list_backups(&backup_settings.snapshot_folder());
+    let (currency, error_message) = match db_operations::currency(conn) {
+        Ok(currency) => (currency, error_message),
+        Err(err) => (Currency::default(), i18n::error(&err)),
+    };
+
+    Ok(SettingsTabData {
+        allocation_rule: db_operations::allocation_rule(conn).unwrap_or_default(),
+        error_message,
+        ..SettingsTabData::new(backup_settings, backups, snapshots, &currency)
+    })
+}
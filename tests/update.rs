@@ -0,0 +1,1417 @@
+//! Plays sequences of messages through the app, as clicking and typing
+//! in the window would, and checks the state after each step
+
+use std::{env, fs, path::PathBuf, time::Instant};
+
+use family_banking::{
+    family_banking::{FamilyBanking, Flags, Message, Status},
+    i18n::{self, Language},
+    keyboard,
+    main_view::{
+        debts_tab::DebtsTabData, home_tab::HomeTabData, member_tab::MemberTabData,
+        users_tab::UsersTabData, EditingPane, MainView, Tab, TabData,
+    },
+    notifications::{self, Link},
+    style::{self, Theme},
+};
+use family_banking_core::{
+    backup::{self, BackupSettings},
+    config::Group,
+    db_operations,
+    error::Rule,
+    model::{
+        AllocationRule, Currency, EntryKind, Member, MemberFilter, SortColumn, SymbolPosition,
+    },
+    Error,
+};
+use iced_native::{
+    event,
+    keyboard::{KeyCode, Modifiers},
+    Event,
+};
+use rusqlite::Connection;
+
+const PASSWORD: &str = "letmein";
+
+/// An app on new books kept in memory. Snapshots go to a folder
+/// of their own in the temp folder rather than next to the tests
+fn new_app(test: &str) -> FamilyBanking {
+    let backup_folder: PathBuf = env::temp_dir().join("family_banking-tests").join(test);
+    let _ = fs::remove_dir_all(&backup_folder);
+
+    let conn = Connection::open_in_memory().unwrap();
+    let group = Group {
+        name: test.to_string(),
+        path: PathBuf::from(":memory:"),
+    };
+    let flags = Flags::from_connection(group.clone(), vec![group], conn);
+
+    assert!(flags.load_error.is_none());
+    backup::store_backup_settings(
+        &flags.db_connection,
+        &BackupSettings {
+            folder: backup_folder,
+            ..BackupSettings::default()
+        },
+    )
+    .unwrap();
+
+    FamilyBanking::from_flags(flags)
+}
+
+/// An app whose admin password has just been set
+fn logged_in_app(test: &str) -> FamilyBanking {
+    let mut app = new_app(test);
+
+    play(
+        &mut app,
+        vec![
+            Message::PasswordInput1Changed(PASSWORD.to_string()),
+            Message::PasswordInput2Changed(PASSWORD.to_string()),
+            Message::NewPasswordButtonPressed,
+        ],
+    );
+    app
+}
+
+fn play(app: &mut FamilyBanking, messages: Vec<Message>) {
+    for message in messages {
+        app.handle(message);
+    }
+}
+
+fn add_user(app: &mut FamilyBanking, name: &str, shares: &str) {
+    play(
+        app,
+        vec![
+            Message::TabButtonPressed(Tab::Users),
+            Message::AddUserButtonPressed,
+            Message::EditPaneUserNameInputChanged(name.to_string()),
+            Message::EditPaneUserShareInputChanged(shares.to_string()),
+            Message::EditPaneConfirmButtonClicked,
+        ],
+    );
+}
+
+fn main_view(app: &FamilyBanking) -> &MainView {
+    match &app.status {
+        Status::LoggedIn(main_view) => main_view,
+        Status::NotLoggedIn(login_view) => {
+            panic!("not logged in: \"{}\"", login_view.login_error_message)
+        }
+        Status::Unreadable(recovery_view) => panic!("books unreadable: {}", recovery_view.error),
+    }
+}
+
+fn login_error(app: &FamilyBanking) -> &str {
+    match &app.status {
+        Status::NotLoggedIn(login_view) => &login_view.login_error_message,
+        Status::LoggedIn(_) => panic!("logged in"),
+        Status::Unreadable(recovery_view) => panic!("books unreadable: {}", recovery_view.error),
+    }
+}
+
+fn home_tab(app: &FamilyBanking) -> &HomeTabData {
+    match &main_view(app).tab_data {
+        TabData::Home(Ok(home_tab_data)) => home_tab_data,
+        other => panic!("not on the home tab: {:?}", other),
+    }
+}
+
+fn users_tab(app: &FamilyBanking) -> &UsersTabData {
+    match &main_view(app).tab_data {
+        TabData::Users(Ok(users_tab_data)) => users_tab_data,
+        other => panic!("not on the users tab: {:?}", other),
+    }
+}
+
+fn debts_tab(app: &FamilyBanking) -> &DebtsTabData {
+    match &main_view(app).tab_data {
+        TabData::Debts(Ok(debts_tab_data)) => debts_tab_data,
+        other => panic!("not on the debts tab: {:?}", other),
+    }
+}
+
+fn member_tab(app: &FamilyBanking) -> &MemberTabData {
+    match &main_view(app).tab_data {
+        TabData::Member(Ok(member_tab_data)) => member_tab_data,
+        other => panic!("not on a member page: {:?}", other),
+    }
+}
+
+/// The message for pressing `key_code` while no input has focus
+fn press(key_code: KeyCode, modifiers: Modifiers) -> Message {
+    keyboard::shortcut(
+        Event::Keyboard(iced_native::keyboard::Event::KeyPressed {
+            key_code,
+            modifiers,
+        }),
+        event::Status::Ignored,
+    )
+    .unwrap()
+}
+
+/// The error shown in the edit pane of the current tab
+fn edit_pane_error(app: &FamilyBanking) -> String {
+    let edit_pane = match &main_view(app).tab_data {
+        TabData::Users(Ok(users_tab_data)) => &users_tab_data.edit_pane,
+        TabData::Debts(Ok(debts_tab_data)) => &debts_tab_data.edit_pane,
+        TabData::Expenses(Ok(expenses_tab_data)) => &expenses_tab_data.edit_pane,
+        TabData::Member(Ok(member_tab_data)) => &member_tab_data.edit_pane,
+        other => panic!("no edit pane on {:?}", other),
+    };
+
+    match edit_pane {
+        EditingPane::AddingUser(details) | EditingPane::EditingUser(_, details) => {
+            details.error_message.clone()
+        }
+        EditingPane::ConfirmingDeletion(confirm_deletion) => confirm_deletion.error_message.clone(),
+        EditingPane::AddingDebt(add_debt) => add_debt.error_message.clone(),
+        EditingPane::RepayingDebt(repay_debt) => repay_debt.error_message.clone(),
+        EditingPane::AddingEntry(add_entry) => add_entry.error_message.clone(),
+        EditingPane::Closed => panic!("the edit pane is closed"),
+    }
+}
+
+#[test]
+fn new_books_ask_for_a_password_of_six_characters() {
+    let mut app = new_app("short_password");
+
+    play(
+        &mut app,
+        vec![
+            Message::PasswordInput1Changed("abc".to_string()),
+            Message::PasswordInput2Changed("abc".to_string()),
+            Message::NewPasswordButtonPressed,
+        ],
+    );
+    assert_eq!(
+        login_error(&app),
+        "Password needs to be at least 6 characters long"
+    );
+
+    play(
+        &mut app,
+        vec![
+            Message::PasswordInput1Changed("abcdef".to_string()),
+            Message::PasswordInput2Changed("abcdeg".to_string()),
+            Message::NewPasswordButtonPressed,
+        ],
+    );
+    assert_eq!(login_error(&app), "Passwords do not match");
+    assert!(app.admin_password.is_none());
+}
+
+#[test]
+fn setting_a_password_logs_in_and_is_needed_next_time() {
+    let mut app = logged_in_app("set_password");
+
+    assert_eq!(main_view(&app).current_tab, Tab::Home);
+    assert!(home_tab(&app).user_details.is_empty());
+    assert!(app.admin_password.is_some());
+
+    play(
+        &mut app,
+        vec![
+            Message::SwitchGroupButtonPressed,
+            Message::PasswordInput1Changed("wrong one".to_string()),
+            Message::LoginButtonPressed,
+        ],
+    );
+    assert_eq!(login_error(&app), "Incorrect password");
+
+    play(
+        &mut app,
+        vec![
+            Message::PasswordInput1Changed(PASSWORD.to_string()),
+            Message::LoginButtonPressed,
+        ],
+    );
+    assert_eq!(main_view(&app).current_tab, Tab::Home);
+}
+
+#[test]
+fn add_user_then_lend_then_repay() {
+    let mut app = logged_in_app("lend_and_repay");
+
+    add_user(&mut app, "Ann", "500");
+
+    let users = users_tab(&app);
+
+    assert!(matches!(users.edit_pane, EditingPane::Closed));
+    assert_eq!(users.user_details.len(), 1);
+    assert_eq!(users.user_details[0].name, "Ann");
+    assert_eq!(users.user_details[0].percent, 100.0);
+
+    play(
+        &mut app,
+        vec![
+            Message::TabButtonPressed(Tab::Debts),
+            Message::AddDebtButtonPressed(1),
+            Message::EditPaneDebtInputChanged("200".to_string()),
+            Message::EditPaneInterestInputChanged("20".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+        ],
+    );
+
+    let ann = &debts_tab(&app).user_details[0];
+
+    assert!(matches!(debts_tab(&app).edit_pane, EditingPane::Closed));
+    assert_eq!((ann.loan, ann.interest), (200.0, 20.0));
+
+    play(
+        &mut app,
+        vec![
+            Message::RepayDebtButtonPressed(1),
+            Message::EditPaneRepaymentInputChanged("250".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+        ],
+    );
+    assert_eq!(edit_pane_error(&app), "Repayment is higher than debt");
+
+    play(
+        &mut app,
+        vec![
+            Message::EditPaneRepaymentInputChanged("210".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+        ],
+    );
+
+    let ann = &debts_tab(&app).user_details[0];
+
+    assert!(matches!(debts_tab(&app).edit_pane, EditingPane::Closed));
+    assert_eq!((ann.loan, ann.interest), (0.0, 10.0));
+
+    app.handle(Message::TabButtonPressed(Tab::Home));
+
+    let summary = home_tab(&app).summary;
+
+    assert_eq!(summary.profit, 10.0);
+    assert_eq!(summary.total_cash, 510.0);
+    assert_eq!(summary.total_debt, 10.0);
+}
+
+#[test]
+fn invalid_input_is_reported_in_the_edit_pane() {
+    let mut app = logged_in_app("invalid_input");
+
+    add_user(&mut app, "", "10");
+    assert_eq!(edit_pane_error(&app), "Enter valid username");
+
+    play(
+        &mut app,
+        vec![
+            Message::EditPaneUserNameInputChanged("Ann".to_string()),
+            Message::EditPaneUserShareInputChanged("ten".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+        ],
+    );
+    assert_eq!(edit_pane_error(&app), "Enter valid number");
+    assert!(!db_operations::has_members(&app.db_connection).unwrap());
+
+    play(
+        &mut app,
+        vec![
+            Message::EditPaneUserShareInputChanged("10".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+            Message::TabButtonPressed(Tab::Debts),
+            Message::AddDebtButtonPressed(1),
+            Message::EditPaneDebtInputChanged("-5".to_string()),
+            Message::EditPaneInterestInputChanged("0".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+        ],
+    );
+    assert_eq!(edit_pane_error(&app), "Loan ammount cannot be negative");
+
+    app.handle(Message::CloseEditPane);
+    assert!(matches!(debts_tab(&app).edit_pane, EditingPane::Closed));
+}
+
+#[test]
+fn editing_and_deleting_a_user() {
+    let mut app = logged_in_app("edit_and_delete");
+
+    add_user(&mut app, "Ann", "500");
+    add_user(&mut app, "Ben", "100");
+
+    play(
+        &mut app,
+        vec![
+            Message::EditUserButtonPressed(2),
+            Message::EditPaneUserNameInputChanged("Benjamin".to_string()),
+            Message::EditPaneUserShareInputChanged("500".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+        ],
+    );
+
+    let names: Vec<&str> = users_tab(&app)
+        .user_details
+        .iter()
+        .map(|user| user.name.as_str())
+        .collect();
+
+    assert_eq!(names, ["Ann", "Benjamin"]);
+    assert_eq!(users_tab(&app).user_details[1].percent, 50.0);
+
+    play(
+        &mut app,
+        vec![
+            Message::DeleteUserButtonPressed(1),
+            Message::EditPaneConfirmButtonClicked,
+        ],
+    );
+
+    let users = users_tab(&app);
+
+    assert_eq!(users.user_details.len(), 1);
+    assert_eq!(users.user_details[0].name, "Benjamin");
+    assert_eq!(users.edit_button_states.len(), 1);
+}
+
+#[test]
+fn expenses_and_income_change_the_profit() {
+    let mut app = logged_in_app("expenses");
+
+    add_user(&mut app, "Ann", "500");
+
+    let categories = db_operations::fetch_categories(&app.db_connection).unwrap();
+    let category = |kind| {
+        categories
+            .iter()
+            .find(|category| category.kind == kind)
+            .cloned()
+            .unwrap()
+    };
+
+    play(
+        &mut app,
+        vec![
+            Message::TabButtonPressed(Tab::Expenses),
+            Message::AddExpenseButtonPressed,
+            Message::EditPaneAmountInputChanged("15".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+        ],
+    );
+    assert_eq!(edit_pane_error(&app), "Select a category");
+
+    play(
+        &mut app,
+        vec![
+            Message::EditPaneCategorySelected(category(EntryKind::Expense)),
+            Message::EditPaneDescriptionInputChanged("Pens".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+            Message::AddIncomeButtonPressed,
+            Message::EditPaneCategorySelected(category(EntryKind::Income)),
+            Message::EditPaneAmountInputChanged("40".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+        ],
+    );
+
+    match &main_view(&app).tab_data {
+        TabData::Expenses(Ok(expenses_tab_data)) => {
+            assert_eq!(expenses_tab_data.entries.len(), 2);
+            assert_eq!(expenses_tab_data.total_expenses, 15.0);
+            assert_eq!(expenses_tab_data.total_income, 40.0);
+        }
+        other => panic!("not on the expenses tab: {:?}", other),
+    }
+
+    app.handle(Message::TabButtonPressed(Tab::Home));
+    assert_eq!(home_tab(&app).summary.profit, 25.0);
+}
+
+#[test]
+fn reconciling_with_an_adjustment_needs_a_reason() {
+    let mut app = logged_in_app("reconciliation");
+
+    add_user(&mut app, "Ann", "500");
+    play(
+        &mut app,
+        vec![
+            Message::TabButtonPressed(Tab::Reconciliation),
+            Message::CountedCashInputChanged("450".to_string()),
+            Message::BankBalanceInputChanged("40".to_string()),
+            Message::PostAdjustmentToggled(true),
+            Message::RecordReconciliationButtonPressed,
+        ],
+    );
+
+    match &main_view(&app).tab_data {
+        TabData::Reconciliation(Ok(reconciliation_tab_data)) => {
+            assert_eq!(reconciliation_tab_data.computed_cash, 500.0);
+            assert_eq!(
+                reconciliation_tab_data.error_message,
+                "Enter a reason for the adjustment"
+            );
+        }
+        other => panic!("not on the cash book tab: {:?}", other),
+    }
+
+    play(
+        &mut app,
+        vec![
+            Message::ReconciliationReasonInputChanged("Miscounted change".to_string()),
+            Message::RecordReconciliationButtonPressed,
+        ],
+    );
+
+    match &main_view(&app).tab_data {
+        TabData::Reconciliation(Ok(reconciliation_tab_data)) => {
+            assert_eq!(reconciliation_tab_data.computed_cash, 490.0);
+            assert_eq!(reconciliation_tab_data.history.len(), 1);
+            assert_eq!(reconciliation_tab_data.history[0].variance(), -10.0);
+        }
+        other => panic!("not on the cash book tab: {:?}", other),
+    }
+}
+
+#[test]
+fn undoing_and_redoing_steps_back_and_forth_through_the_changes() {
+    let mut app = logged_in_app("undo_and_redo");
+
+    add_user(&mut app, "Ann", "500");
+    play(
+        &mut app,
+        vec![
+            Message::TabButtonPressed(Tab::Debts),
+            Message::AddDebtButtonPressed(1),
+            Message::EditPaneDebtInputChanged("200".to_string()),
+            Message::EditPaneInterestInputChanged("20".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+            Message::RepayDebtButtonPressed(1),
+            Message::EditPaneRepaymentInputChanged("210".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+        ],
+    );
+    assert_eq!(app.undo_stack.len(), 2);
+    assert!(app.redo_stack.is_empty());
+
+    let debt = |app: &FamilyBanking| {
+        let ann = &debts_tab(app).user_details[0];
+        (ann.loan, ann.interest)
+    };
+
+    app.handle(Message::UndoButtonPressed);
+    assert_eq!(main_view(&app).current_tab, Tab::Debts);
+    assert_eq!(debt(&app), (200.0, 20.0));
+
+    app.handle(Message::UndoButtonPressed);
+    assert_eq!(debt(&app), (0.0, 0.0));
+    assert_eq!((app.undo_stack.len(), app.redo_stack.len()), (0, 2));
+
+    app.handle(Message::RedoButtonPressed);
+    assert_eq!(debt(&app), (200.0, 20.0));
+
+    // A new change can no longer be followed by the undone repayment
+    play(
+        &mut app,
+        vec![
+            Message::AddDebtButtonPressed(1),
+            Message::EditPaneDebtInputChanged("50".to_string()),
+            Message::EditPaneInterestInputChanged("5".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+        ],
+    );
+    assert_eq!(debt(&app), (250.0, 25.0));
+    assert_eq!(app.undo_stack.len(), 2);
+    assert!(app.redo_stack.is_empty());
+
+    app.handle(Message::RedoButtonPressed);
+    assert_eq!(debt(&app), (250.0, 25.0));
+
+    app.handle(Message::TabButtonPressed(Tab::Home));
+    assert_eq!(home_tab(&app).summary.total_cash, 250.0);
+}
+
+#[test]
+fn an_undo_that_cannot_be_made_is_explained() {
+    let mut app = logged_in_app("undo_error");
+
+    add_user(&mut app, "Ann", "500");
+    play(
+        &mut app,
+        vec![
+            Message::TabButtonPressed(Tab::Debts),
+            Message::AddDebtButtonPressed(1),
+            Message::EditPaneDebtInputChanged("200".to_string()),
+            Message::EditPaneInterestInputChanged("0".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+        ],
+    );
+    // Repaid outside of the app, which the undo stack does not know of
+    db_operations::repay(&app.db_connection, 1, 150.0).unwrap();
+
+    app.handle(Message::UndoButtonPressed);
+    assert_eq!(
+        main_view(&app).undo_error,
+        "Later repayments depend on this, undo them first"
+    );
+    assert_eq!(app.undo_stack.len(), 1);
+}
+
+#[test]
+fn deleting_a_user_clears_the_undo_history() {
+    let mut app = logged_in_app("undo_after_delete");
+
+    add_user(&mut app, "Ann", "500");
+    add_user(&mut app, "Ben", "300");
+    play(
+        &mut app,
+        vec![
+            Message::TabButtonPressed(Tab::Debts),
+            Message::AddDebtButtonPressed(2),
+            Message::EditPaneDebtInputChanged("100".to_string()),
+            Message::EditPaneInterestInputChanged("0".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+            Message::TabButtonPressed(Tab::Users),
+        ],
+    );
+    assert_eq!(app.undo_stack.len(), 1);
+
+    play(
+        &mut app,
+        vec![
+            Message::DeleteUserButtonPressed(1),
+            Message::EditPaneConfirmButtonClicked,
+        ],
+    );
+    assert_eq!(users_tab(&app).user_details.len(), 1);
+    assert!(app.undo_stack.is_empty());
+
+    // Nothing happens without anything to undo
+    app.handle(Message::UndoButtonPressed);
+    assert_eq!(users_tab(&app).user_details.len(), 1);
+    assert!(main_view(&app).undo_error.is_empty());
+}
+
+#[test]
+fn adding_a_user_clears_the_undo_history_instead_of_being_undone() {
+    let mut app = logged_in_app("undo_after_add");
+
+    add_user(&mut app, "Ann", "500");
+    assert!(app.undo_stack.is_empty());
+
+    play(
+        &mut app,
+        vec![
+            Message::TabButtonPressed(Tab::Debts),
+            Message::AddDebtButtonPressed(1),
+            Message::EditPaneDebtInputChanged("100".to_string()),
+            Message::EditPaneInterestInputChanged("0".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+            Message::TabButtonPressed(Tab::Users),
+        ],
+    );
+    assert_eq!(app.undo_stack.len(), 1);
+
+    add_user(&mut app, "Ben", "300");
+    assert!(app.undo_stack.is_empty());
+
+    app.handle(Message::UndoButtonPressed);
+
+    let users = &users_tab(&app).user_details;
+
+    assert_eq!(users.len(), 2);
+    assert_eq!((users[0].loan, users[1].shares), (100.0, 300.0));
+
+    // A member that could not be added leaves the history as it was
+    play(
+        &mut app,
+        vec![
+            Message::TabButtonPressed(Tab::Debts),
+            Message::AddDebtButtonPressed(1),
+            Message::EditPaneDebtInputChanged("50".to_string()),
+            Message::EditPaneInterestInputChanged("0".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+            Message::TabButtonPressed(Tab::Users),
+        ],
+    );
+    add_user(&mut app, "", "10");
+    assert_eq!(app.undo_stack.len(), 1);
+}
+
+#[test]
+fn renaming_a_user_clears_the_undo_history_instead_of_undoing_something_else() {
+    let mut app = logged_in_app("undo_after_rename");
+
+    add_user(&mut app, "Ann", "500");
+    play(
+        &mut app,
+        vec![
+            Message::TabButtonPressed(Tab::Debts),
+            Message::AddDebtButtonPressed(1),
+            Message::EditPaneDebtInputChanged("100".to_string()),
+            Message::EditPaneInterestInputChanged("0".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+            Message::TabButtonPressed(Tab::Users),
+            Message::EditUserButtonPressed(1),
+            Message::EditPaneUserNameInputChanged("Ann Banda".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+        ],
+    );
+    assert!(app.undo_stack.is_empty());
+
+    app.handle(Message::UndoButtonPressed);
+
+    let ann = &users_tab(&app).user_details[0];
+
+    assert_eq!(ann.name, "Ann Banda");
+    assert_eq!(ann.loan, 100.0);
+
+    // Nor is a change to the shares made along with the name undone alone
+    play(
+        &mut app,
+        vec![
+            Message::EditUserButtonPressed(1),
+            Message::EditPaneUserNameInputChanged("Ann Phiri".to_string()),
+            Message::EditPaneUserShareInputChanged("600".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+        ],
+    );
+    assert!(app.undo_stack.is_empty());
+
+    app.handle(Message::UndoButtonPressed);
+
+    let ann = &users_tab(&app).user_details[0];
+
+    assert_eq!((ann.name.as_str(), ann.shares), ("Ann Phiri", 600.0));
+
+    // A change to the shares alone can still be undone
+    play(
+        &mut app,
+        vec![
+            Message::EditUserButtonPressed(1),
+            Message::EditPaneUserShareInputChanged("700".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+        ],
+    );
+    assert_eq!(app.undo_stack.len(), 1);
+
+    app.handle(Message::UndoButtonPressed);
+
+    let ann = &users_tab(&app).user_details[0];
+
+    assert_eq!((ann.name.as_str(), ann.shares), ("Ann Phiri", 600.0));
+}
+
+#[test]
+fn clicking_a_header_sorts_the_table_and_is_remembered_per_tab() {
+    let mut app = logged_in_app("sorting");
+
+    add_user(&mut app, "Ben", "300");
+    add_user(&mut app, "Ann", "100");
+    add_user(&mut app, "Cat", "200");
+
+    let names = |members: &[Member]| -> Vec<String> {
+        members.iter().map(|member| member.name.clone()).collect()
+    };
+
+    assert_eq!(names(&users_tab(&app).user_details), ["Ann", "Ben", "Cat"]);
+
+    app.handle(Message::SortColumnPressed(SortColumn::Shares));
+    assert_eq!(names(&users_tab(&app).user_details), ["Ben", "Cat", "Ann"]);
+
+    app.handle(Message::SortColumnPressed(SortColumn::Shares));
+    assert_eq!(names(&users_tab(&app).user_details), ["Ann", "Cat", "Ben"]);
+
+    // The other tabs keep their own order
+    app.handle(Message::TabButtonPressed(Tab::Home));
+    assert_eq!(names(&home_tab(&app).user_details), ["Ann", "Ben", "Cat"]);
+
+    app.handle(Message::SortColumnPressed(SortColumn::Name));
+    assert_eq!(names(&home_tab(&app).user_details), ["Cat", "Ben", "Ann"]);
+
+    app.handle(Message::TabButtonPressed(Tab::Users));
+    assert_eq!(names(&users_tab(&app).user_details), ["Ann", "Cat", "Ben"]);
+
+    // Buttons still act on the member in their row
+    app.handle(Message::EditUserButtonPressed(
+        users_tab(&app).user_details[1].id,
+    ));
+    match &users_tab(&app).edit_pane {
+        EditingPane::EditingUser(_, details) => assert_eq!(details.name_value, "Cat"),
+        other => panic!("not editing a user: {:?}", other),
+    }
+
+    // The table is still sorted when the order cannot be saved
+    app.db_connection
+        .execute_batch("PRAGMA query_only = ON")
+        .unwrap();
+    app.handle(Message::SortColumnPressed(SortColumn::Name));
+    assert_eq!(names(&users_tab(&app).user_details), ["Ann", "Ben", "Cat"]);
+    assert!(app
+        .notifications
+        .last()
+        .unwrap()
+        .text
+        .starts_with("The table is sorted, but the order could not be saved: "));
+}
+
+#[test]
+fn the_member_search_is_shared_by_every_table() {
+    let mut app = logged_in_app("member_search");
+
+    add_user(&mut app, "Ann", "500");
+    play(
+        &mut app,
+        vec![
+            Message::MemberSearchChanged("an".to_string()),
+            Message::MemberFilterSelected(MemberFilter::HasDebt),
+            Message::TabButtonPressed(Tab::Debts),
+        ],
+    );
+
+    assert_eq!(app.member_query.search, "an");
+    assert_eq!(app.member_query.filter, MemberFilter::HasDebt);
+    assert!(!app.member_query.matches(&debts_tab(&app).user_details[0]));
+
+    play(
+        &mut app,
+        vec![
+            Message::AddDebtButtonPressed(1),
+            Message::EditPaneDebtInputChanged("100".to_string()),
+            Message::EditPaneInterestInputChanged("0".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+            Message::TabButtonPressed(Tab::Home),
+        ],
+    );
+
+    assert_eq!(app.member_query.search, "an");
+    assert!(app.member_query.matches(&home_tab(&app).user_details[0]));
+}
+
+#[test]
+fn everything_for_a_member_can_be_done_from_their_page() {
+    let mut app = logged_in_app("member_page");
+
+    add_user(&mut app, "Ann", "500");
+    add_user(&mut app, "Ben", "100");
+
+    play(
+        &mut app,
+        vec![
+            Message::TabButtonPressed(Tab::Member(1)),
+            Message::AddDebtButtonPressed(1),
+            Message::EditPaneDebtInputChanged("200".to_string()),
+            Message::EditPaneInterestInputChanged("20".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+            Message::RepayDebtButtonPressed(1),
+            Message::EditPaneRepaymentInputChanged("50".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+            Message::EditUserButtonPressed(1),
+            Message::EditPaneUserNameInputChanged("Ann Banda".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+        ],
+    );
+
+    let details = &member_tab(&app).details;
+
+    assert_eq!(main_view(&app).current_tab, Tab::Member(1));
+    assert_eq!(details.member.name, "Ann Banda");
+    assert_eq!(details.member.debt(), 170.0);
+    assert_eq!(details.loans.len(), 1);
+    assert_eq!(details.loans[0].repaid, 50.0);
+    assert_eq!(details.repayments.len(), 1);
+    // The rename has no ledger record to reverse
+    assert!(app.undo_stack.is_empty());
+
+    play(
+        &mut app,
+        vec![
+            Message::MemberPhoneInputChanged(" 0999 123 456 ".to_string()),
+            Message::MemberNotesInputChanged("Treasurer".to_string()),
+            Message::SaveMemberProfilePressed,
+        ],
+    );
+
+    assert!(!member_tab(&app).profile_changed());
+    assert_eq!(member_tab(&app).error_message, "");
+
+    play(
+        &mut app,
+        vec![
+            Message::MemberPhoneInputChanged("call me".to_string()),
+            Message::SaveMemberProfilePressed,
+        ],
+    );
+
+    assert_eq!(member_tab(&app).error_message, "Enter a valid phone number");
+
+    app.handle(Message::TabButtonPressed(Tab::Member(1)));
+
+    assert_eq!(member_tab(&app).details.profile.phone, "0999 123 456");
+    assert_eq!(member_tab(&app).notes_value, "Treasurer");
+
+    // The page of a deleted member is gone, so the users tab opens
+    play(
+        &mut app,
+        vec![
+            Message::DeleteUserButtonPressed(1),
+            Message::EditPaneConfirmButtonClicked,
+        ],
+    );
+
+    assert_eq!(main_view(&app).current_tab, Tab::Users);
+    assert_eq!(users_tab(&app).user_details.len(), 1);
+
+    app.handle(Message::TabButtonPressed(Tab::Member(1)));
+
+    assert!(matches!(main_view(&app).tab_data, TabData::Member(Err(_))));
+}
+
+#[test]
+fn the_chosen_theme_is_used_and_saved_with_the_books() {
+    let mut app = logged_in_app("theme");
+
+    assert_eq!(app.theme, Theme::Light);
+
+    play(
+        &mut app,
+        vec![
+            Message::TabButtonPressed(Tab::Settings),
+            Message::ThemeSelected(Theme::Dark),
+        ],
+    );
+
+    assert_eq!(app.theme, Theme::Dark);
+    assert_eq!(
+        db_operations::get_setting(&app.db_connection, style::THEME_SETTING).unwrap(),
+        Some("dark".to_string())
+    );
+    assert_eq!(Theme::from_setting("dark"), Theme::Dark);
+    assert_eq!(Theme::from_setting("sepia"), Theme::Light);
+    assert_ne!(Theme::Dark.palette(), Theme::Light.palette());
+}
+
+#[test]
+fn the_chosen_language_is_used_for_messages_and_saved_with_the_books() {
+    let mut app = logged_in_app("language");
+
+    assert_eq!(app.language, Language::English);
+
+    play(
+        &mut app,
+        vec![
+            Message::TabButtonPressed(Tab::Settings),
+            Message::LanguageSelected(Language::Chichewa),
+        ],
+    );
+
+    assert_eq!(app.language, Language::Chichewa);
+    assert_eq!(
+        db_operations::get_setting(&app.db_connection, i18n::LANGUAGE_SETTING).unwrap(),
+        Some("ny".to_string())
+    );
+
+    add_user(&mut app, "", "10");
+    assert_eq!(edit_pane_error(&app), "Lowetsani dzina lolondola");
+
+    play(
+        &mut app,
+        vec![
+            Message::EditPaneUserNameInputChanged("Ann".to_string()),
+            Message::EditPaneUserShareInputChanged("ten".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+        ],
+    );
+    assert_eq!(edit_pane_error(&app), "Lowetsani nambala yolondola");
+
+    assert_eq!(i18n::untranslated(Language::Chichewa), Vec::<&str>::new());
+    assert_eq!(i18n::tr("no.such_key"), "no.such_key");
+    assert_eq!(Language::from_setting("ny"), Language::Chichewa);
+    assert_eq!(Language::from_setting("fr"), Language::English);
+}
+
+#[test]
+fn every_rule_of_the_books_is_shown_from_the_catalog_by_its_code() {
+    i18n::set_language(Language::English);
+
+    for rule in [
+        Rule::Username,
+        Rule::Phone,
+        Rule::SharesNegative,
+        Rule::LoanNegative,
+        Rule::InterestNegative,
+        Rule::RepaymentNegative,
+        Rule::RepaymentTooHigh,
+        Rule::AmountNegative,
+        Rule::AmountNotPositive,
+        Rule::AlreadyUndone,
+        Rule::MemberDeleted("Ann".to_string()),
+        Rule::LaterRepayments,
+        Rule::EntryTooOld,
+        Rule::CurrencySymbol,
+        Rule::TooManyDecimals(4),
+        Rule::DecimalSeparator,
+        Rule::ThousandsSeparator,
+        Rule::SameSeparators,
+        Rule::Setting("currency.symbol".to_string()),
+        Rule::Password("too short".to_string()),
+        Rule::GroupName,
+        Rule::GroupExists("Chikondi".to_string()),
+    ] {
+        let err = Error::Validation(rule);
+
+        assert_eq!(i18n::error(&err), err.to_string());
+    }
+
+    for rule in [
+        Rule::OneDatabase,
+        Rule::NeedsPath("--db".to_string()),
+        Rule::UnknownArgument("--fly".to_string()),
+        Rule::NoConfigFolder,
+        Rule::GroupFileMissing("Chikondi".to_string(), "chikondi.db".to_string()),
+        Rule::FileMissing("books.db".to_string()),
+        Rule::FileExists("books.db".to_string()),
+    ] {
+        let err = Error::Usage(rule);
+
+        assert_eq!(i18n::error(&err), err.to_string());
+    }
+
+    i18n::set_language(Language::Chichewa);
+    assert_eq!(
+        i18n::error(&Error::Validation(Rule::MemberDeleted("Ann".to_string()))),
+        "Ann wachotsedwa, bwezeretsani chithunzi kuti mumubweze"
+    );
+    i18n::set_language(Language::English);
+}
+
+#[test]
+fn repayments_are_split_by_the_rule_chosen_in_the_settings() {
+    let mut app = logged_in_app("allocation_rule");
+
+    add_user(&mut app, "Ann", "500");
+    play(
+        &mut app,
+        vec![
+            Message::TabButtonPressed(Tab::Settings),
+            Message::AllocationRuleSelected(AllocationRule::InterestFirst),
+        ],
+    );
+
+    match &main_view(&app).tab_data {
+        TabData::Settings(Ok(settings_tab_data)) => {
+            assert_eq!(
+                settings_tab_data.allocation_rule,
+                AllocationRule::InterestFirst
+            );
+            assert_eq!(settings_tab_data.error_message, "");
+        }
+        _ => panic!("Not on the settings tab"),
+    }
+
+    play(
+        &mut app,
+        vec![
+            Message::TabButtonPressed(Tab::Debts),
+            Message::AddDebtButtonPressed(1),
+            Message::EditPaneDebtInputChanged("200".to_string()),
+            Message::EditPaneInterestInputChanged("20".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+            Message::RepayDebtButtonPressed(1),
+            Message::EditPaneRepaymentInputChanged("30".to_string()),
+        ],
+    );
+
+    // The pane previews the split with the rule the group chose
+    match &debts_tab(&app).edit_pane {
+        EditingPane::RepayingDebt(repay_debt) => {
+            assert_eq!(repay_debt.rule, AllocationRule::InterestFirst)
+        }
+        other => panic!("not repaying a debt: {:?}", other),
+    }
+
+    app.handle(Message::EditPaneConfirmButtonClicked);
+
+    let ann = &debts_tab(&app).user_details[0];
+
+    assert_eq!(ann.loan, 190.0);
+    assert_eq!(ann.interest, 0.0);
+    assert_eq!(
+        db_operations::allocation_rule(&app.db_connection).unwrap(),
+        AllocationRule::InterestFirst
+    );
+}
+
+#[test]
+fn amounts_are_read_and_saved_in_the_chosen_currency() {
+    let mut app = logged_in_app("currency");
+
+    play(
+        &mut app,
+        vec![
+            Message::TabButtonPressed(Tab::Settings),
+            Message::CurrencySymbolInputChanged("€".to_string()),
+            Message::CurrencyPositionSelected(SymbolPosition::After),
+            Message::CurrencyGroupingInputChanged(".".to_string()),
+            Message::SaveCurrencyButtonPressed,
+        ],
+    );
+
+    match &main_view(&app).tab_data {
+        TabData::Settings(Ok(settings_tab_data)) => {
+            assert_eq!(
+                settings_tab_data.error_message,
+                "The thousands and decimal separators must be different"
+            );
+            assert_eq!(settings_tab_data.status_message, "");
+        }
+        _ => panic!("Not on the settings tab"),
+    }
+    assert_eq!(app.currency, Currency::default());
+
+    play(
+        &mut app,
+        vec![
+            Message::CurrencyDecimalSeparatorInputChanged(",".to_string()),
+            Message::SaveCurrencyButtonPressed,
+        ],
+    );
+
+    let euro = Currency {
+        symbol: "€".to_string(),
+        position: SymbolPosition::After,
+        decimals: 2,
+        grouping: Some('.'),
+        decimal_separator: ',',
+    };
+
+    assert_eq!(app.currency, euro);
+    assert_eq!(db_operations::currency(&app.db_connection).unwrap(), euro);
+
+    add_user(&mut app, "Ann", "1.250,50 €");
+
+    assert_eq!(users_tab(&app).user_details[0].shares, 1250.5);
+}
+
+#[test]
+fn the_keyboard_logs_in_moves_between_inputs_and_confirms() {
+    let ctrl = Modifiers {
+        control: true,
+        ..Modifiers::default()
+    };
+    let shift = Modifiers {
+        shift: true,
+        ..Modifiers::default()
+    };
+    let mut app = new_app("keyboard");
+
+    play(
+        &mut app,
+        vec![
+            Message::PasswordInput1Changed(PASSWORD.to_string()),
+            Message::PasswordInput2Changed(PASSWORD.to_string()),
+            press(KeyCode::Enter, Modifiers::default()),
+            press(KeyCode::Key2, ctrl),
+            Message::AddUserButtonPressed,
+            press(KeyCode::Tab, Modifiers::default()),
+        ],
+    );
+    assert_eq!(main_view(&app).current_tab, Tab::Users);
+    assert!(users_tab(&app).table.search_input.is_focused());
+
+    let name_and_shares_focus = |app: &FamilyBanking| match &users_tab(app).edit_pane {
+        EditingPane::AddingUser(details) => (
+            details.name_input.is_focused(),
+            details.shares_input.is_focused(),
+        ),
+        other => panic!("not adding a user: {:?}", other),
+    };
+
+    app.handle(press(KeyCode::Tab, Modifiers::default()));
+    assert_eq!(name_and_shares_focus(&app), (true, false));
+    assert!(!users_tab(&app).table.search_input.is_focused());
+
+    play(
+        &mut app,
+        vec![press(KeyCode::Tab, shift), press(KeyCode::Tab, shift)],
+    );
+    assert_eq!(name_and_shares_focus(&app), (false, true));
+
+    play(
+        &mut app,
+        vec![
+            Message::EditPaneUserNameInputChanged("Ann".to_string()),
+            Message::EditPaneUserShareInputChanged("10".to_string()),
+            press(KeyCode::Enter, Modifiers::default()),
+        ],
+    );
+    assert_eq!(users_tab(&app).user_details.len(), 1);
+
+    let id = users_tab(&app).user_details[0].id;
+
+    play(
+        &mut app,
+        vec![
+            Message::AddUserButtonPressed,
+            press(KeyCode::Escape, Modifiers::default()),
+        ],
+    );
+    assert!(matches!(users_tab(&app).edit_pane, EditingPane::Closed));
+
+    // Deleting is only done with the button
+    play(
+        &mut app,
+        vec![
+            Message::DeleteUserButtonPressed(id),
+            press(KeyCode::Enter, Modifiers::default()),
+        ],
+    );
+    assert_eq!(users_tab(&app).user_details.len(), 1);
+
+    app.handle(press(KeyCode::F, ctrl));
+    assert!(users_tab(&app).table.search_input.is_focused());
+
+    app.handle(press(KeyCode::Key6, ctrl));
+    assert_eq!(main_view(&app).current_tab, Tab::Settings);
+
+    // An input that submits on Enter has already used it
+    assert!(keyboard::shortcut(
+        Event::Keyboard(iced_native::keyboard::Event::KeyPressed {
+            key_code: KeyCode::Enter,
+            modifiers: Modifiers::default(),
+        }),
+        event::Status::Captured,
+    )
+    .is_none());
+}
+
+#[test]
+fn a_notification_says_what_was_done_and_goes_by_itself() {
+    let mut app = logged_in_app("notifications");
+
+    add_user(&mut app, "Chikondi", "10000");
+    assert_eq!(
+        app.notifications[0].text,
+        "Added Chikondi with K10,000.00 in shares"
+    );
+
+    let id = users_tab(&app).user_details[0].id;
+
+    play(
+        &mut app,
+        vec![
+            Message::TabButtonPressed(Tab::Debts),
+            Message::AddDebtButtonPressed(id),
+            Message::EditPaneDebtInputChanged("4000".to_string()),
+            Message::EditPaneInterestInputChanged("1000".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+            Message::RepayDebtButtonPressed(id),
+            Message::EditPaneRepaymentInputChanged("5000".to_string()),
+            Message::EditPaneConfirmButtonClicked,
+        ],
+    );
+    assert_eq!(app.notifications.len(), 3);
+    assert_eq!(
+        app.notifications[2].text,
+        "Repaid K5,000.00 for Chikondi (K4,000.00 principal, K1,000.00 interest)"
+    );
+
+    let repayment = db_operations::last_ledger_id(&app.db_connection).unwrap();
+
+    assert_eq!(
+        app.notifications[2].link,
+        Some(Link {
+            tab: Tab::Member(id),
+            record: repayment,
+        })
+    );
+
+    // The link goes to the member's page, with the repayment highlighted
+    app.handle(Message::NotificationLinkPressed(2));
+    assert_eq!(main_view(&app).current_tab, Tab::Member(id));
+    assert_eq!(member_tab(&app).highlighted_record, repayment);
+    assert_eq!(Some(member_tab(&app).details.repayments[0].id), repayment);
+    assert_eq!(app.notifications.len(), 2);
+
+    app.handle(Message::DismissNotification(0));
+    assert_eq!(app.notifications.len(), 1);
+
+    app.handle(Message::NotificationTick(Instant::now()));
+    assert_eq!(app.notifications.len(), 1);
+
+    app.handle(Message::NotificationTick(
+        Instant::now() + notifications::SHOWN_FOR,
+    ));
+    assert!(app.notifications.is_empty());
+}
+
+#[test]
+fn a_group_list_that_cannot_be_saved_is_noted_on_the_login() {
+    let group = Group {
+        name: "group_list".to_string(),
+        path: PathBuf::from(":memory:"),
+    };
+    let flags = Flags {
+        group_list_error: Some(Error::Usage(Rule::NoConfigFolder)),
+        ..Flags::from_connection(
+            group.clone(),
+            vec![group],
+            Connection::open_in_memory().unwrap(),
+        )
+    };
+    let app = FamilyBanking::from_flags(flags);
+
+    assert!(matches!(app.status, Status::NotLoggedIn(_)));
+    assert_eq!(
+        app.notifications[0].text,
+        "The group list could not be saved: There is no config folder on this system"
+    );
+}
+
+#[test]
+fn damaged_books_open_on_the_recovery_screen_and_a_backup_brings_them_back() {
+    let folder = env::temp_dir()
+        .join("family_banking-tests")
+        .join("recovery");
+    let path = folder.join("books.store");
+    let _ = fs::remove_dir_all(&folder);
+    fs::create_dir_all(&folder).unwrap();
+
+    let conn = Connection::open(&path).unwrap();
+    db_operations::get_password(&conn).unwrap();
+    db_operations::update_db_schema(&conn).unwrap();
+    db_operations::store_new_user(&conn, "Ann".to_string(), 500.0).unwrap();
+    backup::create_backup(&conn).unwrap();
+    drop(conn);
+    fs::write(&path, "Not a database. ".repeat(100)).unwrap();
+
+    let group = Group {
+        name: "recovery".to_string(),
+        path: path.clone(),
+    };
+    let flags =
+        Flags::from_connection(group.clone(), vec![group], Connection::open(&path).unwrap());
+
+    assert!(matches!(flags.load_error, Some(Error::Schema(_))));
+
+    let mut app = FamilyBanking::from_flags(flags);
+
+    match &app.status {
+        Status::Unreadable(recovery_view) => assert_eq!(recovery_view.backups.len(), 1),
+        other => panic!("not on the recovery screen: {:?}", other),
+    }
+
+    // Trying again cannot help a damaged file
+    app.handle(Message::RetryOpeningBooksPressed);
+    assert!(matches!(app.status, Status::Unreadable(_)));
+
+    play(
+        &mut app,
+        vec![
+            Message::RestoreBackupButtonPressed(0),
+            Message::ConfirmRestoreButtonPressed,
+        ],
+    );
+
+    assert!(matches!(app.status, Status::NotLoggedIn(_)));
+    assert!(app.notifications[0].text.starts_with("Restored "));
+    assert_eq!(
+        db_operations::members(&app.db_connection).unwrap()[0].name,
+        "Ann"
+    );
+    // The damaged file is kept beside the restored one
+    assert!(folder.join("books.store.damaged").is_file());
+}
+
+#[test]
+fn every_tab_loads() {
+    let mut app = logged_in_app("every_tab");
+
+    for tab in [
+        Tab::Home,
+        Tab::Users,
+        Tab::Debts,
+        Tab::Expenses,
+        Tab::Reconciliation,
+        Tab::Settings,
+    ] {
+        app.handle(Message::TabButtonPressed(tab));
+
+        let main_view = main_view(&app);
+
+        assert_eq!(main_view.current_tab, tab);
+        assert!(
+            match &main_view.tab_data {
+                TabData::Home(result) => result.is_ok(),
+                TabData::Users(result) => result.is_ok(),
+                TabData::Debts(result) => result.is_ok(),
+                TabData::Expenses(result) => result.is_ok(),
+                TabData::Reconciliation(result) => result.is_ok(),
+                TabData::Settings(result) => result.is_ok(),
+                TabData::Member(result) => result.is_ok(),
+            },
+            "{:?} did not load",
+            tab
+        );
+    }
+}
+
+#[test]
+fn messages_for_another_screen_are_ignored() {
+    let mut app = new_app("ignored_messages");
+
+    play(
+        &mut app,
+        vec![
+            Message::TabButtonPressed(Tab::Users),
+            Message::AddUserButtonPressed,
+            Message::EditPaneConfirmButtonClicked,
+            Message::RepayDebtButtonPressed(1),
+        ],
+    );
+    assert_eq!(login_error(&app), "");
+
+    let mut app = logged_in_app("ignored_messages_logged_in");
+
+    play(
+        &mut app,
+        vec![
+            Message::PasswordInput1Changed("typed".to_string()),
+            Message::AddDebtButtonPressed(1),
+            Message::EditPaneConfirmButtonClicked,
+        ],
+    );
+    assert_eq!(main_view(&app).current_tab, Tab::Home);
+    assert!(home_tab(&app).user_details.is_empty());
+}
+
+#[test]
+fn closing_the_window_exits() {
+    let mut app = logged_in_app("close_window");
+
+    assert!(!app.exiting);
+
+    app.handle(Message::WindowCloseRequested);
+
+    assert!(app.exiting);
+}
+
+#[test]
+fn a_failed_exit_snapshot_keeps_the_window_open_until_closed_again() {
+    let mut app = logged_in_app("close_window_no_snapshot");
+    let not_a_folder = env::temp_dir()
+        .join("family_banking-tests")
+        .join("close_window_no_snapshot.txt");
+
+    fs::create_dir_all(not_a_folder.parent().unwrap()).unwrap();
+    fs::write(&not_a_folder, "").unwrap();
+    backup::store_backup_settings(
+        &app.db_connection,
+        &BackupSettings {
+            folder: not_a_folder,
+            ..BackupSettings::default()
+        },
+    )
+    .unwrap();
+
+    app.handle(Message::WindowCloseRequested);
+    assert!(!app.exiting);
+    assert!(app.notifications[0]
+        .text
+        .starts_with("Could not take a snapshot on exit: "));
+
+    app.handle(Message::WindowCloseRequested);
+    assert!(app.exiting);
+}